@@ -5,6 +5,7 @@ pub const INCHI_PREFIX: &str = "InChI=";
 ///
 /// `c` = connections, `h` = H atoms, `q` = charge, `p` = protons,
 /// `b` = double bond stereo, `t` = tetrahedral stereo, `m` = stereo type,
-/// `s` = stereo bond notation, `i` = isotopes, `f` = fixed H, `r` =
-/// reconnected.
-pub const KNOWN_LAYER_PREFIXES: &[char] = &['c', 'h', 'q', 'p', 'b', 't', 'm', 's', 'i', 'f', 'r'];
+/// `s` = stereo bond notation, `i` = isotopes, `f` = fixed H, `o` =
+/// fixed-H transposition, `r` = reconnected.
+pub const KNOWN_LAYER_PREFIXES: &[char] =
+    &['c', 'h', 'q', 'p', 'b', 't', 'm', 's', 'i', 'f', 'o', 'r'];
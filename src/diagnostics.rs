@@ -0,0 +1,79 @@
+//! Diagnostics collected by `InChI::validate`, which reports every problem
+//! of an InChI string in one pass instead of stopping at the first error.
+
+use alloc::string::String;
+use core::{fmt, ops::Range};
+
+use crate::errors::LayerId;
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The InChI parses, but describes an implausible or non-conforming
+    /// structure.
+    Warning,
+    /// The layer cannot be parsed, and was left out to check the others.
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// A problem found in an InChI string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub(crate) severity: Severity,
+    pub(crate) code: &'static str,
+    pub(crate) message: String,
+    pub(crate) layer: LayerId,
+    pub(crate) span: Option<Range<usize>>,
+}
+
+impl Diagnostic {
+    /// Returns how serious the problem is.
+    #[must_use]
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns a stable, machine-readable code identifying the problem.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// Returns the human-readable description of the problem.
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the layer or sublayer in which the problem occurred.
+    #[must_use]
+    pub fn layer(&self) -> LayerId {
+        self.layer
+    }
+
+    /// Returns the byte range of the input in which the problem occurred,
+    /// if it can be located in the string.
+    #[must_use]
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}] in the {}: {}", self.severity, self.code, self.layer, self.message)?;
+        if let Some(span) = &self.span {
+            write!(f, " at bytes {}..{}", span.start, span.end)?;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,11 @@
 //! Module for InChI-related errors.
 
+use alloc::string::String;
+use core::{
+    fmt::{self, Display, Write},
+    ops::Range,
+};
+
 use molecular_formulas::errors::{NumericError, ParserError};
 
 use crate::impls::main_layer::{
@@ -44,9 +50,57 @@ pub enum Error<Idx> {
     /// Invalid isotope specification in the `/i` layer.
     #[error("Invalid isotope value: '{0}'")]
     InvalidIsotopeValue(char),
+    /// An atom index of the `/i` layer that is zero, has a leading zero or
+    /// is out of range, at the given character.
+    #[error("Invalid isotope atom index at '{0}'")]
+    InvalidIsotopeAtomIndex(char),
+    /// A mass shift of the `/i` layer without digits, with a leading zero or
+    /// out of range, at the given character.
+    #[error("Invalid isotopic mass shift at '{0}'")]
+    InvalidMassShift(char),
+    /// A hydrogen isotope count with a leading zero or out of range, at the
+    /// given character.
+    #[error("Invalid hydrogen isotope count at '{0}'")]
+    InvalidHydrogenIsotopeCount(char),
     /// Invalid stereochemistry value.
     #[error("Invalid stereo value: '{0}'")]
     InvalidStereoValue(char),
+    /// Invalid component transposition in the `/o` sublayer.
+    #[error("Invalid transposition value: '{0}'")]
+    InvalidTranspositionValue(char),
+    /// An atom index exceeds the non-hydrogen atom count of its component.
+    #[error(
+        "Atom index {index} in component {component} of the {layer} exceeds non-hydrogen atom count {num_atoms}"
+    )]
+    AtomIndexOutOfBounds {
+        /// The layer holding the atom index
+        layer: LayerId,
+        /// The 0-based index of the component
+        component: usize,
+        /// The 1-based atom index from the input
+        index: Idx,
+        /// The number of non-hydrogen atoms in the subformula
+        num_atoms: usize,
+    },
+    /// A layer appearing where the InChI layer order does not allow it.
+    #[error("The {layer} cannot follow the {previous}")]
+    UnexpectedLayerOrder {
+        /// The misplaced layer
+        layer: LayerId,
+        /// The layer it follows
+        previous: LayerId,
+    },
+    /// A layer appearing a second time.
+    #[error("The {layer} appears a second time, after the {previous}")]
+    DuplicateLayer {
+        /// The repeated layer
+        layer: LayerId,
+        /// The layer it follows
+        previous: LayerId,
+    },
+    /// A reconnected `/r` layer containing another `/r` layer.
+    #[error("The reconnected layer cannot contain another reconnected layer")]
+    NestedReconnectedLayer,
     /// Unrecognized layer prefix character after the main layer
     #[error("Unrecognized layer prefix: '{0}'")]
     UnrecognizedLayerPrefix(char),
@@ -93,6 +147,29 @@ pub enum AtomConnectionTokenError<Idx> {
     /// Atom index is zero (InChI indices are 1-based)
     #[error("Atom index is zero; InChI uses 1-based indices")]
     ZeroAtomIndex,
+    /// Atom index exceeds the formula's non-hydrogen atom count
+    #[error("Atom index {index} exceeds non-hydrogen atom count {num_atoms}")]
+    AtomIndexOutOfBounds {
+        /// The 1-based atom index from the input
+        index: Idx,
+        /// The number of non-hydrogen atoms in the subformula
+        num_atoms: usize,
+    },
+    /// The same bond is listed twice, e.g. by a ring closure repeating an
+    /// existing bond
+    #[error("Duplicate bond between atoms {0} and {1}")]
+    DuplicateEdge(Idx, Idx),
+    /// The molecular graph could not be built from the parsed bonds
+    #[error("The molecular graph could not be built from the bonds")]
+    InvalidGraph,
+    /// An atom of a multi-atom component has no bonds at all
+    #[error("Atom {0} has no bonds, but disconnected fragments must be separate components")]
+    IsolatedAtom(Idx),
+    /// The bonds of a component form several disconnected fragments
+    #[error(
+        "Atom {0} is not connected to atom 1, but disconnected fragments must be separate components"
+    )]
+    DisconnectedGraph(Idx),
 }
 
 #[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
@@ -133,3 +210,447 @@ pub enum HydrogenLayerTokenError<Idx> {
     #[error("Invalid range: {0} > {1}")]
     InvalidRange(Idx, Idx),
 }
+
+impl<Idx> Error<Idx> {
+    /// Returns a stable, machine-readable code identifying the kind of error.
+    ///
+    /// Errors wrapping a tokenizer error return the code of the wrapped error.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::MissingInchiPrefix => "missing_inchi_prefix",
+            Error::MissingVersionPrefix => "missing_version_prefix",
+            Error::MissingForwardSlash(_) => "missing_forward_slash",
+            Error::MolecularFormulaParserError(_) => "invalid_formula",
+            Error::WrongPrefix => "wrong_prefix",
+            Error::FormulaAndConnectionLayerMixtureMismatch(_) => "component_count_mismatch",
+            Error::AtomConnectionTokenError(error) => error.code(),
+            Error::TryFromIntError(_) => "integer_out_of_range",
+            Error::HydrogenLayerTokenError(error) => error.code(),
+            Error::InvalidChargeValue(_) => "invalid_charge",
+            Error::InvalidIsotopeValue(_) => "invalid_isotope",
+            Error::InvalidIsotopeAtomIndex(_) => "invalid_isotope_atom_index",
+            Error::InvalidMassShift(_) => "invalid_mass_shift",
+            Error::InvalidHydrogenIsotopeCount(_) => "invalid_hydrogen_isotope_count",
+            Error::InvalidStereoValue(_) => "invalid_stereo",
+            Error::InvalidTranspositionValue(_) => "invalid_transposition",
+            Error::AtomIndexOutOfBounds { .. } => "atom_index_out_of_bounds",
+            Error::UnexpectedLayerOrder { .. } => "unexpected_layer_order",
+            Error::DuplicateLayer { .. } => "duplicate_layer",
+            Error::NestedReconnectedLayer => "nested_reconnected_layer",
+            Error::UnrecognizedLayerPrefix(_) => "unrecognized_layer_prefix",
+        }
+    }
+}
+
+impl<Idx> AtomConnectionTokenError<Idx> {
+    /// Returns a stable, machine-readable code identifying the kind of error.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            AtomConnectionTokenError::NumericError(_) => "connection_numeric_error",
+            AtomConnectionTokenError::InvalidCharacter(_) => "connection_invalid_character",
+            AtomConnectionTokenError::UnderlyingIteratorEmpty => "connection_empty_input",
+            AtomConnectionTokenError::ConsecutiveAtomIndices => "connection_consecutive_indices",
+            AtomConnectionTokenError::IllegalConsecutiveSubTokens { .. } => {
+                "connection_illegal_token_sequence"
+            }
+            AtomConnectionTokenError::UnexpectedEndOfInput(_) => "connection_unexpected_end",
+            AtomConnectionTokenError::ClosingBracketBeforeOpeningBracket => {
+                "connection_unbalanced_bracket"
+            }
+            AtomConnectionTokenError::CommaBeforeAnyEdge => "connection_comma_before_edge",
+            AtomConnectionTokenError::IllegalStartingToken(_) => "connection_illegal_start",
+            AtomConnectionTokenError::SelfLoopDetected(_) => "connection_self_loop",
+            AtomConnectionTokenError::ZeroAtomIndex => "connection_zero_index",
+            AtomConnectionTokenError::AtomIndexOutOfBounds { .. } => {
+                "connection_index_out_of_bounds"
+            }
+            AtomConnectionTokenError::DuplicateEdge(..) => "connection_duplicate_edge",
+            AtomConnectionTokenError::InvalidGraph => "connection_invalid_graph",
+            AtomConnectionTokenError::IsolatedAtom(_) => "connection_isolated_atom",
+            AtomConnectionTokenError::DisconnectedGraph(_) => "connection_disconnected_graph",
+        }
+    }
+}
+
+impl<Idx> HydrogenLayerTokenError<Idx> {
+    /// Returns a stable, machine-readable code identifying the kind of error.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            HydrogenLayerTokenError::NumericError(_) => "hydrogen_numeric_error",
+            HydrogenLayerTokenError::InvalidCharacter(_) => "hydrogen_invalid_character",
+            HydrogenLayerTokenError::IllegalConsecutiveSubTokens { .. } => {
+                "hydrogen_illegal_token_sequence"
+            }
+            HydrogenLayerTokenError::UnexpectedEndOfInput(_) => "hydrogen_unexpected_end",
+            HydrogenLayerTokenError::TryFromIntError(_) => "hydrogen_count_out_of_range",
+            HydrogenLayerTokenError::ZeroAtomIndex => "hydrogen_zero_index",
+            HydrogenLayerTokenError::AtomIndexOutOfBounds { .. } => "hydrogen_index_out_of_bounds",
+            HydrogenLayerTokenError::InvalidRange(..) => "hydrogen_invalid_range",
+        }
+    }
+}
+
+/// Identifies the layer or sublayer of an InChI in which a parse error
+/// occurred.
+///
+/// Sublayers are identified by their own prefix, so the `/t` sublayer of the
+/// main, isotopic or fixed-H stereochemistry is always
+/// [`LayerId::TetrahedralStereo`]; the span of the error tells them apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LayerId {
+    /// The `InChI=` prefix and the version.
+    Version,
+    /// The chemical formula, in the main or in the `/f` layer.
+    Formula,
+    /// The `/c` atom connection sublayer.
+    Connections,
+    /// The `/h` hydrogen sublayer, including the isotopic `/h`.
+    Hydrogens,
+    /// The `/q` charge layer.
+    Charge,
+    /// The `/p` proton layer.
+    Protons,
+    /// The `/b` double bond stereo sublayer.
+    DoubleBondStereo,
+    /// The `/t` tetrahedral stereo sublayer.
+    TetrahedralStereo,
+    /// The `/m` stereo inversion sublayer.
+    StereoInversion,
+    /// The `/s` stereo type sublayer.
+    StereoType,
+    /// The `/i` isotope layer.
+    Isotope,
+    /// The `/f` fixed-H layer.
+    FixedHydrogen,
+    /// The `/o` fixed-H transposition sublayer.
+    Transposition,
+    /// The `/r` reconnected layer.
+    Reconnected,
+    /// A segment that does not start with a known layer prefix.
+    Unknown,
+}
+
+impl LayerId {
+    /// Identifies the layer of a `/`-separated segment from its prefix.
+    pub(crate) fn from_segment(segment: &str) -> Self {
+        match segment.chars().next() {
+            Some('c') => LayerId::Connections,
+            Some('h') => LayerId::Hydrogens,
+            Some('q') => LayerId::Charge,
+            Some('p') => LayerId::Protons,
+            Some('b') => LayerId::DoubleBondStereo,
+            Some('t') => LayerId::TetrahedralStereo,
+            Some('m') => LayerId::StereoInversion,
+            Some('s') => LayerId::StereoType,
+            Some('i') => LayerId::Isotope,
+            Some('f') => LayerId::FixedHydrogen,
+            Some('o') => LayerId::Transposition,
+            Some('r') => LayerId::Reconnected,
+            Some(first) if first.is_ascii_uppercase() || first.is_ascii_digit() => LayerId::Formula,
+            _ => LayerId::Unknown,
+        }
+    }
+
+    /// Returns the prefix character of the layer, if it has one.
+    #[must_use]
+    pub fn prefix(self) -> Option<char> {
+        match self {
+            LayerId::Connections => Some('c'),
+            LayerId::Hydrogens => Some('h'),
+            LayerId::Charge => Some('q'),
+            LayerId::Protons => Some('p'),
+            LayerId::DoubleBondStereo => Some('b'),
+            LayerId::TetrahedralStereo => Some('t'),
+            LayerId::StereoInversion => Some('m'),
+            LayerId::StereoType => Some('s'),
+            LayerId::Isotope => Some('i'),
+            LayerId::FixedHydrogen => Some('f'),
+            LayerId::Transposition => Some('o'),
+            LayerId::Reconnected => Some('r'),
+            LayerId::Version | LayerId::Formula | LayerId::Unknown => None,
+        }
+    }
+}
+
+impl Display for LayerId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LayerId::Version => "version prefix",
+            LayerId::Formula => "formula",
+            LayerId::Connections => "connection layer",
+            LayerId::Hydrogens => "hydrogen layer",
+            LayerId::Charge => "charge layer",
+            LayerId::Protons => "proton layer",
+            LayerId::DoubleBondStereo => "double bond stereo layer",
+            LayerId::TetrahedralStereo => "tetrahedral stereo layer",
+            LayerId::StereoInversion => "stereo inversion layer",
+            LayerId::StereoType => "stereo type layer",
+            LayerId::Isotope => "isotope layer",
+            LayerId::FixedHydrogen => "fixed-H layer",
+            LayerId::Transposition => "transposition layer",
+            LayerId::Reconnected => "reconnected layer",
+            LayerId::Unknown => "unknown layer",
+        };
+        match self.prefix() {
+            Some(prefix) => write!(f, "{name} (/{prefix})"),
+            None => f.write_str(name),
+        }
+    }
+}
+
+/// An error raised by a layer parser, with the byte range of the fault in
+/// the parsed text when the parser can point at it.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct LayerError<Idx> {
+    pub(crate) kind: Error<Idx>,
+    pub(crate) fault: Option<Range<usize>>,
+}
+
+impl<Idx> LayerError<Idx> {
+    /// Creates an error on the bytes `fault` of the parsed text.
+    pub(crate) fn at(kind: impl Into<Error<Idx>>, fault: Range<usize>) -> Self {
+        Self { kind: kind.into(), fault: Some(fault) }
+    }
+
+    /// Creates an error on the character of `text` starting at byte
+    /// `offset`, or on the end of `text` when `offset` is past its last
+    /// character.
+    pub(crate) fn at_char(kind: impl Into<Error<Idx>>, text: &str, offset: usize) -> Self {
+        let width =
+            text.get(offset..).and_then(|rest| rest.chars().next()).map_or(0, char::len_utf8);
+        Self::at(kind, offset..offset + width)
+    }
+
+    /// Makes an error raised while parsing `inner`, a subslice of `outer`,
+    /// relative to `outer`. An error without a fault is placed on the whole
+    /// of `inner`.
+    #[must_use]
+    pub(crate) fn within(self, outer: &str, inner: &str) -> Self {
+        let offset = (inner.as_ptr() as usize).saturating_sub(outer.as_ptr() as usize);
+        let fault = self.fault.unwrap_or(0..inner.len());
+        Self { kind: self.kind, fault: Some(fault.start + offset..fault.end + offset) }
+    }
+
+    /// Returns what went wrong, dropping the fault.
+    #[cfg(test)]
+    pub(crate) fn into_kind(self) -> Error<Idx> {
+        self.kind
+    }
+}
+
+impl<Idx> From<Error<Idx>> for LayerError<Idx> {
+    fn from(kind: Error<Idx>) -> Self {
+        Self { kind, fault: None }
+    }
+}
+
+impl<Idx> From<ParserError> for LayerError<Idx> {
+    fn from(error: ParserError) -> Self {
+        Error::from(error).into()
+    }
+}
+
+impl<Idx> From<core::num::TryFromIntError> for LayerError<Idx> {
+    fn from(error: core::num::TryFromIntError) -> Self {
+        Error::from(error).into()
+    }
+}
+
+impl<Idx> From<AtomConnectionTokenError<Idx>> for LayerError<Idx> {
+    fn from(error: AtomConnectionTokenError<Idx>) -> Self {
+        Error::from(error).into()
+    }
+}
+
+impl<Idx> From<HydrogenLayerTokenError<Idx>> for LayerError<Idx> {
+    fn from(error: HydrogenLayerTokenError<Idx>) -> Self {
+        Error::from(error).into()
+    }
+}
+
+impl From<ParseError> for LayerError<u16> {
+    /// Keeps the span of a located error as the fault, for layers parsed
+    /// with the top-level parser, such as the reconnected layer.
+    fn from(error: ParseError) -> Self {
+        Self { kind: error.kind, fault: Some(error.span) }
+    }
+}
+
+/// An error raised while parsing an InChI string, located in the input.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("{kind} [{}] in the {layer} at bytes {}..{}", kind.code(), span.start, span.end)]
+pub struct ParseError {
+    kind: Error<u16>,
+    span: Range<usize>,
+    layer: LayerId,
+}
+
+impl ParseError {
+    /// Creates an error located at `span` of the input, in `layer`.
+    pub(crate) fn new(kind: Error<u16>, span: Range<usize>, layer: LayerId) -> Self {
+        Self { kind, span, layer }
+    }
+
+    /// Creates an error located in the `/`-separated segment at the start of
+    /// `remainder`, which must be a suffix of `input`: on the fault of the
+    /// error within the segment when the layer parser found one, and on the
+    /// whole segment otherwise.
+    pub(crate) fn at_segment(
+        error: impl Into<LayerError<u16>>,
+        input: &str,
+        remainder: &str,
+    ) -> Self {
+        let LayerError { kind, fault } = error.into();
+        let start = input.len() - remainder.len();
+        let segment = remainder.split('/').next().unwrap_or_default();
+        let span = fault.map_or(0..segment.len(), |fault| {
+            fault.start.min(remainder.len())..fault.end.min(remainder.len())
+        });
+        Self::new(kind, start + span.start..start + span.end, LayerId::from_segment(segment))
+    }
+
+    /// Shifts the span of the error by `offset` bytes, to make it relative
+    /// to an enclosing input.
+    #[must_use]
+    pub(crate) fn offset_by(mut self, offset: usize) -> Self {
+        self.span = self.span.start + offset..self.span.end + offset;
+        self
+    }
+
+    /// Replaces the span of the error with its image through `map`, to make
+    /// it relative to an input that was rewritten before parsing.
+    #[must_use]
+    pub(crate) fn map_span(mut self, map: impl FnOnce(Range<usize>) -> Range<usize>) -> Self {
+        self.span = map(self.span);
+        self
+    }
+
+    /// Returns what went wrong.
+    #[must_use]
+    pub fn kind(&self) -> &Error<u16> {
+        &self.kind
+    }
+
+    /// Returns what went wrong, consuming the located error.
+    #[must_use]
+    pub fn into_kind(self) -> Error<u16> {
+        self.kind
+    }
+
+    /// Returns the byte range of the input in which the error occurred.
+    #[must_use]
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Returns the layer or sublayer in which the error occurred.
+    #[must_use]
+    pub fn layer(&self) -> LayerId {
+        self.layer
+    }
+
+    /// Returns a stable, machine-readable code identifying the kind of error.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    /// Renders the input with carets under the span of the error, followed by
+    /// the error message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::inchi::InChI;
+    ///
+    /// let inchi = "InChI=1S/C2H6O/c1-2-3/q+x";
+    /// let error = inchi.parse::<InChI>().unwrap_err();
+    /// assert_eq!(
+    ///     error.render(inchi),
+    ///     "InChI=1S/C2H6O/c1-2-3/q+x\n                        ^ invalid_charge: Invalid charge value: 'x'"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn render(&self, input: &str) -> String {
+        let column = input.get(..self.span.start).map_or(self.span.start, |s| s.chars().count());
+        let width = input.get(self.span.clone()).map_or(0, |s| s.chars().count()).max(1);
+        let mut rendered = String::from(input);
+        rendered.push('\n');
+        rendered.extend(core::iter::repeat_n(' ', column));
+        rendered.extend(core::iter::repeat_n('^', width));
+        let _ = write!(rendered, " {}: {}", self.code(), self.kind);
+        rendered
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+/// Errors that can occur while parsing an InChIKey.
+pub enum InChIKeyError {
+    /// The key does not have 27 characters.
+    #[error("InChIKey must have 27 characters, found {0}")]
+    InvalidLength(usize),
+    /// A block separator is not a hyphen.
+    #[error("Expected '-' at position {position}, found '{found}'")]
+    MissingHyphen {
+        /// The 0-based position of the separator
+        position: usize,
+        /// The character found instead
+        found: char,
+    },
+    /// A block contains a character other than an uppercase ASCII letter.
+    #[error("Invalid character '{found}' at position {position}")]
+    InvalidCharacter {
+        /// The 0-based position of the character
+        position: usize,
+        /// The invalid character
+        found: char,
+    },
+    /// The standard flag is neither `S` nor `N`.
+    #[error("Invalid standard flag '{0}', expected 'S' or 'N'")]
+    InvalidStandardFlag(char),
+    /// The version is not a supported InChI version.
+    #[error("Unsupported InChIKey version '{0}'")]
+    UnsupportedVersion(char),
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+/// Errors that can occur while reading the atoms of a component.
+pub enum ComponentError {
+    /// The formula has more atoms than a 16-bit atom index can address.
+    #[error("Component {component} has {atoms} atoms, more than an atom index can address")]
+    TooManyAtoms {
+        /// The 0-based index of the component
+        component: usize,
+        /// The number of atoms in the formula of the component
+        atoms: usize,
+    },
+}
+
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+/// Errors that can occur while assigning bond orders to a component.
+pub enum KekulizationError {
+    /// The atoms of the component could not be read from its formula.
+    #[error(transparent)]
+    Component(#[from] ComponentError),
+    /// The component has several atoms but the InChI has no `/c` layer.
+    #[error("Component {component} has several atoms but no connection layer")]
+    MissingConnections {
+        /// The 0-based index of the component
+        component: usize,
+    },
+    /// No bond orders satisfy the valences, hydrogens and charges.
+    #[error("No bond order assignment satisfies the valences of component {component}")]
+    NoValidAssignment {
+        /// The 0-based index of the component
+        component: usize,
+    },
+    /// The search gave up before finding an assignment or ruling one out.
+    #[error("The bond order search for component {component} exceeded its step limit")]
+    SearchLimitExceeded {
+        /// The 0-based index of the component
+        component: usize,
+    },
+}
@@ -1,6 +1,18 @@
+mod aromaticity;
 pub(crate) mod charge_layer;
+mod diagnostics;
+pub(crate) mod display;
+mod fixed_hydrogen;
 mod from_str;
+mod inchi_key;
 pub(crate) mod isotope_layer;
+mod kekule;
+mod layer_order;
 pub(crate) mod main_layer;
+mod parse_options;
 pub(crate) mod proton_layer;
+mod reconnected_layer;
+pub(crate) mod rings;
 pub(crate) mod stereochemistry_layer;
+pub(crate) mod valences;
+mod validation;
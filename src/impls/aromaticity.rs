@@ -0,0 +1,253 @@
+//! Perception of aromaticity on a Kekulé structure.
+
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+
+use elements_rs::Element;
+
+use crate::{
+    errors::KekulizationError,
+    impls::{
+        rings::{ring_bonds, smallest_rings},
+        valences::valences,
+    },
+    inchi::{Aromaticity, BondOrder, Component, InChI, KekuleStructure},
+    version::Version,
+};
+
+/// Returns whether `electrons` π electrons satisfy Hückel's 4n + 2 rule.
+fn is_huckel(electrons: usize) -> bool {
+    electrons >= 2 && (electrons - 2).is_multiple_of(4)
+}
+
+/// Returns the π electrons `atom` contributes to its rings, or `None` when it
+/// cannot be aromatic.
+fn contribution(
+    elements: &[Element],
+    structure: &KekuleStructure,
+    in_ring: &[bool],
+    atom: usize,
+) -> Option<usize> {
+    let mut degree = 0;
+    let mut double_bond = None;
+    for (bond, &((a, b), order)) in structure.bonds().iter().enumerate() {
+        let neighbour = match (usize::from(a), usize::from(b)) {
+            (a, b) if a == atom => b,
+            (a, b) if b == atom => a,
+            _ => continue,
+        };
+        degree += 1;
+        match order {
+            BondOrder::Single => {}
+            BondOrder::Double if double_bond.is_none() => {
+                double_bond = Some((neighbour, in_ring[bond]));
+            }
+            BondOrder::Double | BondOrder::Triple => return None,
+        }
+    }
+    match double_bond {
+        Some((_, true)) => Some(1),
+        Some((neighbour, false)) => (elements[neighbour] != Element::C).then_some(0),
+        None => {
+            // The non-bonding electrons left on a main group atom with single
+            // bonds only: an empty p orbital, as in borole, or a lone pair.
+            let charge = structure.charges()[atom];
+            valences(elements[atom], charge)?;
+            let connections = i16::try_from(degree + usize::from(structure.hydrogens()[atom]))
+                .unwrap_or(i16::MAX);
+            let electrons = i16::from(elements[atom].valence_electrons()) - charge - connections;
+            match (connections, electrons) {
+                (3, 0) => Some(0),
+                (3, 2) | (2, 4) => Some(2),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Returns the bonds of `ring`, with the smaller atom first.
+fn ring_bond_set(ring: &[u16]) -> BTreeSet<(u16, u16)> {
+    ring.iter().zip(ring.iter().cycle().skip(1)).map(|(&a, &b)| (a.min(b), a.max(b))).collect()
+}
+
+/// Perceives the aromatic atoms, bonds and rings of `structure`, whose atoms
+/// are of `elements`.
+pub(crate) fn perceive(elements: &[Element], structure: KekuleStructure) -> Aromaticity {
+    let number_of_atoms = elements.len();
+    let bonds: Vec<(u16, u16)> = structure.bonds().iter().map(|&(bond, _)| bond).collect();
+    let in_ring = ring_bonds(number_of_atoms, &bonds);
+    let contributions: Vec<Option<usize>> = (0..number_of_atoms)
+        .map(|atom| contribution(elements, &structure, &in_ring, atom))
+        .collect();
+
+    // Only the rings whose atoms all contribute can be aromatic.
+    let rings: Vec<Vec<u16>> = smallest_rings(number_of_atoms, &bonds)
+        .into_iter()
+        .filter(|ring| ring.iter().all(|&atom| contributions[usize::from(atom)].is_some()))
+        .collect();
+    let ring_bonds: Vec<BTreeSet<(u16, u16)>> =
+        rings.iter().map(|ring| ring_bond_set(ring)).collect();
+    let electrons = |members: &[usize]| -> usize {
+        let atoms: BTreeSet<u16> =
+            members.iter().flat_map(|&ring| rings[ring].iter().copied()).collect();
+        atoms.iter().filter_map(|&atom| contributions[usize::from(atom)]).sum()
+    };
+    let fused = |a: usize, b: usize| !ring_bonds[a].is_disjoint(&ring_bonds[b]);
+
+    let mut aromatic = vec![false; rings.len()];
+    for ring in 0..rings.len() {
+        if is_huckel(electrons(&[ring])) {
+            aromatic[ring] = true;
+        }
+        for other in (ring + 1..rings.len()).filter(|&other| fused(ring, other)) {
+            if is_huckel(electrons(&[ring, other])) {
+                aromatic[ring] = true;
+                aromatic[other] = true;
+            }
+        }
+    }
+
+    // Fused systems, grown from each ring not yet part of one.
+    let mut system_of = vec![usize::MAX; rings.len()];
+    for start in 0..rings.len() {
+        if system_of[start] != usize::MAX {
+            continue;
+        }
+        system_of[start] = start;
+        let mut system = vec![start];
+        let mut next = 0;
+        while let Some(&ring) = system.get(next) {
+            next += 1;
+            for (other, system_of_other) in system_of.iter_mut().enumerate() {
+                if *system_of_other == usize::MAX && fused(ring, other) {
+                    *system_of_other = start;
+                    system.push(other);
+                }
+            }
+        }
+        if system.len() > 2 && is_huckel(electrons(&system)) {
+            for ring in system {
+                aromatic[ring] = true;
+            }
+        }
+    }
+
+    let mut atoms = vec![false; number_of_atoms];
+    let mut aromatic_bonds = BTreeSet::new();
+    let mut aromatic_rings = Vec::new();
+    for (ring, bonds) in rings
+        .into_iter()
+        .zip(ring_bonds)
+        .zip(aromatic)
+        .filter_map(|(ring, aromatic)| aromatic.then_some(ring))
+    {
+        for &atom in &ring {
+            atoms[usize::from(atom)] = true;
+        }
+        aromatic_bonds.extend(bonds);
+        aromatic_rings.push(ring);
+    }
+    Aromaticity {
+        atoms,
+        bonds: aromatic_bonds.into_iter().collect(),
+        rings: aromatic_rings,
+        kekule_structure: structure,
+    }
+}
+
+impl Component<'_> {
+    /// Perceives the aromatic atoms, bonds and rings of the component, on
+    /// the Kekulé structure returned by [`Component::kekulize`].
+    ///
+    /// See [`Aromaticity`] for the model.
+    ///
+    /// The flags come back in an [`Aromaticity`] rather than on each
+    /// [`Atom`](crate::inchi::Atom): the component borrows the unlabelled
+    /// graph of the `/c` layer, and perceiving them needs a Kekulé structure
+    /// search that may fail, which the per-atom view should not run.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`KekulizationError`] when the component has no Kekulé
+    /// structure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::inchi::InChI;
+    ///
+    /// // Toluene: the methyl carbon C1 is not aromatic, the ring is.
+    /// let toluene: InChI = "InChI=1S/C7H8/c1-7-5-3-2-4-6-7/h2-6H,1H3".parse().unwrap();
+    /// let aromaticity = toluene.components().next().unwrap().aromaticity().unwrap();
+    /// assert_eq!(aromaticity.aromatic_atoms().collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6]);
+    /// assert!(aromaticity.is_aromatic_bond(4, 6));
+    /// assert!(!aromaticity.is_aromatic_bond(0, 6));
+    /// ```
+    pub fn aromaticity(&self) -> Result<Aromaticity, KekulizationError> {
+        let structure = self.kekulize()?;
+        Ok(perceive(&self.elements()?, structure))
+    }
+}
+
+impl<V: Version> InChI<V> {
+    /// Perceives the aromaticity of every component, in formula order.
+    ///
+    /// See [`Component::aromaticity`] for how each component is handled.
+    #[must_use]
+    pub fn aromaticity(&self) -> Vec<Result<Aromaticity, KekulizationError>> {
+        self.components().map(|component| component.aromaticity()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::inchi::{Aromaticity, InChI};
+
+    fn aromaticity(inchi: &str) -> Aromaticity {
+        let inchi: InChI = inchi.parse().unwrap();
+        inchi.components().next().unwrap().aromaticity().unwrap()
+    }
+
+    fn aromatic_atoms(inchi: &str) -> Vec<u16> {
+        aromaticity(inchi).aromatic_atoms().collect()
+    }
+
+    #[test]
+    fn test_monocycles() {
+        assert_eq!(aromatic_atoms("InChI=1S/C6H6/c1-2-4-6-5-3-1/h1-6H"), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(aromatic_atoms("InChI=1S/C5H5N/c1-2-4-6-5-3-1/h1-5H"), [0, 1, 2, 3, 4, 5]);
+        // Pyrrole, furan and thiophene share their heteroatom's lone pair.
+        assert_eq!(aromatic_atoms("InChI=1S/C4H5N/c1-2-4-5-3-1/h1-5H"), [0, 1, 2, 3, 4]);
+        assert_eq!(aromatic_atoms("InChI=1S/C4H4O/c1-2-4-5-3-1/h1-4H"), [0, 1, 2, 3, 4]);
+        assert_eq!(aromatic_atoms("InChI=1S/C4H4S/c1-2-4-5-3-1/h1-4H"), [0, 1, 2, 3, 4]);
+        assert_eq!(aromatic_atoms("InChI=1S/C5H5/c1-2-4-5-3-1/h1-5H/q-1"), [0, 1, 2, 3, 4]);
+        let imidazole = aromaticity("InChI=1S/C3H4N2/c1-2-5-3-4-1/h1-3H,(H,4,5)");
+        assert_eq!(imidazole.aromatic_rings().len(), 1);
+    }
+
+    #[test]
+    fn test_non_aromatic_rings() {
+        // Cyclohexene, cyclopentadiene, cyclooctatetraene and
+        // p-benzoquinone.
+        assert!(aromatic_atoms("InChI=1S/C6H10/c1-2-4-6-5-3-1/h1-2H,3-6H2").is_empty());
+        assert!(aromatic_atoms("InChI=1S/C5H6/c1-2-4-5-3-1/h1-4H,5H2").is_empty());
+        assert!(aromatic_atoms("InChI=1S/C8H8/c1-2-4-6-8-7-5-3-1/h1-8H").is_empty());
+        assert!(aromatic_atoms("InChI=1S/C6H4O2/c7-5-1-2-6(8)4-3-5/h1-4H").is_empty());
+    }
+
+    #[test]
+    fn test_fused_systems() {
+        let naphthalene = aromaticity("InChI=1S/C10H8/c1-2-6-10-8-4-3-7-9(10)5-1/h1-8H");
+        assert_eq!(naphthalene.aromatic_rings().len(), 2);
+        assert_eq!(naphthalene.aromatic_atoms().count(), 10);
+        assert_eq!(naphthalene.aromatic_bonds().len(), 11);
+        // Azulene is only aromatic as a whole, with 10 π electrons.
+        let azulene = aromaticity("InChI=1S/C10H8/c1-2-5-9-7-4-8-10(9)6-3-1/h1-8H");
+        assert_eq!(azulene.aromatic_atoms().count(), 10);
+        // Indane keeps its five-membered ring out.
+        let indane = aromaticity("InChI=1S/C9H10/c1-2-5-9-7-3-6-8(9)4-1/h1-2,4-5H,3,6-7H2");
+        assert_eq!(indane.aromatic_atoms().count(), 6);
+        assert_eq!(indane.aromatic_rings().len(), 1);
+    }
+}
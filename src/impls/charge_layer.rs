@@ -1,10 +1,14 @@
+use alloc::{format, string::String};
+use core::fmt::{self, Display};
+
 use molecular_formulas::{BaselineDigit, InChIFormula, MolecularFormula, try_fold_number};
 
 use crate::{
-    errors::Error,
+    errors::{Error, LayerError},
+    impls::display::write_components,
     inchi::charge_layer::ChargeSubLayer,
     traits::{
-        parse::{FromStrWithContext, PrefixFromStrWithContext},
+        parse::{FromStrWithContext, PrefixFromStrWithContext, consumed},
         prefix::Prefix,
     },
 };
@@ -12,8 +16,9 @@ use crate::{
 /// Parses a charge value from a character iterator.
 ///
 /// Expected formats: empty → 0, `+N` → +N, `-N` → −N.
-/// InChI charges always carry an explicit sign for nonzero values.
-pub(crate) fn parse_charge(s: &str) -> Result<i16, Error<u16>> {
+/// InChI charges always carry an explicit sign for nonzero values. The fault
+/// of an error is relative to `s`.
+pub(crate) fn parse_charge(s: &str) -> Result<i16, LayerError<u16>> {
     if s.is_empty() {
         return Ok(0);
     }
@@ -29,38 +34,75 @@ pub(crate) fn parse_charge(s: &str) -> Result<i16, Error<u16>> {
             chars.next();
             true
         }
-        Some(&c) => return Err(Error::InvalidChargeValue(c)),
+        Some(&c) => return Err(LayerError::at_char(Error::InvalidChargeValue(c), s, 0)),
         None => unreachable!(),
     };
 
+    let sign = if negative { '-' } else { '+' };
     let Some(Ok(magnitude)) = try_fold_number::<u16, BaselineDigit, _>(&mut chars) else {
-        let c = chars.peek().copied().unwrap_or(if negative { '-' } else { '+' });
-        return Err(Error::InvalidChargeValue(c));
+        let c = chars.peek().copied().unwrap_or(sign);
+        return Err(LayerError::at_char(Error::InvalidChargeValue(c), s, consumed(s, &chars)));
     };
 
     if let Some(&c) = chars.peek() {
-        return Err(Error::InvalidChargeValue(c));
+        return Err(LayerError::at_char(Error::InvalidChargeValue(c), s, consumed(s, &chars)));
     }
 
     if negative {
         i16::try_from(magnitude).map(|v| -v).or({
             // magnitude == 32768 is valid as -32768 (i16::MIN)
-            if magnitude == 32768 { Ok(i16::MIN) } else { Err(Error::InvalidChargeValue('-')) }
+            if magnitude == 32768 {
+                Ok(i16::MIN)
+            } else {
+                Err(LayerError::at(Error::InvalidChargeValue(sign), 0..s.len()))
+            }
         })
     } else {
-        i16::try_from(magnitude).map_err(|_| Error::InvalidChargeValue('+'))
+        i16::try_from(magnitude)
+            .map_err(|_| LayerError::at(Error::InvalidChargeValue(sign), 0..s.len()))
     }
 }
 
-impl FromStrWithContext for ChargeSubLayer {
+/// A component of a `/q` sublayer.
+pub(crate) trait ChargeComponent: Copy {
+    /// Parses a component, with the fault of an error relative to `s`.
+    fn parse(s: &str) -> Result<Self, LayerError<u16>>;
+
+    /// Writes the component as in the sublayer, empty when neutral.
+    fn to_component_string(self) -> String;
+}
+
+impl ChargeComponent for i16 {
+    fn parse(s: &str) -> Result<Self, LayerError<u16>> {
+        parse_charge(s)
+    }
+
+    fn to_component_string(self) -> String {
+        if self == 0 { String::new() } else { format!("{self:+}") }
+    }
+}
+
+/// A component of the fixed-H `/q` sublayer, `None` when written `m` as its
+/// charge is the same as in the main layer.
+impl ChargeComponent for Option<i16> {
+    fn parse(s: &str) -> Result<Self, LayerError<u16>> {
+        if s == "m" { Ok(None) } else { parse_charge(s).map(Some) }
+    }
+
+    fn to_component_string(self) -> String {
+        self.map_or_else(|| String::from("m"), i16::to_component_string)
+    }
+}
+
+impl<C: ChargeComponent> FromStrWithContext for ChargeSubLayer<C> {
     type Context<'a> = &'a InChIFormula;
     type Input<'a> = &'a str;
     type Idx = u16;
 
-    fn from_str_with_context(
+    fn from_str_located(
         input: Self::Input<'_>,
         context: Self::Context<'_>,
-    ) -> Result<Self, Error<Self::Idx>> {
+    ) -> Result<Self, LayerError<Self::Idx>> {
         let s = input.strip_prefix(Self::PREFIX).ok_or(Error::WrongPrefix)?;
 
         let mut subformulas = context.subformulas();
@@ -84,7 +126,8 @@ impl FromStrWithContext for ChargeSubLayer {
                 context.number_of_mixtures(),
             ))?;
 
-            let charge = parse_charge(component_str)?;
+            let charge =
+                C::parse(component_str).map_err(|error| error.within(input, component_str))?;
 
             charges.push(charge);
             for _ in 1..reps {
@@ -98,17 +141,26 @@ impl FromStrWithContext for ChargeSubLayer {
         if subformulas.next().is_some() {
             return Err(Error::FormulaAndConnectionLayerMixtureMismatch(
                 context.number_of_mixtures(),
-            ));
+            )
+            .into());
         }
 
         Ok(ChargeSubLayer { charges })
     }
 }
 
-impl PrefixFromStrWithContext for ChargeSubLayer {}
+impl<C: ChargeComponent> PrefixFromStrWithContext for ChargeSubLayer<C> {}
+
+impl<C: ChargeComponent> Display for ChargeSubLayer<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::PREFIX)?;
+        write_components(f, self.charges.iter().map(|&charge| charge.to_component_string()))
+    }
+}
 
 #[cfg(test)]
 mod tests {
+    use alloc::string::ToString;
     use core::str::FromStr;
 
     use molecular_formulas::InChIFormula;
@@ -177,6 +229,15 @@ mod tests {
         assert_eq!(result.charges, &[-1, -1]);
     }
 
+    #[test]
+    fn test_display_round_trip() {
+        for (q_layer, ctx) in
+            [("q-2", "O"), ("q;+1", "ClH.Na"), ("q2*+1", "2Na"), ("q+2;;", "C15H32N2.2C4H6O6")]
+        {
+            assert_eq!(parse(q_layer, ctx).unwrap().to_string(), q_layer);
+        }
+    }
+
     #[test]
     fn test_wrong_prefix() {
         let err = parse("h-2", "O").unwrap_err();
@@ -189,6 +250,13 @@ mod tests {
         assert!(matches!(err, Error::InvalidChargeValue('a')));
     }
 
+    #[test]
+    fn test_main_layer_rejects_same_as_main() {
+        // Only the fixed-H charges may refer to the main layer with `m`.
+        let err = parse("q-1;m", "C2H3O2.Na").unwrap_err();
+        assert_eq!(err, Error::InvalidChargeValue('m'));
+    }
+
     #[test]
     fn test_sign_without_digits() {
         let err = parse("q+", "O").unwrap_err();
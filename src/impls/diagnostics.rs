@@ -0,0 +1,252 @@
+//! Collects every problem of an InChI string in one pass.
+
+use alloc::{string::ToString, vec::Vec};
+use core::fmt::Display;
+
+use super::parse_options::FixedInput;
+use crate::{
+    diagnostics::{Diagnostic, Severity},
+    errors::{LayerId, ParseError},
+    inchi::InChI,
+    validation::{ChargeIssue, ConformanceIssue, StereoIssue},
+    version::Version,
+};
+
+impl Diagnostic {
+    /// Creates the diagnostic of a layer that failed to parse, whose span in
+    /// the original input is `span`.
+    fn from_parse_error(error: &ParseError, span: core::ops::Range<usize>) -> Self {
+        Self {
+            severity: Severity::Error,
+            code: error.code(),
+            message: error.kind().to_string(),
+            layer: error.layer(),
+            span: Some(span),
+        }
+    }
+
+    /// Creates the warning of an issue found by a validation pass.
+    fn warning(code: &'static str, layer: LayerId, issue: &impl Display) -> Self {
+        Self { severity: Severity::Warning, code, message: issue.to_string(), layer, span: None }
+    }
+}
+
+/// The stereo sublayers, read by the stereo and conformance passes, and the
+/// isotope layer that holds the isotopic ones.
+const STEREO_LAYERS: [LayerId; 5] = [
+    LayerId::DoubleBondStereo,
+    LayerId::TetrahedralStereo,
+    LayerId::StereoInversion,
+    LayerId::StereoType,
+    LayerId::Isotope,
+];
+
+/// Returns whether none of `layers` is among the `dropped` ones.
+fn all_parsed(dropped: &[LayerId], layers: &[LayerId]) -> bool {
+    !layers.iter().any(|layer| dropped.contains(layer))
+}
+
+impl<V: Version> InChI<V> {
+    /// Returns the diagnostics of every problem found in `input`, or none
+    /// when it is a valid and plausible InChI.
+    ///
+    /// Unlike [`FromStr`](core::str::FromStr), which stops at the first
+    /// error, a layer that fails to parse is reported as an
+    /// [`Severity::Error`] and left out, and the rest of the string is parsed
+    /// again to find the problems of the other layers. Errors in the version
+    /// prefix or in the formula cannot be recovered from, since every other
+    /// layer depends on them. Once the string parses, the validation passes
+    /// report implausible stereo, hydrogen imbalances, excess valences,
+    /// inconsistent charges and, for standard InChIs, non-conforming layers
+    /// as [`Severity::Warning`]s. A pass that reads a layer left out is
+    /// skipped, since it would only report on the string without that layer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::{diagnostics::Severity, inchi::InChI};
+    ///
+    /// let diagnostics = <InChI>::validate("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/q+x/t2x");
+    /// let codes: Vec<_> =
+    ///     diagnostics.iter().map(|diagnostic| (diagnostic.severity(), diagnostic.code())).collect();
+    /// assert_eq!(codes, [(Severity::Error, "invalid_charge"), (Severity::Error, "invalid_stereo")]);
+    /// ```
+    #[must_use]
+    pub fn validate(input: &str) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut skipped: Vec<usize> = Vec::new();
+        let mut dropped: Vec<LayerId> = Vec::new();
+        let mut segments = Vec::new();
+        loop {
+            let mut fixed = FixedInput::default();
+            segments.clear();
+            fixed.push_segments(input, 0, |index, _, span| {
+                segments.push(span);
+                !skipped.contains(&index)
+            });
+            match fixed.text.parse::<Self>() {
+                Ok(inchi) => {
+                    diagnostics.extend(inchi.validation_diagnostics(&dropped));
+                    return diagnostics;
+                }
+                Err(error) => {
+                    let span = fixed.original_span(error.span());
+                    diagnostics.push(Diagnostic::from_parse_error(&error, span.clone()));
+                    // The version and the formula are the first two segments.
+                    let Some(index) = segments
+                        .iter()
+                        .position(|segment| segment.start <= span.start && span.end <= segment.end)
+                        .filter(|&index| index >= 2 && !skipped.contains(&index))
+                    else {
+                        return diagnostics;
+                    };
+                    // The reconnected layer spans every segment that follows
+                    // its own.
+                    let layer = LayerId::from_segment(&input[segments[index].clone()]);
+                    if layer == LayerId::Reconnected {
+                        skipped.extend(index..segments.len());
+                    } else {
+                        skipped.push(index);
+                    }
+                    dropped.push(layer);
+                }
+            }
+        }
+    }
+
+    /// Returns the warnings of the validation passes that read none of the
+    /// `dropped` layers.
+    fn validation_diagnostics(&self, dropped: &[LayerId]) -> Vec<Diagnostic> {
+        use LayerId::{Charge, Connections, Hydrogens, Protons, Reconnected};
+
+        let mut diagnostics = Vec::new();
+        if all_parsed(dropped, &[Connections, Hydrogens, Reconnected])
+            && all_parsed(dropped, &STEREO_LAYERS)
+        {
+            diagnostics.extend(self.check_stereochemistry().iter().map(|issue| {
+                let layer = match issue {
+                    StereoIssue::UnbondedDoubleBond { .. } => LayerId::DoubleBondStereo,
+                    _ => LayerId::TetrahedralStereo,
+                };
+                Diagnostic::warning("implausible_stereo", layer, issue)
+            }));
+        }
+        if all_parsed(dropped, &[Hydrogens, Reconnected]) {
+            diagnostics.extend(
+                self.check_hydrogen_balance()
+                    .iter()
+                    .map(|issue| Diagnostic::warning("hydrogen_imbalance", Hydrogens, issue)),
+            );
+        }
+        if all_parsed(dropped, &[Connections, Hydrogens, Charge, Reconnected]) {
+            diagnostics.extend(
+                self.check_valences()
+                    .iter()
+                    .map(|issue| Diagnostic::warning("valence_exceeded", Connections, issue)),
+            );
+        }
+        if all_parsed(dropped, &[Hydrogens, Charge, Protons, Reconnected]) {
+            diagnostics.extend(self.check_charges().iter().map(|issue| {
+                let layer = match issue {
+                    ChargeIssue::MobileChargeExceedsCharge { .. } => Charge,
+                    ChargeIssue::ProtonsExceedHydrogens { .. } => Protons,
+                };
+                Diagnostic::warning("inconsistent_charge", layer, issue)
+            }));
+        }
+        if V::IS_STANDARD && all_parsed(dropped, &STEREO_LAYERS) {
+            diagnostics.extend(self.check_standard_conformance().iter().map(|issue| {
+                let layer = match issue {
+                    ConformanceIssue::FixedHydrogenLayer => LayerId::FixedHydrogen,
+                    ConformanceIssue::ReconnectedLayer => LayerId::Reconnected,
+                    ConformanceIssue::UnpairedStereoFlag { inversion: true, .. } => {
+                        LayerId::StereoInversion
+                    }
+                    ConformanceIssue::NonAbsoluteStereo { .. }
+                    | ConformanceIssue::UnpairedStereoFlag { inversion: false, .. }
+                    | ConformanceIssue::StereoFlagsWithoutTetrahedral { .. } => LayerId::StereoType,
+                };
+                Diagnostic::warning(issue.rule(), layer, issue)
+            }));
+        }
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{diagnostics::Severity, errors::LayerId, inchi::InChI};
+
+    #[test]
+    fn test_valid_inchi_has_no_diagnostics() {
+        assert!(<InChI>::validate("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3").is_empty());
+    }
+
+    #[test]
+    fn test_errors_in_several_layers() {
+        let input = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/q+x/b9-3+/t2x/m0/s1";
+        let diagnostics = <InChI>::validate(input);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity(), diagnostic.layer(), diagnostic.span()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Severity::Error, LayerId::Charge, Some(36..37)),
+                (Severity::Error, LayerId::DoubleBondStereo, Some(39..43)),
+                (Severity::Error, LayerId::TetrahedralStereo, Some(46..47)),
+            ]
+        );
+        assert_eq!(&input[36..37], "x");
+        assert_eq!(&input[39..43], "9-3+");
+        assert_eq!(&input[46..47], "x");
+    }
+
+    #[test]
+    fn test_unrecoverable_formula_error() {
+        let diagnostics = <InChI>::validate("InChI=1S/C2OH6/c1-2-3/q+x");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code(), "invalid_formula");
+        assert_eq!(diagnostics[0].layer(), LayerId::Formula);
+    }
+
+    #[test]
+    fn test_warnings_of_validation_passes() {
+        let diagnostics = <InChI>::validate("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H4/t2-/m0/s2");
+        let codes: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity(), diagnostic.code()))
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                (Severity::Warning, "implausible_stereo"),
+                (Severity::Warning, "hydrogen_imbalance"),
+                (Severity::Warning, "valence_exceeded"),
+                (Severity::Warning, "absolute_stereo_only"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_passes_reading_a_dropped_layer_are_skipped() {
+        // Without its /t, the /m and /s flags would be reported as lacking
+        // tetrahedral stereo, and the stereo pass would check the rest.
+        let diagnostics = <InChI>::validate("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H4/t2x/m0/s2");
+        let codes: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.severity(), diagnostic.code()))
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                (Severity::Error, "invalid_stereo"),
+                (Severity::Warning, "hydrogen_imbalance"),
+                (Severity::Warning, "valence_exceeded"),
+            ]
+        );
+    }
+}
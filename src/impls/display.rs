@@ -0,0 +1,117 @@
+//! Serialization of a parsed InChI back to its canonical string.
+
+use alloc::string::String;
+use core::fmt::{self, Display, Write};
+
+use crate::{
+    inchi::{AnyInChI, InChI},
+    version::Version,
+};
+
+/// Writes the per-component strings of a layer, separated by `;`.
+///
+/// Runs of consecutive identical non-empty components are compressed with
+/// the `n*` repetition prefix, as in canonical InChI (`2*1H2`). Empty
+/// components are never compressed (`+2;;`).
+pub(crate) fn write_components<W: fmt::Write>(
+    f: &mut W,
+    components: impl IntoIterator<Item = String>,
+) -> fmt::Result {
+    let mut components = components.into_iter().peekable();
+    let mut first = true;
+    while let Some(component) = components.next() {
+        if !first {
+            f.write_char(';')?;
+        }
+        first = false;
+        if component.is_empty() {
+            continue;
+        }
+        let mut repetitions = 1usize;
+        while components.next_if_eq(&component).is_some() {
+            repetitions += 1;
+        }
+        if repetitions > 1 {
+            write!(f, "{repetitions}*")?;
+        }
+        f.write_str(&component)?;
+    }
+    Ok(())
+}
+
+/// Writes the layers of an InChI after the version prefix, separated by `/`.
+///
+/// This is shared by the top-level InChI and by the reconnected `/r` layer,
+/// which holds a complete nested set of layers.
+pub(crate) fn write_layers<V: Version>(
+    f: &mut fmt::Formatter<'_>,
+    inchi: &InChI<V>,
+) -> fmt::Result {
+    let layers: [Option<&dyn Display>; 7] = [
+        inchi.main_layer.as_ref().map(|layer| layer as &dyn Display),
+        inchi.charge.as_ref().map(|layer| layer as &dyn Display),
+        inchi.proton.as_ref().map(|layer| layer as &dyn Display),
+        inchi.stereochemistry.as_ref().map(|layer| layer as &dyn Display),
+        inchi.isotope.as_ref().map(|layer| layer as &dyn Display),
+        inchi.fixed_hydrogen.as_ref().map(|layer| layer as &dyn Display),
+        inchi.reconnected.as_ref().map(|layer| layer as &dyn Display),
+    ];
+    for (position, layer) in layers.into_iter().flatten().enumerate() {
+        if position > 0 {
+            f.write_char('/')?;
+        }
+        write!(f, "{layer}")?;
+    }
+    Ok(())
+}
+
+impl<V: Version> Display for InChI<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}/", crate::constants::INCHI_PREFIX, V::VERSION_PREFIX)?;
+        write_layers(f, self)
+    }
+}
+
+impl Display for AnyInChI {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnyInChI::Standard(inchi) => write!(f, "{inchi}"),
+            AnyInChI::NonStandard(inchi) => write!(f, "{inchi}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec};
+
+    use super::write_components;
+
+    fn render(components: &[&str]) -> String {
+        let mut out = String::new();
+        write_components(&mut out, components.iter().map(|&c| String::from(c))).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_single_component() {
+        assert_eq!(render(&["1-2-3"]), "1-2-3");
+    }
+
+    #[test]
+    fn test_repetition_is_compressed() {
+        assert_eq!(render(&["1H2", "1H2", "1H2"]), "3*1H2");
+        assert_eq!(render(&["", "1H2", "1H2", "1H2"]), ";3*1H2");
+    }
+
+    #[test]
+    fn test_empty_components_are_not_compressed() {
+        assert_eq!(render(&["+2", "", ""]), "+2;;");
+    }
+
+    #[test]
+    fn test_non_consecutive_components_are_not_compressed() {
+        let components = vec!["+1", "-1", "+1"];
+        assert_eq!(render(&components), "+1;-1;+1");
+    }
+}
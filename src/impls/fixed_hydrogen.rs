@@ -0,0 +1,283 @@
+use alloc::vec::Vec;
+use core::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+use molecular_formulas::{BaselineDigit, InChIFormula, MolecularFormula, try_fold_number};
+
+use crate::{
+    errors::{Error, LayerError},
+    inchi::{
+        FixedHydrogenLayer, IsotopeLayer, StereochemistryLayer, charge_layer::ChargeSubLayer,
+        fixed_hydrogen::TranspositionSubLayer, main_layer::HydrogensSubLayer,
+    },
+    traits::{
+        parse::{FromStrWithContext, PrefixFromStrWithContext, consumed},
+        prefix::Prefix,
+    },
+};
+
+impl FromStrWithContext for FixedHydrogenLayer {
+    type Context<'a> = &'a InChIFormula;
+    type Input<'a> = &'a str;
+    type Idx = u16;
+
+    /// Parses the `/f` segment itself, i.e. the formula of the fixed-H
+    /// structure. The sublayers that follow it are parsed by
+    /// [`PrefixFromStrWithContext::try_build_layer`].
+    fn from_str_located(
+        input: Self::Input<'_>,
+        context: Self::Context<'_>,
+    ) -> Result<Self, LayerError<Self::Idx>> {
+        let s = input.strip_prefix(Self::PREFIX).ok_or(Error::WrongPrefix)?;
+
+        // An empty `/f` means that the formula is the same as the main layer one.
+        let chemical_formula = if s.is_empty() {
+            None
+        } else {
+            let chemical_formula = InChIFormula::from_str(s)?;
+            if chemical_formula.number_of_mixtures() != context.number_of_mixtures() {
+                return Err(Error::FormulaAndConnectionLayerMixtureMismatch(
+                    context.number_of_mixtures(),
+                )
+                .into());
+            }
+            Some(chemical_formula)
+        };
+
+        Ok(FixedHydrogenLayer {
+            chemical_formula,
+            hydrogens: None,
+            charge: None,
+            stereochemistry: None,
+            isotope: None,
+            transposition: None,
+        })
+    }
+}
+
+impl PrefixFromStrWithContext for FixedHydrogenLayer {
+    fn try_build_layer(
+        input: &mut &str,
+        context: Self::Context<'_>,
+    ) -> Result<Option<Self>, LayerError<Self::Idx>> {
+        if !input.starts_with(Self::PREFIX) {
+            return Ok(None);
+        }
+
+        // Consume the /f segment
+        let (f_segment, remainder) = input.split_once('/').unwrap_or((input, ""));
+        let mut layer = Self::from_str_located(f_segment, context)?;
+        *input = remainder;
+
+        // The sublayers refer to the fixed-H formula when one is given.
+        let formula = layer.chemical_formula.clone().unwrap_or_else(|| context.clone());
+
+        layer.hydrogens = HydrogensSubLayer::try_build_layer(input, &formula)?;
+        layer.charge = ChargeSubLayer::try_build_layer(input, &formula)?;
+        layer.stereochemistry = StereochemistryLayer::try_build_layer(input, &formula)?;
+        layer.isotope = IsotopeLayer::try_build_layer(input, &formula)?;
+        layer.transposition = TranspositionSubLayer::try_build_layer(input, ())?;
+
+        Ok(Some(layer))
+    }
+}
+
+impl Display for FixedHydrogenLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::PREFIX)?;
+        if let Some(chemical_formula) = &self.chemical_formula {
+            write!(f, "{chemical_formula}")?;
+        }
+        if let Some(hydrogens) = &self.hydrogens {
+            write!(f, "/{hydrogens}")?;
+        }
+        if let Some(charge) = &self.charge {
+            write!(f, "/{charge}")?;
+        }
+        if let Some(stereochemistry) = &self.stereochemistry {
+            write!(f, "/{stereochemistry}")?;
+        }
+        if let Some(isotope) = &self.isotope {
+            write!(f, "/{isotope}")?;
+        }
+        if let Some(transposition) = &self.transposition {
+            write!(f, "/{transposition}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStrWithContext for TranspositionSubLayer {
+    type Context<'a> = ();
+    type Input<'a> = &'a str;
+    type Idx = u16;
+
+    /// Parses cycles of 1-based component numbers, such as `o(1,2)(3,5,4)`.
+    fn from_str_located(
+        input: Self::Input<'_>,
+        _context: Self::Context<'_>,
+    ) -> Result<Self, LayerError<Self::Idx>> {
+        let s = input.strip_prefix(Self::PREFIX).ok_or(Error::WrongPrefix)?;
+
+        let mut chars = s.chars().peekable();
+        let mut cycles = Vec::new();
+        // Locates an error on the character of `s` at `position`.
+        let fault = |c: char, position: usize| {
+            LayerError::at_char(Error::InvalidTranspositionValue(c), s, position).within(input, s)
+        };
+
+        while let Some(c) = chars.next() {
+            if c != '(' {
+                return Err(fault(c, consumed(s, &chars) - c.len_utf8()));
+            }
+            let mut cycle = Vec::new();
+            loop {
+                let component = match try_fold_number::<u16, BaselineDigit, _>(&mut chars) {
+                    Some(Ok(n)) if n > 0 => n - 1,
+                    _ => {
+                        let position = consumed(s, &chars);
+                        return Err(fault(chars.peek().copied().unwrap_or(')'), position));
+                    }
+                };
+                cycle.push(component);
+                match chars.next() {
+                    Some(',') => {}
+                    Some(')') => break,
+                    Some(c) => return Err(fault(c, consumed(s, &chars) - c.len_utf8())),
+                    None => return Err(fault(')', s.len())),
+                }
+            }
+            cycles.push(cycle);
+        }
+
+        if cycles.is_empty() {
+            return Err(LayerError::at_char(Error::InvalidTranspositionValue('o'), input, 0));
+        }
+
+        Ok(TranspositionSubLayer { cycles })
+    }
+}
+
+impl PrefixFromStrWithContext for TranspositionSubLayer {}
+
+impl Display for TranspositionSubLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::PREFIX)?;
+        for cycle in &self.cycles {
+            write!(f, "(")?;
+            for (position, component) in cycle.iter().enumerate() {
+                if position > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}", component + 1)?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+    use core::str::FromStr;
+
+    use molecular_formulas::InChIFormula;
+
+    use crate::{
+        errors::{Error, LayerError},
+        inchi::{FixedHydrogenLayer, fixed_hydrogen::TranspositionSubLayer},
+        traits::parse::{FromStrWithContext, PrefixFromStrWithContext},
+    };
+
+    fn formula(s: &str) -> InChIFormula {
+        InChIFormula::from_str(s).expect("valid formula")
+    }
+
+    fn build(input: &str, ctx: &str) -> (Result<Option<FixedHydrogenLayer>, Error<u16>>, String) {
+        let mut input = input;
+        let result = FixedHydrogenLayer::try_build_layer(&mut input, &formula(ctx));
+        (result.map_err(LayerError::into_kind), input.to_string())
+    }
+
+    #[test]
+    fn test_empty_formula_with_hydrogens() {
+        let (result, remainder) = build("f/h3H", "C2H4O2");
+        let layer = result.unwrap().unwrap();
+        assert!(remainder.is_empty());
+        assert!(layer.chemical_formula().is_none());
+        assert_eq!(layer.hydrogens().unwrap()[0].fixed_hydrogens(), &[0, 0, 1, 0]);
+        assert!(layer.charges().is_none());
+    }
+
+    #[test]
+    fn test_formula_and_charge() {
+        let (result, remainder) = build("fC2H3O2/q-1", "C2H4O2");
+        let layer = result.unwrap().unwrap();
+        assert!(remainder.is_empty());
+        assert_eq!(layer.chemical_formula().unwrap().to_string(), "C2H3O2");
+        assert_eq!(layer.charges(), Some(&[Some(-1)][..]));
+    }
+
+    #[test]
+    fn test_charge_same_as_main_layer() {
+        let (result, remainder) = build("fC2H3O2.Na/q-1;m", "C2H4O2.Na");
+        let layer = result.unwrap().unwrap();
+        assert!(remainder.is_empty());
+        assert_eq!(layer.charges(), Some(&[Some(-1), None][..]));
+        assert_eq!(layer.to_string(), "fC2H3O2.Na/q-1;m");
+    }
+
+    #[test]
+    fn test_stereo_isotope_and_transposition() {
+        let (result, remainder) = build("f/h2*1H/t2*2-/m1/s1/i3+1;/o(1,2)/rC2", "2C3H8O");
+        let layer = result.unwrap().unwrap();
+        assert_eq!(remainder, "rC2");
+        let stereo = layer.stereochemistry().unwrap();
+        assert_eq!(stereo.tetrahedral().unwrap().components().len(), 2);
+        assert_eq!(stereo.allene().unwrap().values(), &[Some(1)]);
+        assert_eq!(layer.isotope().unwrap().components()[0].atoms()[0].atom_index(), 2);
+        assert_eq!(layer.transposition().unwrap().cycles(), &[alloc::vec![0, 1]]);
+    }
+
+    #[test]
+    fn test_not_present() {
+        let (result, remainder) = build("rC2H6O", "C2H6O");
+        assert!(result.unwrap().is_none());
+        assert_eq!(remainder, "rC2H6O");
+    }
+
+    #[test]
+    fn test_formula_component_mismatch() {
+        let (result, _) = build("fC2H3O2.Na", "C2H4O2");
+        assert!(matches!(result, Err(Error::FormulaAndConnectionLayerMixtureMismatch(1))));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for input in ["f/h3H", "fC2H3O2/q-1", "f/h2*1H/q2*-1/o(1,2)"] {
+            let ctx = if input.contains("2*") { "2C2H4O2" } else { "C2H4O2" };
+            let (result, _) = build(input, ctx);
+            assert_eq!(result.unwrap().unwrap().to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_transposition_cycles() {
+        let result = TranspositionSubLayer::from_str_with_context("o(1,2)(3,5,4)", ()).unwrap();
+        assert_eq!(result.cycles, alloc::vec![alloc::vec![0, 1], alloc::vec![2, 4, 3]]);
+    }
+
+    #[test]
+    fn test_transposition_invalid() {
+        for input in ["o", "o1,2", "o(1,2", "o(0,1)", "o()", "o(1;2)"] {
+            let result = TranspositionSubLayer::from_str_with_context(input, ());
+            assert!(
+                matches!(result, Err(Error::InvalidTranspositionValue(_))),
+                "{input}: {result:?}"
+            );
+        }
+    }
+}
@@ -1,31 +1,29 @@
 use alloc::vec::Vec;
 use core::str::FromStr;
 
+use super::layer_order::LayerOrder;
 use crate::{
-    errors::Error,
+    errors::{Error, LayerError, LayerId, ParseError},
     inchi::{
-        InChI, IsotopeLayer, MainLayer,
-        charge_layer::ChargeSubLayer,
-        isotope_layer::IsotopeComponent,
-        proton_layer::ProtonSublayer,
-        stereochemistry_layer::{
-            AlleneSublayer, DoubleBondSublayer, StereoChemistryInformationSublayer,
-            StereochemistryLayer, TetrahedralSublayer,
-        },
+        AnyInChI, FixedHydrogenLayer, InChI, IsotopeLayer, MainLayer, ReconnectedLayer,
+        charge_layer::ChargeSubLayer, isotope_layer::IsotopeComponent,
+        proton_layer::ProtonSublayer, stereochemistry_layer::StereochemistryLayer,
     },
     traits::{
         parse::{ConsumeStr, PrefixFromStrWithContext},
         prefix::Prefix,
     },
-    version::Version,
+    version::{StandardVersion1_07_4, Version, Version1_07_4},
 };
 
 /// Parses a proton-only InChI (no chemical formula).
 ///
 /// After the `/` separator, the input starts with `p` and may optionally
 /// include an isotope layer in the form `/i/hXY`.
-fn parse_proton_only<V: Version>(mut s: &str) -> Result<InChI<V>, Error<u16>> {
-    let proton = ProtonSublayer::try_build_layer(&mut s, ())?;
+fn parse_proton_only<V: Version>(input: &str) -> Result<InChI<V>, ParseError> {
+    let mut s = input;
+    let proton = ProtonSublayer::try_build_layer(&mut s, ())
+        .map_err(|error| ParseError::at_segment(error, input, s))?;
 
     // Parse optional isotope layer: only `/i/hXY` form is valid here
     // (empty atom body + hydrogen isotope sublayer).
@@ -38,8 +36,10 @@ fn parse_proton_only<V: Version>(mut s: &str) -> Result<InChI<V>, Error<u16>> {
             && remainder.as_bytes().get(1).is_some_and(|&b| b == b'D' || b == b'T' || b == b'H')
         {
             let (h_seg, rest) = remainder.split_once('/').unwrap_or((remainder, ""));
+            let hydrogens = super::isotope_layer::parse_h_isotope_segment(h_seg)
+                .map_err(|error| ParseError::at_segment(error, input, remainder))?;
             remainder = rest;
-            super::isotope_layer::parse_h_isotope_segment(h_seg)?
+            hydrogens
         } else {
             Vec::new()
         };
@@ -47,13 +47,18 @@ fn parse_proton_only<V: Version>(mut s: &str) -> Result<InChI<V>, Error<u16>> {
         s = remainder;
         Some(IsotopeLayer {
             components: alloc::vec![IsotopeComponent { atoms: Vec::new(), hydrogens }],
+            stereochemistry: None,
         })
     } else {
         None
     };
 
     if !s.is_empty() {
-        return Err(Error::UnrecognizedLayerPrefix(s.chars().next().unwrap_or('/')));
+        return Err(ParseError::at_segment(
+            Error::UnrecognizedLayerPrefix(s.chars().next().unwrap_or('/')),
+            input,
+            s,
+        ));
     }
 
     Ok(InChI {
@@ -68,87 +73,166 @@ fn parse_proton_only<V: Version>(mut s: &str) -> Result<InChI<V>, Error<u16>> {
     })
 }
 
+/// Checks that the layers following the chemical formula appear in the
+/// order of the InChI layer grammar, each at most once.
+///
+/// The check stops at the reconnected `/r` layer, whose nested layers are
+/// checked when it is parsed, and at the first segment without a known
+/// prefix, which the layer parsers report.
+fn check_layer_order(s: &str) -> Result<(), ParseError> {
+    let mut order = LayerOrder::after_formula();
+    let Some((_, mut remainder)) = s.split_once('/') else {
+        return Ok(());
+    };
+    loop {
+        let layer = LayerId::from_segment(remainder);
+        if matches!(layer, LayerId::Reconnected | LayerId::Unknown) {
+            return Ok(());
+        }
+        order.advance(layer).map_err(|error| ParseError::at_segment(error, s, remainder))?;
+        match remainder.split_once('/') {
+            Some((_, next)) => remainder = next,
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Parses the layers of an InChI that follow the version prefix, starting
+/// from the chemical formula.
+///
+/// This is shared by the top-level parser and by the reconnected `/r` layer,
+/// which holds a complete nested set of layers. The spans of the errors are
+/// relative to `s`.
+pub(super) fn parse_layers<V: Version>(s: &str) -> Result<InChI<V>, ParseError> {
+    // Every layer parser leaves the remainder at the segment that failed, so
+    // errors are located on that segment.
+    let mut layer_remainder = s;
+    let locate =
+        |error: LayerError<u16>, remainder: &str| ParseError::at_segment(error, s, remainder);
+    check_layer_order(s)?;
+
+    // Parse the main layer (formula, connections, hydrogens), then the
+    // remaining layers in their canonical order.
+    let main_layer = MainLayer::consume_str(&mut layer_remainder)
+        .map_err(|error| locate(error, layer_remainder))?;
+
+    let charge =
+        ChargeSubLayer::try_build_layer(&mut layer_remainder, main_layer.chemical_formula())
+            .map_err(|error| locate(error, layer_remainder))?;
+
+    let proton = ProtonSublayer::try_build_layer(&mut layer_remainder, ())
+        .map_err(|error| locate(error, layer_remainder))?;
+
+    let stereochemistry =
+        StereochemistryLayer::try_build_layer(&mut layer_remainder, main_layer.chemical_formula())
+            .map_err(|error| locate(error, layer_remainder))?;
+
+    let isotope =
+        IsotopeLayer::try_build_layer(&mut layer_remainder, main_layer.chemical_formula())
+            .map_err(|error| locate(error, layer_remainder))?;
+
+    let fixed_hydrogen =
+        FixedHydrogenLayer::try_build_layer(&mut layer_remainder, main_layer.chemical_formula())
+            .map_err(|error| locate(error, layer_remainder))?;
+
+    // The reconnected layer holds nested layers, whose errors are already
+    // located relative to the reconnected layer.
+    let reconnected = if layer_remainder.starts_with(ReconnectedLayer::<V>::PREFIX) {
+        let offset = s.len() - layer_remainder.len();
+        let layer = ReconnectedLayer::parse_located(layer_remainder)
+            .map_err(|error| error.offset_by(offset))?;
+        layer_remainder = "";
+        Some(layer)
+    } else {
+        None
+    };
+
+    // Validate that every remaining segment starts with a known layer prefix.
+    if !layer_remainder.is_empty() {
+        loop {
+            let Some(prefix) = layer_remainder.chars().next() else {
+                return Err(locate(Error::UnrecognizedLayerPrefix('/').into(), layer_remainder));
+            };
+            if !crate::constants::KNOWN_LAYER_PREFIXES.contains(&prefix) {
+                return Err(locate(Error::UnrecognizedLayerPrefix(prefix).into(), layer_remainder));
+            }
+            match layer_remainder.split_once('/') {
+                Some((_, next)) => layer_remainder = next,
+                None => break,
+            }
+        }
+    }
+
+    Ok(InChI {
+        main_layer: Some(main_layer),
+        charge,
+        proton,
+        stereochemistry,
+        isotope,
+        fixed_hydrogen,
+        reconnected,
+        _version: core::marker::PhantomData,
+    })
+}
+
 impl<V: Version> FromStr for InChI<V> {
-    type Err = Error<u16>;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         // First we remove the "InChI=" prefix
-        let Some(s) = s.strip_prefix(crate::constants::INCHI_PREFIX) else {
-            return Err(Self::Err::MissingInchiPrefix);
+        let Some(versioned) = s.strip_prefix(crate::constants::INCHI_PREFIX) else {
+            return Err(ParseError::new(Error::MissingInchiPrefix, 0..0, LayerId::Version));
         };
+        let mut offset = crate::constants::INCHI_PREFIX.len();
 
         // Next the version prefix is removed
-        let Some(s) = s.strip_prefix(V::VERSION_PREFIX) else {
-            return Err(Self::Err::MissingVersionPrefix);
+        let Some(layers) = versioned.strip_prefix(V::VERSION_PREFIX) else {
+            let span = offset..offset + versioned.find('/').unwrap_or(versioned.len());
+            return Err(ParseError::new(Error::MissingVersionPrefix, span, LayerId::Version));
         };
+        offset += V::VERSION_PREFIX.len();
 
         // Then we remove the first '/' to get the first layer
-        let Some(s) = s.strip_prefix('/') else {
-            return Err(Self::Err::MissingForwardSlash("Missing chemical formula forward slash"));
+        let Some(layers) = layers.strip_prefix('/') else {
+            return Err(ParseError::new(
+                Error::MissingForwardSlash("Missing chemical formula forward slash"),
+                offset..offset,
+                LayerId::Version,
+            ));
         };
+        offset += 1;
 
         // Proton-only InChIs have no chemical formula — just /p and optionally /i
-        if s.starts_with(ProtonSublayer::PREFIX) {
-            return parse_proton_only(s);
+        if layers.starts_with(ProtonSublayer::PREFIX) {
+            return parse_proton_only(layers).map_err(|error| error.offset_by(offset));
         }
 
-        // Parse the main layer (formula, connections, hydrogens).
-        // Remaining layers after the hydrogen layer are validated but not parsed yet.
-        let (main_layer, mut layer_remainder) = MainLayer::consume_str(s)?;
-
-        let charge =
-            ChargeSubLayer::try_build_layer(&mut layer_remainder, main_layer.chemical_formula())?;
-
-        let proton = ProtonSublayer::try_build_layer(&mut layer_remainder, ())?;
-
-        let double_bond = DoubleBondSublayer::try_build_layer(
-            &mut layer_remainder,
-            main_layer.chemical_formula(),
-        )?;
-        let tetrahedral = TetrahedralSublayer::try_build_layer(
-            &mut layer_remainder,
-            main_layer.chemical_formula(),
-        )?;
-        let allene = AlleneSublayer::try_build_layer(&mut layer_remainder, ())?;
-        let stereo_info =
-            StereoChemistryInformationSublayer::try_build_layer(&mut layer_remainder, ())?;
-
-        let stereochemistry = if double_bond.is_some()
-            || tetrahedral.is_some()
-            || allene.is_some()
-            || stereo_info.is_some()
-        {
-            Some(StereochemistryLayer { double_bond, tetrahedral, allene, stereo_info })
-        } else {
-            None
+        parse_layers(layers).map_err(|error| error.offset_by(offset))
+    }
+}
+
+impl FromStr for AnyInChI {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(versioned) = s.strip_prefix(crate::constants::INCHI_PREFIX) else {
+            return Err(ParseError::new(Error::MissingInchiPrefix, 0..0, LayerId::Version));
         };
 
-        let isotope = IsotopeLayer::try_build_layer(
-            &mut layer_remainder,
-            (main_layer.chemical_formula(), None),
-        )?;
-
-        // Validate that every remaining segment starts with a known layer prefix.
-        if !layer_remainder.is_empty() {
-            for segment in layer_remainder.split('/') {
-                let Some(prefix) = segment.chars().next() else {
-                    return Err(Error::UnrecognizedLayerPrefix('/'));
-                };
-                if !crate::constants::KNOWN_LAYER_PREFIXES.contains(&prefix) {
-                    return Err(Error::UnrecognizedLayerPrefix(prefix));
-                }
-            }
+        // The standard prefix `1S` also starts with the non-standard `1`, so
+        // the version is detected from the whole `<version>/` prefix.
+        if versioned.starts_with(StandardVersion1_07_4::VERSION_PREFIX)
+            && versioned[StandardVersion1_07_4::VERSION_PREFIX.len()..].starts_with('/')
+        {
+            s.parse().map(AnyInChI::Standard)
+        } else if versioned.starts_with(Version1_07_4::VERSION_PREFIX)
+            && versioned[Version1_07_4::VERSION_PREFIX.len()..].starts_with('/')
+        {
+            s.parse().map(AnyInChI::NonStandard)
+        } else {
+            let offset = crate::constants::INCHI_PREFIX.len();
+            let span = offset..offset + versioned.find('/').unwrap_or(versioned.len());
+            Err(ParseError::new(Error::MissingVersionPrefix, span, LayerId::Version))
         }
-
-        Ok(InChI {
-            main_layer: Some(main_layer),
-            charge,
-            proton,
-            stereochemistry,
-            isotope,
-            fixed_hydrogen: None,
-            reconnected: None,
-            _version: core::marker::PhantomData,
-        })
     }
 }
@@ -0,0 +1,275 @@
+//! Generation of the standard InChIKey from a parsed InChI.
+
+mod sha256;
+
+use alloc::string::String;
+use core::{
+    fmt::{self, Display, Write},
+    str::FromStr,
+};
+
+use sha256::sha256;
+
+use crate::{
+    errors::InChIKeyError,
+    inchi::InChI,
+    inchi_key::{INCHI_KEY_LENGTH, InChIKey},
+    version::Version,
+};
+
+/// Positions of the two block separators of an InChIKey.
+const HYPHEN_POSITIONS: [usize; 2] = [14, 25];
+/// Position of the standard flag of an InChIKey.
+const STANDARD_FLAG_POSITION: usize = 23;
+/// Position of the version flag of an InChIKey.
+const VERSION_POSITION: usize = 24;
+
+/// Minor-layer strings shorter than this are hashed twice over, as done by
+/// the reference implementation.
+const MINOR_PART_REPETITION_THRESHOLD: usize = 255;
+
+/// Returns the base-26 triplet with the given 14-bit index.
+///
+/// The table enumerates the triplets `AAA` to `ZZZ` in lexicographic order,
+/// skipping those starting with `E` and the range `TAA` to `TTV`, which
+/// leaves exactly 2^14 triplets.
+fn triplet(index: u32) -> [char; 3] {
+    const SKIPPED_E: u32 = 4 * 26 * 26;
+    const SKIPPED_T: u32 = 19 * 26 * 26;
+    let mut raw = index;
+    if raw >= SKIPPED_E {
+        raw += 26 * 26;
+    }
+    if raw >= SKIPPED_T {
+        raw += 19 * 26 + 22;
+    }
+    [letter(raw / (26 * 26)), letter(raw / 26 % 26), letter(raw % 26)]
+}
+
+/// Returns the base-26 doublet with the given 9-bit index.
+fn doublet(index: u32) -> [char; 2] {
+    [letter(index / 26), letter(index % 26)]
+}
+
+/// Returns the uppercase letter at `position` (0 = `A`).
+fn letter(position: u32) -> char {
+    char::from(b'A' + u8::try_from(position).expect("position is below 26"))
+}
+
+/// Reads `bits` bits of `digest` starting from bit `start`, where bits are
+/// numbered from the least significant bit of the first byte.
+fn bits(digest: &[u8; 32], start: usize, bits: usize) -> u32 {
+    (0..bits).fold(0, |value, offset| {
+        let bit = start + offset;
+        value | u32::from((digest[bit / 8] >> (bit % 8)) & 1) << offset
+    })
+}
+
+/// Returns the protonation flag for the `/p` proton balance: `N` when
+/// neutral, `O` to `Z` for +1 to +12, `M` to `B` for −1 to −12, and `A` when
+/// out of range.
+fn protonation_flag(proton_count: i16) -> char {
+    if (-12..=12).contains(&proton_count) {
+        char::from(b'N'.wrapping_add_signed(i8::try_from(proton_count).expect("within ±12")))
+    } else {
+        'A'
+    }
+}
+
+impl<V: Version> InChI<V> {
+    /// Returns the InChIKey of the InChI.
+    ///
+    /// The key is made of a 14-character block hashing the main layer and the
+    /// charge, an 8-character block hashing the remaining layers, the
+    /// standard and version flags, and a protonation flag encoding the `/p`
+    /// layer: `LFQSCWFLJHTTHZ-UHFFFAOYSA-N`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::inchi::InChI;
+    ///
+    /// let inchi: InChI = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3".parse().unwrap();
+    /// assert_eq!(inchi.inchi_key().as_str(), "LFQSCWFLJHTTHZ-UHFFFAOYSA-N");
+    /// ```
+    #[must_use]
+    pub fn inchi_key(&self) -> InChIKey {
+        let mut major = String::new();
+        let mut minor = String::new();
+        let mut proton_count = 0;
+
+        if let Some(main_layer) = &self.main_layer {
+            let _ = write!(major, "{main_layer}");
+            if let Some(charge) = &self.charge {
+                let _ = write!(major, "/{charge}");
+            }
+            proton_count = self.proton.as_ref().map_or(0, |proton| proton.proton_count);
+        } else if let Some(proton) = &self.proton {
+            // A proton-only InChI has no other connectivity to hash.
+            let _ = write!(major, "{proton}");
+        }
+
+        if let Some(stereochemistry) = &self.stereochemistry {
+            let _ = write!(minor, "/{stereochemistry}");
+        }
+        if let Some(isotope) = &self.isotope {
+            let _ = write!(minor, "/{isotope}");
+        }
+        if let Some(fixed_hydrogen) = &self.fixed_hydrogen {
+            let _ = write!(minor, "/{fixed_hydrogen}");
+        }
+        if let Some(reconnected) = &self.reconnected {
+            let _ = write!(minor, "/{reconnected}");
+        }
+        if !minor.is_empty() && minor.len() < MINOR_PART_REPETITION_THRESHOLD {
+            minor = minor.repeat(2);
+        }
+
+        let major = sha256(major.as_bytes());
+        let minor = sha256(minor.as_bytes());
+
+        let mut key = String::with_capacity(INCHI_KEY_LENGTH);
+        for start in [0, 14, 28, 42] {
+            key.extend(triplet(bits(&major, start, 14)));
+        }
+        key.extend(doublet(bits(&major, 56, 9)));
+        key.push('-');
+        for start in [0, 14] {
+            key.extend(triplet(bits(&minor, start, 14)));
+        }
+        key.extend(doublet(bits(&minor, 28, 9)));
+        key.push(if V::IS_STANDARD { 'S' } else { 'N' });
+        key.push('A');
+        key.push('-');
+        key.push(protonation_flag(proton_count));
+        InChIKey { key }
+    }
+}
+
+impl FromStr for InChIKey {
+    type Err = InChIKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let length = s.chars().count();
+        if length != INCHI_KEY_LENGTH {
+            return Err(InChIKeyError::InvalidLength(length));
+        }
+        for (position, character) in s.chars().enumerate() {
+            if HYPHEN_POSITIONS.contains(&position) {
+                if character != '-' {
+                    return Err(InChIKeyError::MissingHyphen { position, found: character });
+                }
+            } else if !character.is_ascii_uppercase() {
+                return Err(InChIKeyError::InvalidCharacter { position, found: character });
+            }
+        }
+        // All characters are ASCII from here on, so byte positions are
+        // character positions.
+        let bytes = s.as_bytes();
+        let standard_flag = char::from(bytes[STANDARD_FLAG_POSITION]);
+        if !matches!(standard_flag, 'S' | 'N') {
+            return Err(InChIKeyError::InvalidStandardFlag(standard_flag));
+        }
+        let version = char::from(bytes[VERSION_POSITION]);
+        if version != 'A' {
+            return Err(InChIKeyError::UnsupportedVersion(version));
+        }
+        Ok(InChIKey { key: String::from(s) })
+    }
+}
+
+impl Display for InChIKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use core::str::FromStr;
+
+    use super::{doublet, protonation_flag, triplet};
+    use crate::{errors::InChIKeyError, inchi_key::InChIKey};
+
+    #[test]
+    fn test_triplet_table_bounds() {
+        assert_eq!(triplet(0), ['A', 'A', 'A']);
+        assert_eq!(triplet(2703), ['D', 'Z', 'Z']);
+        assert_eq!(triplet(2704), ['F', 'A', 'A']);
+        assert_eq!(triplet(12167), ['S', 'Z', 'Z']);
+        assert_eq!(triplet(12168), ['T', 'T', 'W']);
+        assert_eq!(triplet(16383), ['Z', 'Z', 'Z']);
+    }
+
+    #[test]
+    fn test_doublet_table_bounds() {
+        assert_eq!(doublet(0), ['A', 'A']);
+        assert_eq!(doublet(511), ['T', 'R']);
+    }
+
+    #[test]
+    fn test_protonation_flag() {
+        assert_eq!(protonation_flag(0), 'N');
+        assert_eq!(protonation_flag(1), 'O');
+        assert_eq!(protonation_flag(12), 'Z');
+        assert_eq!(protonation_flag(-1), 'M');
+        assert_eq!(protonation_flag(-12), 'B');
+        assert_eq!(protonation_flag(13), 'A');
+        assert_eq!(protonation_flag(-13), 'A');
+    }
+
+    #[test]
+    fn test_parse_inchi_key() {
+        let key = InChIKey::from_str("LFQSCWFLJHTTHZ-UHFFFAOYSA-N").unwrap();
+        assert_eq!(key.skeleton(), "LFQSCWFLJHTTHZ");
+        assert_eq!(key.stereo_isotope(), "UHFFFAOY");
+        assert!(key.is_standard());
+        assert_eq!(key.version(), 1);
+        assert_eq!(key.protonation(), Some(0));
+        assert_eq!(key.to_string(), "LFQSCWFLJHTTHZ-UHFFFAOYSA-N");
+    }
+
+    #[test]
+    fn test_parse_inchi_key_flags() {
+        let key = InChIKey::from_str("LFQSCWFLJHTTHZ-UHFFFAOYNA-O").unwrap();
+        assert!(!key.is_standard());
+        assert_eq!(key.protonation(), Some(1));
+        let key = InChIKey::from_str("LFQSCWFLJHTTHZ-UHFFFAOYSA-B").unwrap();
+        assert_eq!(key.protonation(), Some(-12));
+        let key = InChIKey::from_str("LFQSCWFLJHTTHZ-UHFFFAOYSA-A").unwrap();
+        assert_eq!(key.protonation(), None);
+    }
+
+    #[test]
+    fn test_invalid_inchi_keys() {
+        assert_eq!(
+            InChIKey::from_str("LFQSCWFLJHTTHZ-UHFFFAOYSA"),
+            Err(InChIKeyError::InvalidLength(25))
+        );
+        assert_eq!(
+            InChIKey::from_str("LFQSCWFLJHTTHZ_UHFFFAOYSA-N"),
+            Err(InChIKeyError::MissingHyphen { position: 14, found: '_' })
+        );
+        assert_eq!(
+            InChIKey::from_str("LFQSCWFLJHTTHZ-UHFFFAOYSAN-"),
+            Err(InChIKeyError::MissingHyphen { position: 25, found: 'N' })
+        );
+        assert_eq!(
+            InChIKey::from_str("LFQSCWFLJhTTHZ-UHFFFAOYSA-N"),
+            Err(InChIKeyError::InvalidCharacter { position: 9, found: 'h' })
+        );
+        assert_eq!(
+            InChIKey::from_str("LFQSCWFLJHTTHZ-UHFFFAOYXA-N"),
+            Err(InChIKeyError::InvalidStandardFlag('X'))
+        );
+        assert_eq!(
+            InChIKey::from_str("LFQSCWFLJHTTHZ-UHFFFAOYSB-N"),
+            Err(InChIKeyError::UnsupportedVersion('B'))
+        );
+        assert_eq!(
+            InChIKey::from_str("LFQSCWFLJHTTHZ-UHFFFAOYSA-\u{e9}"),
+            Err(InChIKeyError::InvalidCharacter { position: 26, found: '\u{e9}' })
+        );
+    }
+}
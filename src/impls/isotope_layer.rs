@@ -1,82 +1,126 @@
-use alloc::{vec, vec::Vec};
+use alloc::{string::ToString, vec, vec::Vec};
+use core::{
+    fmt::{self, Display},
+    iter::Peekable,
+    str::Chars,
+};
 
 use elements_rs::isotopes::HydrogenIsotope;
 use molecular_formulas::{BaselineDigit, InChIFormula, MolecularFormula, try_fold_number};
 
 use crate::{
-    errors::Error,
-    inchi::isotope_layer::{IsotopeAtom, IsotopeComponent, IsotopeHydrogen, IsotopeLayer},
+    errors::{Error, LayerError, LayerId},
+    impls::display::write_components,
+    inchi::{
+        StereochemistryLayer,
+        isotope_layer::{IsotopeAtom, IsotopeComponent, IsotopeHydrogen, IsotopeLayer},
+    },
     traits::{
-        parse::{FromStrWithContext, PrefixFromStrWithContext},
+        parse::{FromStrWithContext, PrefixFromStrWithContext, consumed},
         prefix::Prefix,
     },
 };
 
+/// Parses the optional count following a hydrogen isotope symbol, `1` when
+/// absent, at the position of `chars` over `text`.
+fn parse_h_isotope_count(
+    text: &str,
+    chars: &mut Peekable<Chars<'_>>,
+) -> Result<u16, LayerError<u16>> {
+    let start = consumed(text, chars);
+    let first = chars.peek().copied();
+    match try_fold_number::<u16, BaselineDigit, _>(chars) {
+        None => Ok(1),
+        Some(Ok(count)) => Ok(count),
+        Some(Err(_)) => {
+            Err(LayerError::at(
+                Error::InvalidHydrogenIsotopeCount(first.unwrap_or('0')),
+                start..consumed(text, chars),
+            ))
+        }
+    }
+}
+
 /// Parses hydrogen isotope specs from a segment like `hD2`, `hT`, `hDT3`.
-pub(crate) fn parse_h_isotope_segment(s: &str) -> Result<Vec<IsotopeHydrogen>, Error<u16>> {
-    let s = s.strip_prefix('h').ok_or(Error::InvalidIsotopeValue('h'))?;
+/// The fault of an error is relative to `segment`.
+pub(crate) fn parse_h_isotope_segment(
+    segment: &str,
+) -> Result<Vec<IsotopeHydrogen>, LayerError<u16>> {
+    let s = segment
+        .strip_prefix('h')
+        .ok_or_else(|| LayerError::at_char(Error::InvalidIsotopeValue('h'), segment, 0))?;
     let mut hydrogens = Vec::new();
     let mut chars = s.chars().peekable();
 
-    while chars.peek().is_some() {
-        let isotope = match chars.next() {
-            Some('D') => HydrogenIsotope::D,
-            Some('T') => HydrogenIsotope::T,
-            Some('H') => HydrogenIsotope::H1,
-            Some(c) => return Err(Error::InvalidIsotopeValue(c)),
-            None => unreachable!(),
+    while let Some(&c) = chars.peek() {
+        let isotope = match c {
+            'D' => HydrogenIsotope::D,
+            'T' => HydrogenIsotope::T,
+            'H' => HydrogenIsotope::H1,
+            c => {
+                let error =
+                    LayerError::at_char(Error::InvalidIsotopeValue(c), s, consumed(s, &chars));
+                return Err(error.within(segment, s));
+            }
         };
-        let count = try_fold_number::<u16, BaselineDigit, _>(&mut chars)
-            .transpose()
-            .map_err(|_| Error::InvalidIsotopeValue('0'))?
-            .unwrap_or(1);
+        chars.next();
+        let count =
+            parse_h_isotope_count(s, &mut chars).map_err(|error| error.within(segment, s))?;
         hydrogens.push(IsotopeHydrogen { isotope, count });
     }
 
     Ok(hydrogens)
 }
 
-/// Parses a mass shift value like `+1`, `-3`, `+0`.
+/// Parses a mass shift value like `+1`, `-3`, `+0` at the position of
+/// `chars` over `spec`.
 /// Unlike `parse_charge`, this allows `+0`/`-0`.
-fn parse_mass_shift(
-    chars: &mut core::iter::Peekable<core::str::Chars<'_>>,
-) -> Result<i16, Error<u16>> {
-    let negative = match chars.next() {
-        Some('+') => false,
-        Some('-') => true,
-        Some(c) => return Err(Error::InvalidIsotopeValue(c)),
-        None => return Err(Error::InvalidIsotopeValue('?')),
+fn parse_mass_shift(spec: &str, chars: &mut Peekable<Chars<'_>>) -> Result<i16, LayerError<u16>> {
+    let start = consumed(spec, chars);
+    let sign = match chars.next() {
+        Some(sign @ ('+' | '-')) => sign,
+        Some(c) => return Err(LayerError::at_char(Error::InvalidIsotopeValue(c), spec, start)),
+        None => return Err(LayerError::at_char(Error::InvalidMassShift('?'), spec, start)),
     };
 
     // Handle explicit zero
     if chars.peek() == Some(&'0') {
         chars.next();
-        if chars.peek().is_some_and(char::is_ascii_digit) {
-            return Err(Error::InvalidIsotopeValue('0'));
+        if let Some(&digit) = chars.peek().filter(|c| c.is_ascii_digit()) {
+            return Err(LayerError::at_char(
+                Error::InvalidMassShift(digit),
+                spec,
+                consumed(spec, chars),
+            ));
         }
         return Ok(0);
     }
 
+    let digits = consumed(spec, chars);
+    let first = chars.peek().copied().unwrap_or(sign);
     let Some(Ok(magnitude)) = try_fold_number::<u16, BaselineDigit, _>(chars) else {
-        return Err(Error::InvalidIsotopeValue(if negative { '-' } else { '+' }));
+        return Err(LayerError::at_char(Error::InvalidMassShift(first), spec, digits));
     };
+    let out_of_range =
+        || LayerError::at(Error::InvalidMassShift(first), digits..consumed(spec, chars));
 
-    if negative {
+    if sign == '-' {
         i16::try_from(magnitude).map(|v| -v).or(if magnitude == 32768 {
             Ok(i16::MIN)
         } else {
-            Err(Error::InvalidIsotopeValue('-'))
+            Err(out_of_range())
         })
     } else {
-        i16::try_from(magnitude).map_err(|_| Error::InvalidIsotopeValue('+'))
+        i16::try_from(magnitude).map_err(|_| out_of_range())
     }
 }
 
 /// Parses inline hydrogen isotope designations (D, T, H with optional count)
-/// from a peekable char iterator.
+/// at the position of `chars` over `spec`.
 fn parse_inline_h_isotopes(
-    chars: &mut core::iter::Peekable<core::str::Chars<'_>>,
-) -> Result<Vec<IsotopeHydrogen>, Error<u16>> {
+    spec: &str,
+    chars: &mut Peekable<Chars<'_>>,
+) -> Result<Vec<IsotopeHydrogen>, LayerError<u16>> {
     let mut hydrogens = Vec::new();
     while let Some(&c) = chars.peek() {
         let isotope = match c {
@@ -86,89 +130,70 @@ fn parse_inline_h_isotopes(
             _ => break,
         };
         chars.next();
-        let count = try_fold_number::<u16, BaselineDigit, _>(chars)
-            .transpose()
-            .map_err(|_| Error::InvalidIsotopeValue('0'))?
-            .unwrap_or(1);
+        let count = parse_h_isotope_count(spec, chars)?;
         hydrogens.push(IsotopeHydrogen { isotope, count });
     }
     Ok(hydrogens)
 }
 
-/// Parses atom isotope specs from a string like `1+1,3+2`, `1-1`, `1+0`,
-/// `1D`, `1D3`, `4T`, or `1+1D`.
-fn parse_atom_specs(s: &str) -> Result<Vec<IsotopeAtom>, Error<u16>> {
-    if s.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let mut atoms = Vec::new();
-    for spec in s.split(',') {
-        let mut chars = spec.chars().peekable();
-
-        // Parse 1-based atom index
-        let one_based = match try_fold_number::<u16, BaselineDigit, _>(&mut chars) {
-            Some(Ok(n)) if n > 0 => n,
-            _ => {
-                return Err(Error::InvalidIsotopeValue(spec.chars().next().unwrap_or('?')));
-            }
-        };
-        let atom_index = one_based - 1;
-
-        // Determine what follows the atom index
-        match chars.peek() {
-            Some('+' | '-') => {
-                let mass_shift = parse_mass_shift(&mut chars)?;
-                let hydrogen_isotopes = parse_inline_h_isotopes(&mut chars)?;
-                if chars.peek().is_some() {
-                    return Err(Error::InvalidIsotopeValue(*chars.peek().unwrap()));
-                }
-                atoms.push(IsotopeAtom {
-                    atom_index,
-                    mass_shift: Some(mass_shift),
-                    hydrogen_isotopes,
-                });
-            }
-            Some('D' | 'T' | 'H') => {
-                let hydrogen_isotopes = parse_inline_h_isotopes(&mut chars)?;
-                if chars.peek().is_some() {
-                    return Err(Error::InvalidIsotopeValue(*chars.peek().unwrap()));
-                }
-                atoms.push(IsotopeAtom { atom_index, mass_shift: None, hydrogen_isotopes });
-            }
-            Some(&c) => return Err(Error::InvalidIsotopeValue(c)),
-            None => {
-                return Err(Error::InvalidIsotopeValue(spec.chars().next().unwrap_or('?')));
-            }
+/// Parses a single atom isotope spec like `1+1`, `1-1`, `1+0`, `1D`, `1D3`,
+/// `4T`, or `1+1D`. The fault of an error is relative to `spec`.
+fn parse_atom_spec(spec: &str) -> Result<IsotopeAtom, LayerError<u16>> {
+    let mut chars = spec.chars().peekable();
+
+    // Parse 1-based atom index
+    // An empty spec follows a trailing comma.
+    let first = spec.chars().next().unwrap_or(',');
+    let one_based = match try_fold_number::<u16, BaselineDigit, _>(&mut chars) {
+        Some(Ok(n)) if n > 0 => n,
+        Some(_) => {
+            let error = Error::InvalidIsotopeAtomIndex(first);
+            return Err(LayerError::at(error, 0..consumed(spec, &chars)));
         }
-    }
-
-    Ok(atoms)
+        None => return Err(LayerError::at_char(Error::InvalidIsotopeValue(first), spec, 0)),
+    };
+    let atom_index = one_based - 1;
+
+    // Determine what follows the atom index
+    let mass_shift = match chars.peek() {
+        Some('+' | '-') => Some(parse_mass_shift(spec, &mut chars)?),
+        Some('D' | 'T' | 'H') => None,
+        Some(&c) => {
+            let error = Error::InvalidIsotopeValue(c);
+            return Err(LayerError::at_char(error, spec, consumed(spec, &chars)));
+        }
+        None => {
+            let error = Error::InvalidMassShift(spec.chars().last().unwrap_or(first));
+            return Err(LayerError::at_char(error, spec, spec.len()));
+        }
+    };
+    let hydrogen_isotopes = parse_inline_h_isotopes(spec, &mut chars)?;
+    if let Some(&c) = chars.peek() {
+        return Err(LayerError::at_char(
+            Error::InvalidIsotopeValue(c),
+            spec,
+            consumed(spec, &chars),
+        ));
+    }
+    Ok(IsotopeAtom { atom_index, mass_shift, hydrogen_isotopes })
 }
 
 impl FromStrWithContext for IsotopeLayer {
-    type Context<'a> = (&'a InChIFormula, Option<&'a str>);
+    type Context<'a> = &'a InChIFormula;
     type Input<'a> = &'a str;
     type Idx = u16;
 
-    fn from_str_with_context(
+    fn from_str_located(
         input: Self::Input<'_>,
-        context: Self::Context<'_>,
-    ) -> Result<Self, Error<Self::Idx>> {
+        formula: Self::Context<'_>,
+    ) -> Result<Self, LayerError<Self::Idx>> {
         let s = input.strip_prefix(Self::PREFIX).ok_or(Error::WrongPrefix)?;
-        let (formula, h_segment) = context;
 
-        // Parse hydrogen isotope specs from the /h sublayer (shared across components
-        // when it appears as a single segment without semicolons).
-        let shared_hydrogens =
-            if let Some(h_seg) = h_segment { parse_h_isotope_segment(h_seg)? } else { Vec::new() };
-
-        // If the atom spec body is empty, build a single-component layer with just H
-        // isotopes.
+        // The hydrogen isotopes of the /h sublayer follow the input, and
+        // `try_build_layer` fills them in.
         if s.is_empty() {
-            let components =
-                vec![IsotopeComponent { atoms: Vec::new(), hydrogens: shared_hydrogens }];
-            return Ok(IsotopeLayer { components });
+            let components = vec![IsotopeComponent { atoms: Vec::new(), hydrogens: Vec::new() }];
+            return Ok(IsotopeLayer { components, stereochemistry: None });
         }
 
         let mut subformulas = formula.subformulas();
@@ -187,12 +212,26 @@ impl FromStrWithContext for IsotopeLayer {
                     (1u32, component_str)
                 };
 
-            subformulas.next().ok_or(Error::FormulaAndConnectionLayerMixtureMismatch(
-                formula.number_of_mixtures(),
-            ))?;
-
-            let atoms = parse_atom_specs(component_str)?;
-            let component = IsotopeComponent { atoms, hydrogens: shared_hydrogens.clone() };
+            let subformula = subformulas.next().ok_or(
+                Error::FormulaAndConnectionLayerMixtureMismatch(formula.number_of_mixtures()),
+            )?;
+
+            let num_atoms = subformula.number_of_non_hydrogens();
+            let mut atoms = Vec::new();
+            for spec in component_str.split(',').filter(|_| !component_str.is_empty()) {
+                let atom = parse_atom_spec(spec).map_err(|error| error.within(input, spec))?;
+                if usize::from(atom.atom_index) >= num_atoms {
+                    let error = Error::AtomIndexOutOfBounds {
+                        layer: LayerId::Isotope,
+                        component: components.len(),
+                        index: atom.atom_index + 1,
+                        num_atoms,
+                    };
+                    return Err(LayerError::from(error).within(input, spec));
+                }
+                atoms.push(atom);
+            }
+            let component = IsotopeComponent { atoms, hydrogens: Vec::new() };
 
             components.push(component.clone());
             for _ in 1..reps {
@@ -206,68 +245,147 @@ impl FromStrWithContext for IsotopeLayer {
         if subformulas.next().is_some() {
             return Err(Error::FormulaAndConnectionLayerMixtureMismatch(
                 formula.number_of_mixtures(),
-            ));
+            )
+            .into());
         }
 
-        Ok(IsotopeLayer { components })
+        Ok(IsotopeLayer { components, stereochemistry: None })
     }
 }
 
 impl PrefixFromStrWithContext for IsotopeLayer {
     fn try_build_layer(
         input: &mut &str,
-        context: Self::Context<'_>,
-    ) -> Result<Option<Self>, Error<Self::Idx>> {
+        formula: Self::Context<'_>,
+    ) -> Result<Option<Self>, LayerError<Self::Idx>> {
         if !input.starts_with(Self::PREFIX) {
             return Ok(None);
         }
 
         // Consume the /i segment
-        let (i_segment, mut remainder) = input.split_once('/').unwrap_or((input, ""));
+        let (i_segment, remainder) = input.split_once('/').unwrap_or((input, ""));
+        let mut layer = Self::from_str_located(i_segment, formula)?;
+        *input = remainder;
 
         // Check if the next segment is the hydrogen isotope sublayer (/hD2, /hT, etc.)
         // It starts with 'h' followed by D, T, or H (not a digit, which would be the
         // main hydrogen layer pattern like "h1H2").
-        let h_isotope_segment = if remainder.starts_with('h')
-            && remainder.as_bytes().get(1).is_some_and(|&b| b == b'D' || b == b'T' || b == b'H')
+        if input.starts_with('h')
+            && input.as_bytes().get(1).is_some_and(|&b| b == b'D' || b == b'T' || b == b'H')
         {
-            let (h_seg, rest) = remainder.split_once('/').unwrap_or((remainder, ""));
-            remainder = rest;
-            Some(h_seg)
-        } else {
-            None
-        };
+            let (h_seg, rest) = input.split_once('/').unwrap_or((input, ""));
+            // The hydrogen isotopes are shared by all components.
+            let hydrogens = parse_h_isotope_segment(h_seg)?;
+            for component in &mut layer.components {
+                component.hydrogens.clone_from(&hydrogens);
+            }
+            *input = rest;
+        }
+
+        // Isotope-dependent stereo sublayers (/b, /t, /m, /s) follow the
+        // hydrogen isotope sublayer.
+        layer.stereochemistry = StereochemistryLayer::try_build_layer(input, formula)?;
 
-        *input = remainder;
-        let (formula, _) = context;
-        let layer = Self::from_str_with_context(i_segment, (formula, h_isotope_segment))?;
         Ok(Some(layer))
     }
 }
 
+impl Display for IsotopeHydrogen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self.isotope {
+            HydrogenIsotope::D => 'D',
+            HydrogenIsotope::T => 'T',
+            HydrogenIsotope::H1 => 'H',
+        };
+        write!(f, "{symbol}")?;
+        if self.count != 1 {
+            write!(f, "{}", self.count)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for IsotopeAtom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.atom_index + 1)?;
+        if let Some(mass_shift) = self.mass_shift {
+            write!(f, "{mass_shift:+}")?;
+        }
+        for hydrogen in &self.hydrogen_isotopes {
+            write!(f, "{hydrogen}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for IsotopeComponent {
+    /// Writes the atom specs of the component; the hydrogen isotopes belong
+    /// to the `/h` sublayer and are written by [`IsotopeLayer`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, atom) in self.atoms.iter().enumerate() {
+            if position > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{atom}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for IsotopeLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::PREFIX)?;
+        write_components(f, self.components.iter().map(ToString::to_string))?;
+        // The hydrogen isotope sublayer is shared by all components.
+        if let Some(component) = self.components.first()
+            && !component.hydrogens.is_empty()
+        {
+            write!(f, "/h")?;
+            for hydrogen in &component.hydrogens {
+                write!(f, "{hydrogen}")?;
+            }
+        }
+        if let Some(stereochemistry) = &self.stereochemistry {
+            write!(f, "/{stereochemistry}")?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::{format, string::ToString};
     use core::str::FromStr;
 
     use elements_rs::isotopes::HydrogenIsotope;
     use molecular_formulas::InChIFormula;
 
+    use super::parse_h_isotope_segment;
     use crate::{
-        errors::Error, inchi::isotope_layer::IsotopeLayer, traits::parse::FromStrWithContext,
+        errors::{Error, LayerError, LayerId},
+        inchi::isotope_layer::IsotopeLayer,
+        traits::parse::FromStrWithContext,
     };
 
     fn formula(s: &str) -> InChIFormula {
         InChIFormula::from_str(s).expect("valid formula")
     }
 
-    fn parse(i_layer: &str, h_seg: Option<&str>, ctx: &str) -> Result<IsotopeLayer, Error<u16>> {
-        IsotopeLayer::from_str_with_context(i_layer, (&formula(ctx), h_seg))
+    fn parse(i_layer: &str, ctx: &str) -> Result<IsotopeLayer, Error<u16>> {
+        IsotopeLayer::from_str_with_context(i_layer, &formula(ctx))
+    }
+
+    fn parse_with_h(i_layer: &str, h_seg: &str, ctx: &str) -> Result<IsotopeLayer, Error<u16>> {
+        let input = format!("{i_layer}/{h_seg}");
+        let mut input = input.as_str();
+        let layer = IsotopeLayer::try_build_layer(&mut input, &formula(ctx));
+        layer.map(Option::unwrap).map_err(LayerError::into_kind)
     }
 
     #[test]
     fn test_deuterated_water() {
-        // i/hD2 → body empty, h_segment = "hD2"
-        let result = parse("i", Some("hD2"), "H2O").unwrap();
+        // i/hD2 → body empty, hydrogens from the /hD2 sublayer
+        let result = parse_with_h("i", "hD2", "H2O").unwrap();
         assert_eq!(result.components.len(), 1);
         assert!(result.components[0].atoms.is_empty());
         assert_eq!(result.components[0].hydrogens.len(), 1);
@@ -277,7 +395,7 @@ mod tests {
 
     #[test]
     fn test_tritium() {
-        let result = parse("i", Some("hT"), "H2O").unwrap();
+        let result = parse_with_h("i", "hT", "H2O").unwrap();
         assert_eq!(result.components[0].hydrogens.len(), 1);
         assert_eq!(result.components[0].hydrogens[0].isotope, HydrogenIsotope::T);
         assert_eq!(result.components[0].hydrogens[0].count, 1);
@@ -285,7 +403,7 @@ mod tests {
 
     #[test]
     fn test_protium() {
-        let result = parse("i", Some("hH"), "H2O").unwrap();
+        let result = parse_with_h("i", "hH", "H2O").unwrap();
         assert_eq!(result.components[0].hydrogens.len(), 1);
         assert_eq!(result.components[0].hydrogens[0].isotope, HydrogenIsotope::H1);
         assert_eq!(result.components[0].hydrogens[0].count, 1);
@@ -294,7 +412,7 @@ mod tests {
     #[test]
     fn test_atom_mass_shift_positive() {
         // i1+1 → atom 0, mass shift +1
-        let result = parse("i1+1", None, "CH4").unwrap();
+        let result = parse("i1+1", "CH4").unwrap();
         assert_eq!(result.components.len(), 1);
         assert_eq!(result.components[0].atoms.len(), 1);
         assert_eq!(result.components[0].atoms[0].atom_index, 0);
@@ -302,10 +420,24 @@ mod tests {
         assert!(result.components[0].atoms[0].hydrogen_isotopes.is_empty());
     }
 
+    #[test]
+    fn test_atom_index_out_of_bounds() {
+        let err = parse("i;3+1", "CH4.C2H6").unwrap_err();
+        assert_eq!(
+            err,
+            Error::AtomIndexOutOfBounds {
+                layer: LayerId::Isotope,
+                component: 1,
+                index: 3,
+                num_atoms: 2,
+            }
+        );
+    }
+
     #[test]
     fn test_two_atom_specs() {
         // i1+1,2-1 → atom 0 shift +1, atom 1 shift -1
-        let result = parse("i1+1,2-1", None, "C2H6").unwrap();
+        let result = parse("i1+1,2-1", "C2H6").unwrap();
         assert_eq!(result.components[0].atoms.len(), 2);
         assert_eq!(result.components[0].atoms[0].atom_index, 0);
         assert_eq!(result.components[0].atoms[0].mass_shift, Some(1));
@@ -316,7 +448,7 @@ mod tests {
     #[test]
     fn test_multi_component_first_empty() {
         // i;1+2 → component 0 empty, component 1 atom 0 shift +2
-        let result = parse("i;1+2", None, "H2O.CH4").unwrap();
+        let result = parse("i;1+2", "H2O.CH4").unwrap();
         assert_eq!(result.components.len(), 2);
         assert!(result.components[0].atoms.is_empty());
         assert_eq!(result.components[1].atoms.len(), 1);
@@ -327,7 +459,7 @@ mod tests {
     #[test]
     fn test_empty_body_no_h() {
         // i with nothing → 1 component, no atoms, no H isotopes
-        let result = parse("i", None, "H2O").unwrap();
+        let result = parse("i", "H2O").unwrap();
         assert_eq!(result.components.len(), 1);
         assert!(result.components[0].atoms.is_empty());
         assert!(result.components[0].hydrogens.is_empty());
@@ -335,20 +467,20 @@ mod tests {
 
     #[test]
     fn test_wrong_prefix() {
-        let err = parse("q+1", None, "H2O").unwrap_err();
+        let err = parse("q+1", "H2O").unwrap_err();
         assert!(matches!(err, Error::WrongPrefix));
     }
 
     #[test]
     fn test_invalid_isotope_char() {
-        let err = parse("iX", None, "CH4").unwrap_err();
+        let err = parse("iX", "CH4").unwrap_err();
         assert!(matches!(err, Error::InvalidIsotopeValue('X')));
     }
 
     #[test]
     fn test_multiple_h_isotopes() {
         // hDT3 → D×1 then T×3
-        let result = parse("i", Some("hDT3"), "H2O").unwrap();
+        let result = parse_with_h("i", "hDT3", "H2O").unwrap();
         assert_eq!(result.components[0].hydrogens.len(), 2);
         assert_eq!(result.components[0].hydrogens[0].isotope, HydrogenIsotope::D);
         assert_eq!(result.components[0].hydrogens[0].count, 1);
@@ -359,21 +491,21 @@ mod tests {
     #[test]
     fn test_zero_atom_index() {
         // 0 is a leading zero → rejected by try_fold_number
-        let err = parse("i0+1", None, "CH4").unwrap_err();
-        assert!(matches!(err, Error::InvalidIsotopeValue('0')));
+        let err = parse("i0+1", "CH4").unwrap_err();
+        assert!(matches!(err, Error::InvalidIsotopeAtomIndex('0')));
     }
 
     #[test]
     fn test_missing_sign_in_atom_spec() {
         // "i1" has no +/- sign → error
-        let err = parse("i1", None, "CH4").unwrap_err();
-        assert!(matches!(err, Error::InvalidIsotopeValue(_)));
+        let err = parse("i1", "CH4").unwrap_err();
+        assert!(matches!(err, Error::InvalidMassShift('1')));
     }
 
     #[test]
     fn test_atom_specs_and_h_isotopes_together() {
         // Atom mass shift + hydrogen isotope sublayer
-        let result = parse("i1+1", Some("hD2"), "CH4").unwrap();
+        let result = parse_with_h("i1+1", "hD2", "CH4").unwrap();
         assert_eq!(result.components.len(), 1);
         assert_eq!(result.components[0].atoms.len(), 1);
         assert_eq!(result.components[0].atoms[0].atom_index, 0);
@@ -385,28 +517,28 @@ mod tests {
 
     #[test]
     fn test_invalid_char_in_h_segment() {
-        let err = parse("i", Some("hX"), "H2O").unwrap_err();
+        let err = parse_h_isotope_segment("hX").unwrap_err().into_kind();
         assert!(matches!(err, Error::InvalidIsotopeValue('X')));
     }
 
     #[test]
     fn test_component_count_mismatch_too_many() {
         // Formula has 1 component but isotope layer has 2
-        let err = parse("i1+1;2+1", None, "CH4").unwrap_err();
+        let err = parse("i1+1;2+1", "CH4").unwrap_err();
         assert!(matches!(err, Error::FormulaAndConnectionLayerMixtureMismatch(_)));
     }
 
     #[test]
     fn test_component_count_mismatch_too_few() {
         // Formula has 2 components but isotope layer has 1
-        let err = parse("i1+1", None, "CH4.C2H6").unwrap_err();
+        let err = parse("i1+1", "CH4.C2H6").unwrap_err();
         assert!(matches!(err, Error::FormulaAndConnectionLayerMixtureMismatch(_)));
     }
 
     #[test]
     fn test_repetition_prefix() {
         // 2*1+1 with a 2-component formula → both have atom 0 shift +1
-        let result = parse("i2*1+1", None, "2CH4").unwrap();
+        let result = parse("i2*1+1", "2CH4").unwrap();
         assert_eq!(result.components.len(), 2);
         for comp in &result.components {
             assert_eq!(comp.atoms.len(), 1);
@@ -417,13 +549,13 @@ mod tests {
 
     #[test]
     fn test_negative_mass_shift() {
-        let result = parse("i1-3", None, "CH4").unwrap();
+        let result = parse("i1-3", "CH4").unwrap();
         assert_eq!(result.components[0].atoms[0].mass_shift, Some(-3));
     }
 
     #[test]
     fn test_large_mass_shift() {
-        let result = parse("i1+100", None, "CH4").unwrap();
+        let result = parse("i1+100", "CH4").unwrap();
         assert_eq!(result.components[0].atoms[0].mass_shift, Some(100));
     }
 
@@ -432,7 +564,7 @@ mod tests {
         // Simulates "i1+1/hD2/f..." — should consume both i and h segments
         let f = formula("CH4");
         let mut input = "i1+1/hD2/f1";
-        let result = IsotopeLayer::try_build_layer(&mut input, (&f, None)).unwrap().unwrap();
+        let result = IsotopeLayer::try_build_layer(&mut input, &f).unwrap().unwrap();
         assert_eq!(input, "f1"); // only /f remains
         assert_eq!(result.components[0].atoms.len(), 1);
         assert_eq!(result.components[0].hydrogens.len(), 1);
@@ -444,16 +576,49 @@ mod tests {
         // "i/h1H2" — the /h segment starts with a digit, so it's the main H layer
         let f = formula("H2O");
         let mut input = "i/h1H2";
-        let result = IsotopeLayer::try_build_layer(&mut input, (&f, None)).unwrap().unwrap();
+        let result = IsotopeLayer::try_build_layer(&mut input, &f).unwrap().unwrap();
         assert_eq!(input, "h1H2"); // /h1H2 is NOT consumed
         assert!(result.components[0].hydrogens.is_empty());
     }
 
+    #[test]
+    fn test_try_build_layer_with_stereo_sublayers() {
+        // Deuterium makes atom 2 a stereocentre: "i2D/t2-/m1/s1".
+        let f = formula("C2H6O");
+        let mut input = "i2D/t2-/m1/s1/f1";
+        let result = IsotopeLayer::try_build_layer(&mut input, &f).unwrap().unwrap();
+        assert_eq!(input, "f1");
+        let stereo = result.stereochemistry().unwrap();
+        assert_eq!(stereo.tetrahedral().unwrap().components()[0][0].atom(), 1);
+        assert!(stereo.double_bond().is_none());
+        assert_eq!(result.to_string(), "i2D/t2-/m1/s1");
+    }
+
+    #[test]
+    fn test_try_build_layer_with_h_and_stereo_sublayers() {
+        let f = formula("C4H8");
+        let mut input = "i/hD2/b2-3+";
+        let result = IsotopeLayer::try_build_layer(&mut input, &f).unwrap().unwrap();
+        assert_eq!(input, "");
+        assert_eq!(result.components[0].hydrogens[0].count, 2);
+        let stereo = result.stereochemistry().unwrap();
+        assert_eq!(stereo.double_bond().unwrap().components()[0].len(), 1);
+        assert_eq!(result.to_string(), "i/hD2/b2-3+");
+    }
+
+    #[test]
+    fn test_try_build_layer_without_stereo_sublayers() {
+        let f = formula("CH4");
+        let mut input = "i1+1";
+        let result = IsotopeLayer::try_build_layer(&mut input, &f).unwrap().unwrap();
+        assert!(result.stereochemistry().is_none());
+    }
+
     #[test]
     fn test_try_build_layer_not_present() {
         let f = formula("CH4");
         let mut input = "f1";
-        let result = IsotopeLayer::try_build_layer(&mut input, (&f, None)).unwrap();
+        let result = IsotopeLayer::try_build_layer(&mut input, &f).unwrap();
         assert!(result.is_none());
         assert_eq!(input, "f1"); // input unchanged
     }
@@ -463,7 +628,7 @@ mod tests {
         // "i/hD2" with no trailing segments
         let f = formula("H2O");
         let mut input = "i/hD2";
-        let result = IsotopeLayer::try_build_layer(&mut input, (&f, None)).unwrap().unwrap();
+        let result = IsotopeLayer::try_build_layer(&mut input, &f).unwrap().unwrap();
         assert_eq!(input, ""); // fully consumed
         assert_eq!(result.components[0].hydrogens[0].isotope, HydrogenIsotope::D);
         assert_eq!(result.components[0].hydrogens[0].count, 2);
@@ -474,21 +639,28 @@ mod tests {
     #[test]
     fn test_h_segment_empty_body() {
         // "h" with no isotope letters → empty hydrogen list
-        let result = parse("i", Some("h"), "H2O").unwrap();
-        assert!(result.components[0].hydrogens.is_empty());
+        assert!(parse_h_isotope_segment("h").unwrap().is_empty());
     }
 
     #[test]
     fn test_h_segment_leading_zero_count() {
         // "hD02" → leading zero in count is rejected
-        let err = parse("i", Some("hD02"), "H2O").unwrap_err();
-        assert!(matches!(err, Error::InvalidIsotopeValue('0')));
+        let err = parse_with_h("i", "hD02", "H2O").unwrap_err();
+        assert!(matches!(err, Error::InvalidHydrogenIsotopeCount('0')));
+    }
+
+    #[test]
+    fn test_h_segment_count_overflow() {
+        let err = parse_with_h("i", "hD99999", "H2O").unwrap_err();
+        assert!(matches!(err, Error::InvalidHydrogenIsotopeCount('9')));
+        let err = parse("i1D99999", "CH4").unwrap_err();
+        assert!(matches!(err, Error::InvalidHydrogenIsotopeCount('9')));
     }
 
     #[test]
     fn test_h_segment_trailing_garbage() {
         // "hD2X" → D×2 parsed, then 'X' hits the match
-        let err = parse("i", Some("hD2X"), "H2O").unwrap_err();
+        let err = parse_with_h("i", "hD2X", "H2O").unwrap_err();
         assert!(matches!(err, Error::InvalidIsotopeValue('X')));
     }
 
@@ -497,22 +669,32 @@ mod tests {
     #[test]
     fn test_atom_spec_trailing_comma() {
         // "i1+1," → trailing comma produces an empty spec
-        let err = parse("i1+1,", None, "CH4").unwrap_err();
-        assert!(matches!(err, Error::InvalidIsotopeValue(_)));
+        let err = parse("i1+1,", "CH4").unwrap_err();
+        assert!(matches!(err, Error::InvalidIsotopeValue(',')));
     }
 
     #[test]
     fn test_atom_spec_sign_without_magnitude() {
         // "i1+" → sign but no digits after it
-        let err = parse("i1+", None, "CH4").unwrap_err();
-        assert!(matches!(err, Error::InvalidIsotopeValue(_)));
+        let err = parse("i1+", "CH4").unwrap_err();
+        assert!(matches!(err, Error::InvalidMassShift('+')));
+    }
+
+    #[test]
+    fn test_atom_spec_malformed_mass_shift() {
+        let err = parse("i1+01", "CH4").unwrap_err();
+        assert!(matches!(err, Error::InvalidMassShift('1')));
+        let err = parse("i1-40000", "CH4").unwrap_err();
+        assert!(matches!(err, Error::InvalidMassShift('4')));
+        let err = parse("i1+x", "CH4").unwrap_err();
+        assert!(matches!(err, Error::InvalidMassShift('x')));
     }
 
     #[test]
     fn test_atom_spec_leading_zero_index() {
         // "i01+1" → leading zero before a digit (01 is not valid)
-        let err = parse("i01+1", None, "CH4").unwrap_err();
-        assert!(matches!(err, Error::InvalidIsotopeValue('0')));
+        let err = parse("i01+1", "CH4").unwrap_err();
+        assert!(matches!(err, Error::InvalidIsotopeAtomIndex('0')));
     }
 
     // --- empty body + multi-component ---
@@ -523,7 +705,7 @@ mod tests {
         // This is the documented behavior: empty body + h sublayer is a
         // single-component shorthand. Multi-component isotope layers use
         // semicolons in the atom body.
-        let result = parse("i", Some("hD2"), "H2O.CH4").unwrap();
+        let result = parse_with_h("i", "hD2", "H2O.CH4").unwrap();
         assert_eq!(result.components.len(), 1);
     }
 
@@ -534,7 +716,7 @@ mod tests {
         // "i/h" — 'h' at end with nothing after it; get(1) returns None
         let f = formula("H2O");
         let mut input = "i/h";
-        let result = IsotopeLayer::try_build_layer(&mut input, (&f, None)).unwrap().unwrap();
+        let result = IsotopeLayer::try_build_layer(&mut input, &f).unwrap().unwrap();
         assert_eq!(input, "h"); // bare /h is NOT consumed as isotope sublayer
         assert!(result.components[0].hydrogens.is_empty());
     }
@@ -544,7 +726,7 @@ mod tests {
         // "i/h/f1" — 'h' followed by '/', get(1) is b'/' → not D/T/H
         let f = formula("H2O");
         let mut input = "i/h/f1";
-        let result = IsotopeLayer::try_build_layer(&mut input, (&f, None)).unwrap().unwrap();
+        let result = IsotopeLayer::try_build_layer(&mut input, &f).unwrap().unwrap();
         assert_eq!(input, "h/f1"); // /h/f1 is NOT consumed
         assert!(result.components[0].hydrogens.is_empty());
     }
@@ -554,7 +736,7 @@ mod tests {
         // Just "i" with nothing after — no slash at all
         let f = formula("H2O");
         let mut input = "i";
-        let result = IsotopeLayer::try_build_layer(&mut input, (&f, None)).unwrap().unwrap();
+        let result = IsotopeLayer::try_build_layer(&mut input, &f).unwrap().unwrap();
         assert_eq!(input, "");
         assert!(result.components[0].atoms.is_empty());
         assert!(result.components[0].hydrogens.is_empty());
@@ -565,7 +747,7 @@ mod tests {
     #[test]
     fn test_zero_mass_shift() {
         // i1+0 → atom 0, mass shift 0
-        let result = parse("i1+0", None, "CH4").unwrap();
+        let result = parse("i1+0", "CH4").unwrap();
         assert_eq!(result.components[0].atoms[0].atom_index, 0);
         assert_eq!(result.components[0].atoms[0].mass_shift, Some(0));
         assert!(result.components[0].atoms[0].hydrogen_isotopes.is_empty());
@@ -573,7 +755,7 @@ mod tests {
 
     #[test]
     fn test_negative_zero_mass_shift() {
-        let result = parse("i1-0", None, "CH4").unwrap();
+        let result = parse("i1-0", "CH4").unwrap();
         assert_eq!(result.components[0].atoms[0].mass_shift, Some(0));
     }
 
@@ -582,7 +764,7 @@ mod tests {
     #[test]
     fn test_atom_level_deuterium() {
         // i1D → atom 0, no mass shift, 1 deuterium
-        let result = parse("i1D", None, "CH4").unwrap();
+        let result = parse("i1D", "CH4").unwrap();
         let atom = &result.components[0].atoms[0];
         assert_eq!(atom.atom_index, 0);
         assert_eq!(atom.mass_shift, None);
@@ -594,7 +776,7 @@ mod tests {
     #[test]
     fn test_atom_level_deuterium_count() {
         // i1D3 → atom 0, no mass shift, 3 deuterium
-        let result = parse("i1D3", None, "CH4").unwrap();
+        let result = parse("i1D3", "CH4").unwrap();
         let atom = &result.components[0].atoms[0];
         assert_eq!(atom.mass_shift, None);
         assert_eq!(atom.hydrogen_isotopes[0].isotope, HydrogenIsotope::D);
@@ -604,7 +786,7 @@ mod tests {
     #[test]
     fn test_atom_level_tritium() {
         // i4T → atom 3, no mass shift, 1 tritium
-        let result = parse("i4T", None, "C4H10").unwrap();
+        let result = parse("i4T", "C4H10").unwrap();
         let atom = &result.components[0].atoms[0];
         assert_eq!(atom.atom_index, 3);
         assert_eq!(atom.mass_shift, None);
@@ -615,7 +797,7 @@ mod tests {
     #[test]
     fn test_atom_level_protium() {
         // i2H3 → atom 1, no mass shift, 3× H1
-        let result = parse("i2H3", None, "C2H6").unwrap();
+        let result = parse("i2H3", "C2H6").unwrap();
         let atom = &result.components[0].atoms[0];
         assert_eq!(atom.atom_index, 1);
         assert_eq!(atom.mass_shift, None);
@@ -626,7 +808,7 @@ mod tests {
     #[test]
     fn test_mass_shift_with_inline_deuterium() {
         // i1+1D → atom 0, mass shift +1, 1 deuterium
-        let result = parse("i1+1D", None, "CH4").unwrap();
+        let result = parse("i1+1D", "CH4").unwrap();
         let atom = &result.components[0].atoms[0];
         assert_eq!(atom.atom_index, 0);
         assert_eq!(atom.mass_shift, Some(1));
@@ -638,7 +820,7 @@ mod tests {
     #[test]
     fn test_atom_level_h1() {
         // i1H → atom 0, no mass shift, 1× H1
-        let result = parse("i1H", None, "CH4").unwrap();
+        let result = parse("i1H", "CH4").unwrap();
         let atom = &result.components[0].atoms[0];
         assert_eq!(atom.mass_shift, None);
         assert_eq!(atom.hydrogen_isotopes[0].isotope, HydrogenIsotope::H1);
@@ -648,11 +830,28 @@ mod tests {
     #[test]
     fn test_atom_level_t1() {
         // i1T → atom 0, no mass shift, 1 tritium
-        let result = parse("i1T", None, "CH4").unwrap();
+        let result = parse("i1T", "CH4").unwrap();
         let atom = &result.components[0].atoms[0];
         assert_eq!(atom.mass_shift, None);
         assert_eq!(atom.hydrogen_isotopes[0].isotope, HydrogenIsotope::T);
     }
 
+    // --- Display ---
+
+    #[test]
+    fn test_display_round_trip() {
+        for (i_layer, ctx) in [
+            ("i1+1,2-1", "C2H6"),
+            ("i;1+2", "H2O.CH4"),
+            ("i2*1+1", "2CH4"),
+            ("i1+0", "CH4"),
+            ("i1+1D,4T,2H3", "C4H10"),
+        ] {
+            assert_eq!(parse(i_layer, ctx).unwrap().to_string(), i_layer);
+        }
+        assert_eq!(parse_with_h("i", "hD2", "H2O").unwrap().to_string(), "i/hD2");
+        assert_eq!(parse_with_h("i1+1", "hDT3", "CH4").unwrap().to_string(), "i1+1/hDT3");
+    }
+
     use crate::traits::parse::PrefixFromStrWithContext;
 }
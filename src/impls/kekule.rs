@@ -0,0 +1,599 @@
+//! Assignment of bond orders from the connections, hydrogens and charges.
+
+use alloc::{vec, vec::Vec};
+
+use elements_rs::Element;
+use geometric_traits::prelude::*;
+
+use crate::{
+    errors::KekulizationError,
+    impls::valences::valences,
+    inchi::{BondOrder, Component, InChI, KekuleStructure},
+    version::Version,
+};
+
+/// The number of search steps after which the assignment gives up.
+const MAX_STEPS: usize = 200_000;
+
+/// A mobile hydrogen group, with the atoms it spreads over.
+struct MobileGroup {
+    /// The number of mobile hydrogens.
+    hydrogens: u8,
+    /// The number of mobile negative charges.
+    negative_charges: u8,
+    /// The 0-based indices of the atoms of the group.
+    atoms: Vec<usize>,
+}
+
+/// A bond that can still take an extra electron pair.
+#[derive(Clone, Copy)]
+struct OpenBond {
+    /// The atom at the other end of the bond.
+    neighbour: usize,
+    /// The index of the bond.
+    bond: usize,
+    /// Whether the neighbour has to expand its valence.
+    expands: bool,
+}
+
+/// The backtracking search for a Kekulé structure of one component.
+///
+/// The search places the mobile negative charges, then the rest of the
+/// component charge, then the mobile hydrogens, and finally distributes the
+/// valence each atom has left over its bonds as double and triple bonds. Each
+/// stage tries every choice until the following stages succeed, and keeps the
+/// choices of the first success. Charges are only placed on atoms that can
+/// still reach one of their valences, and hydrogens on atoms whose neighbours
+/// can still take the multiple bonds they need, so that most wrong choices fail
+/// at once.
+struct Search {
+    /// The element of each atom.
+    elements: Vec<Element>,
+    /// The bonds, with the smaller atom index first.
+    bonds: Vec<(u16, u16)>,
+    /// The neighbours of each atom, with the index of the bond to them.
+    adjacency: Vec<Vec<(usize, usize)>>,
+    /// The mobile hydrogen groups.
+    groups: Vec<MobileGroup>,
+    /// The charge of the component.
+    charge: i16,
+    /// The number of pairs of opposite charges to add to the charge of the
+    /// component, as in isocyanides.
+    separated_charges: u16,
+    /// The number of negative charges left to place once the positive ones
+    /// are.
+    pending_negative_charges: u16,
+    /// The first atom whose element has no valence table, which takes the
+    /// charge the mobile groups leave.
+    charge_sink: Option<usize>,
+    /// The hydrogens of each atom.
+    hydrogens: Vec<u8>,
+    /// The formal charge of each atom.
+    charges: Vec<i16>,
+    /// The valence of each atom.
+    valences: Vec<u8>,
+    /// The valence each atom has left to share in multiple bonds.
+    remaining: Vec<u8>,
+    /// The order of each bond beyond a single bond.
+    extra: Vec<u8>,
+    /// The number of search steps taken.
+    steps: usize,
+}
+
+impl Search {
+    /// Returns the Kekulé structure found by the search.
+    fn into_structure(self) -> KekuleStructure {
+        let bonds = self
+            .bonds
+            .into_iter()
+            .zip(self.extra)
+            .map(|(bond, extra)| {
+                let order = match extra {
+                    0 => BondOrder::Single,
+                    1 => BondOrder::Double,
+                    _ => BondOrder::Triple,
+                };
+                (bond, order)
+            })
+            .collect();
+        KekuleStructure { bonds, hydrogens: self.hydrogens, charges: self.charges }
+    }
+
+    /// Takes a search step, returning false once the step limit is reached.
+    fn step(&mut self) -> bool {
+        self.steps += 1;
+        self.steps <= MAX_STEPS
+    }
+
+    /// Returns whether `atom`, with its current charge and hydrogens, can
+    /// still reach one of the valences of its element.
+    fn fits(&self, atom: usize) -> bool {
+        let used = self.adjacency[atom].len() + usize::from(self.hydrogens[atom]);
+        valences(self.elements[atom], self.charges[atom])
+            .is_none_or(|candidates| candidates.last().is_some_and(|&max| usize::from(max) >= used))
+    }
+
+    /// Returns whether the neighbours of `atom` can take the multiple bonds
+    /// it needs to reach the lowest of its valences, with the charges placed
+    /// and the hydrogens placed so far.
+    fn can_pair(&self, atom: usize) -> bool {
+        let used = self.adjacency[atom].len() + usize::from(self.hydrogens[atom]);
+        let Some(candidates) = valences(self.elements[atom], self.charges[atom]) else {
+            return true;
+        };
+        let Some(&valence) = candidates.iter().find(|&&valence| usize::from(valence) >= used)
+        else {
+            return false;
+        };
+        let available: usize = self.adjacency[atom]
+            .iter()
+            .map(|&(neighbour, _)| {
+                let used = self.adjacency[neighbour].len() + usize::from(self.hydrogens[neighbour]);
+                valences(self.elements[neighbour], self.charges[neighbour])
+                    .and_then(<[u8]>::last)
+                    .map_or(0, |&max| usize::from(max).saturating_sub(used).min(2))
+            })
+            .sum();
+        usize::from(valence) - used <= available
+    }
+
+    /// Runs the search, returning whether a structure was found.
+    fn run(&mut self) -> bool {
+        let first = self.groups.first().map_or(0, |group| group.negative_charges);
+        self.place_negative_charges(0, 0, first)
+    }
+
+    /// Puts the `left` mobile negative charges of `group` on distinct atoms
+    /// of the group from `position` on.
+    fn place_negative_charges(&mut self, group: usize, position: usize, left: u8) -> bool {
+        if !self.step() {
+            return false;
+        }
+        let Some(mobile_group) = self.groups.get(group) else {
+            return self.place_charges();
+        };
+        if left == 0 {
+            let next = self.groups.get(group + 1).map_or(0, |next| next.negative_charges);
+            return self.place_negative_charges(group + 1, 0, next);
+        }
+        if mobile_group.atoms.len() - position < usize::from(left) {
+            return false;
+        }
+        let atom = mobile_group.atoms[position];
+        self.charges[atom] -= 1;
+        if self.fits(atom) && self.place_negative_charges(group, position + 1, left - 1) {
+            return true;
+        }
+        self.charges[atom] += 1;
+        self.place_negative_charges(group, position + 1, left)
+    }
+
+    /// Places the charge of the component not carried by the mobile groups,
+    /// with the separated pairs of opposite charges.
+    fn place_charges(&mut self) -> bool {
+        let placed: i16 = self.charges.iter().sum();
+        let mut left = self.charge - placed;
+        let mut sunk = 0;
+        if let Some(sink) = self.charge_sink {
+            self.charges[sink] += left;
+            sunk = left;
+            left = 0;
+        }
+        let pairs = self.separated_charges;
+        self.pending_negative_charges = (-left).max(0).unsigned_abs() + pairs;
+        let found = self.place_unit_charges(0, left.max(0).unsigned_abs() + pairs, 1);
+        if !found && let Some(sink) = self.charge_sink {
+            self.charges[sink] -= sunk;
+        }
+        found
+    }
+
+    /// Puts `left` unit charges of `sign` on the atoms from `atom` on, the
+    /// positive charges before the negative ones.
+    fn place_unit_charges(&mut self, atom: usize, left: u16, sign: i16) -> bool {
+        if !self.step() {
+            return false;
+        }
+        if left == 0 {
+            if sign > 0 {
+                return self.place_unit_charges(0, self.pending_negative_charges, -1);
+            }
+            let first = self.groups.first().map_or(0, |group| group.hydrogens);
+            return self.place_hydrogens(0, 0, first);
+        }
+        if atom == self.elements.len() {
+            return false;
+        }
+        self.charges[atom] += sign;
+        if self.fits(atom) && self.place_unit_charges(atom, left - 1, sign) {
+            return true;
+        }
+        self.charges[atom] -= sign;
+        self.place_unit_charges(atom + 1, left, sign)
+    }
+
+    /// Spreads the `left` mobile hydrogens of `group` over its atoms from
+    /// `position` on.
+    fn place_hydrogens(&mut self, group: usize, position: usize, left: u8) -> bool {
+        if !self.step() {
+            return false;
+        }
+        let Some(mobile_group) = self.groups.get(group) else {
+            return self.choose_valences();
+        };
+        let atom = mobile_group.atoms[position];
+        if position + 1 == mobile_group.atoms.len() {
+            self.hydrogens[atom] += left;
+            let next = self.groups.get(group + 1).map_or(0, |next| next.hydrogens);
+            if self.can_pair(atom) && self.place_hydrogens(group + 1, 0, next) {
+                return true;
+            }
+            self.hydrogens[atom] -= left;
+            return false;
+        }
+        for share in 0..=left {
+            self.hydrogens[atom] += share;
+            if self.can_pair(atom) && self.place_hydrogens(group, position + 1, left - share) {
+                return true;
+            }
+            self.hydrogens[atom] -= share;
+        }
+        false
+    }
+
+    /// Gives every atom the lowest of its valences it can reach, and
+    /// distributes the valences left over the bonds.
+    fn choose_valences(&mut self) -> bool {
+        if !self.step() {
+            return false;
+        }
+        for atom in 0..self.elements.len() {
+            let degree = self.adjacency[atom].len();
+            let used = degree + usize::from(self.hydrogens[atom]);
+            // Atoms without a valence table only take single bonds.
+            let Some(candidates) = valences(self.elements[atom], self.charges[atom]) else {
+                self.valences[atom] = 0;
+                self.remaining[atom] = 0;
+                continue;
+            };
+            let Some(&valence) = candidates.iter().find(|&&valence| usize::from(valence) >= used)
+            else {
+                return false;
+            };
+            let remaining = usize::from(valence) - used;
+            // Each bond can take at most two extra electron pairs.
+            if remaining > 2 * degree {
+                return false;
+            }
+            self.valences[atom] = valence;
+            self.remaining[atom] = u8::try_from(remaining).unwrap_or(u8::MAX);
+        }
+        // Each multiple bond takes a valence from both of its atoms, and
+        // expanding an octet adds two, so an odd total cannot be paired.
+        let total: usize = self.remaining.iter().copied().map(usize::from).sum();
+        // Octets are only expanded when no structure keeps them, so that
+        // pyrrole does not get a pentavalent nitrogen.
+        total.is_multiple_of(2) && (self.assign_bonds(false) || self.assign_bonds(true))
+    }
+
+    /// Returns whether `atom` can expand to its next valence, as the sulfur
+    /// of a sulfone or the nitrogen of a nitro group.
+    fn can_expand(&self, atom: usize) -> bool {
+        valences(self.elements[atom], self.charges[atom])
+            .is_some_and(|candidates| candidates.contains(&(self.valences[atom] + 2)))
+    }
+
+    /// Returns the bonds of `atom` that can still take an extra electron
+    /// pair, with whether the neighbour needs to expand its valence, those
+    /// that do not coming first. Neighbours only expand when `expand` is
+    /// set.
+    fn open_bonds(&self, atom: usize, expand: bool) -> Vec<OpenBond> {
+        let mut open: Vec<OpenBond> = self.adjacency[atom]
+            .iter()
+            .filter(|&&(_, bond)| self.extra[bond] < 2)
+            .filter_map(|&(neighbour, bond)| {
+                if self.remaining[neighbour] > 0 {
+                    Some(OpenBond { neighbour, bond, expands: false })
+                } else {
+                    (expand && self.can_expand(neighbour)).then_some(OpenBond {
+                        neighbour,
+                        bond,
+                        expands: true,
+                    })
+                }
+            })
+            .collect();
+        open.sort_by_key(|open_bond| open_bond.expands);
+        open
+    }
+
+    /// Distributes the remaining valences over the bonds.
+    ///
+    /// The atom with the fewest open bonds is served first, so that atoms
+    /// with a single choice propagate it at once, as in a chain of
+    /// alternating bonds. When `expand` is set, a neighbour left without
+    /// valence expands its octet if an atom needs it.
+    fn assign_bonds(&mut self, expand: bool) -> bool {
+        if !self.step() {
+            return false;
+        }
+        let mut most_constrained: Option<(usize, Vec<OpenBond>)> = None;
+        for atom in (0..self.elements.len()).filter(|&atom| self.remaining[atom] > 0) {
+            let open = self.open_bonds(atom, expand);
+            if open.is_empty() {
+                return false;
+            }
+            if most_constrained.as_ref().is_none_or(|(_, fewest)| open.len() < fewest.len()) {
+                most_constrained = Some((atom, open));
+            }
+        }
+        let Some((atom, open)) = most_constrained else {
+            return true;
+        };
+        for OpenBond { neighbour, bond, expands } in open {
+            if expands {
+                self.valences[neighbour] += 2;
+                self.remaining[neighbour] += 2;
+            }
+            self.extra[bond] += 1;
+            self.remaining[atom] -= 1;
+            self.remaining[neighbour] -= 1;
+            if self.assign_bonds(expand) {
+                return true;
+            }
+            self.extra[bond] -= 1;
+            self.remaining[atom] += 1;
+            self.remaining[neighbour] += 1;
+            if expands {
+                self.valences[neighbour] -= 2;
+                self.remaining[neighbour] -= 2;
+            }
+        }
+        false
+    }
+}
+
+impl Component<'_> {
+    /// Assigns an order to every bond of the component, returning a Kekulé
+    /// structure in which every atom has one of the valences of its element.
+    ///
+    /// The valences depend on the formal charge, so that N⁺ takes four bonds
+    /// like carbon, and atoms below the second period may expand their
+    /// octet. The mobile hydrogens and mobile negative charges are placed on
+    /// atoms of their groups, and the rest of the component charge spread
+    /// over the atoms, or on the first metal when the component has one.
+    /// Components that cannot be written otherwise, such as isocyanides, get
+    /// one pair of opposite charges.
+    /// Atoms of elements without a valence table, such as metals, only take
+    /// single bonds. The structure is the one described by the formula and
+    /// the `/h` layer, before the `/p` (de)protonation.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`KekulizationError`] when the atoms of the component cannot
+    /// be read from its formula, when the component has several atoms but no
+    /// connection layer, when no assignment exists, or when the search gives
+    /// up after too many steps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::inchi::{BondOrder, InChI};
+    ///
+    /// let benzene: InChI = "InChI=1S/C6H6/c1-2-4-6-5-3-1/h1-6H".parse().unwrap();
+    /// let structure = benzene.components().next().unwrap().kekulize().unwrap();
+    /// let double_bonds =
+    ///     structure.bonds().iter().filter(|&&(_, order)| order == BondOrder::Double).count();
+    /// assert_eq!(double_bonds, 3);
+    /// ```
+    pub fn kekulize(&self) -> Result<KekuleStructure, KekulizationError> {
+        let component = self.index();
+        let elements = self.elements()?;
+        let number_of_atoms = elements.len();
+        let mut hydrogens = self.fixed_hydrogens().to_vec();
+        hydrogens.resize(number_of_atoms, 0);
+
+        // Components made only of hydrogen number their hydrogens as atoms,
+        // and have no bonds to assign.
+        if elements.iter().all(|&element| element == Element::H) {
+            let mut charges = vec![0; number_of_atoms];
+            if let Some(first) = charges.first_mut() {
+                *first = self.charge();
+            }
+            return Ok(KekuleStructure { bonds: Vec::new(), hydrogens, charges });
+        }
+
+        let bonds: Vec<(u16, u16)> = match self.graph() {
+            Some(graph) => {
+                (0..graph.number_of_nodes().min(u16::try_from(number_of_atoms).unwrap_or(u16::MAX)))
+                    .flat_map(|atom| {
+                        graph
+                            .neighbors(atom)
+                            .filter(move |&other| {
+                                atom < other && usize::from(other) < number_of_atoms
+                            })
+                            .map(move |other| (atom, other))
+                    })
+                    .collect()
+            }
+            None if number_of_atoms > 1 => {
+                return Err(KekulizationError::MissingConnections { component });
+            }
+            None => Vec::new(),
+        };
+        let mut bonds = bonds;
+        bonds.sort_unstable();
+        let mut adjacency = vec![Vec::new(); number_of_atoms];
+        for (bond, &(a, b)) in bonds.iter().enumerate() {
+            adjacency[usize::from(a)].push((usize::from(b), bond));
+            adjacency[usize::from(b)].push((usize::from(a), bond));
+        }
+
+        let groups = self
+            .mobile_hydrogens()
+            .iter()
+            .map(|group| {
+                MobileGroup {
+                    hydrogens: group.count(),
+                    negative_charges: group.negative_count(),
+                    atoms: group
+                        .atoms()
+                        .iter()
+                        .map(|&atom| usize::from(atom))
+                        .filter(|&atom| atom < number_of_atoms)
+                        .collect(),
+                }
+            })
+            .filter(|group| !group.atoms.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut search = Search {
+            charge_sink: elements.iter().position(|&element| valences(element, 0).is_none()),
+            elements,
+            adjacency,
+            groups,
+            charge: self.charge(),
+            hydrogens,
+            charges: vec![0; number_of_atoms],
+            valences: vec![0; number_of_atoms],
+            remaining: vec![0; number_of_atoms],
+            extra: vec![0; bonds.len()],
+            bonds,
+            steps: 0,
+            separated_charges: 0,
+            pending_negative_charges: 0,
+        };
+        let mut found = search.run();
+        if !found && search.steps <= MAX_STEPS {
+            // Some neutral components, such as isocyanides, can only be
+            // written with a pair of opposite charges.
+            search.separated_charges = 1;
+            found = search.run();
+        }
+        if found {
+            Ok(search.into_structure())
+        } else if search.steps > MAX_STEPS {
+            Err(KekulizationError::SearchLimitExceeded { component })
+        } else {
+            Err(KekulizationError::NoValidAssignment { component })
+        }
+    }
+}
+
+impl<V: Version> InChI<V> {
+    /// Assigns the bond orders of every component, in formula order.
+    ///
+    /// See [`Component::kekulize`] for how each component is handled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::{errors::KekulizationError, inchi::InChI};
+    ///
+    /// // The allyl radical has an odd number of electrons to pair.
+    /// let inchi: InChI = "InChI=1S/C3H5/c1-3-2/h3H,1-2H2".parse().unwrap();
+    /// assert_eq!(inchi.kekulize(), vec![Err(KekulizationError::NoValidAssignment { component: 0 })]);
+    /// ```
+    #[must_use]
+    pub fn kekulize(&self) -> Vec<Result<KekuleStructure, KekulizationError>> {
+        self.components().map(|component| component.kekulize()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{
+        errors::KekulizationError,
+        inchi::{BondOrder, InChI, KekuleStructure},
+    };
+
+    fn kekulize(inchi: &str) -> KekuleStructure {
+        let inchi: InChI = inchi.parse().unwrap();
+        inchi.components().next().unwrap().kekulize().unwrap()
+    }
+
+    fn count(structure: &KekuleStructure, order: BondOrder) -> usize {
+        structure.bonds().iter().filter(|&&(_, bond_order)| bond_order == order).count()
+    }
+
+    #[test]
+    fn test_aromatic_rings() {
+        let benzene = kekulize("InChI=1S/C6H6/c1-2-4-6-5-3-1/h1-6H");
+        assert_eq!(count(&benzene, BondOrder::Double), 3);
+        let pyridine = kekulize("InChI=1S/C5H5N/c1-2-4-6-5-3-1/h1-5H");
+        assert_eq!(count(&pyridine, BondOrder::Double), 3);
+        let pyrrole = kekulize("InChI=1S/C4H5N/c1-2-4-5-3-1/h1-5H");
+        assert_eq!(count(&pyrrole, BondOrder::Double), 2);
+        // Every carbon of benzene takes exactly one double bond.
+        for atom in 0..6 {
+            let doubles = benzene
+                .bonds()
+                .iter()
+                .filter(|&&((a, b), order)| (a == atom || b == atom) && order == BondOrder::Double)
+                .count();
+            assert_eq!(doubles, 1);
+        }
+    }
+
+    #[test]
+    fn test_triple_bond() {
+        // Acetonitrile: C1 methyl, C2 nitrile carbon, N3.
+        let acetonitrile = kekulize("InChI=1S/C2H3N/c1-2-3/h1H3");
+        assert_eq!(acetonitrile.bond_order(0, 1), Some(BondOrder::Single));
+        assert_eq!(acetonitrile.bond_order(1, 2), Some(BondOrder::Triple));
+        assert_eq!(acetonitrile.bond_order(2, 1), Some(BondOrder::Triple));
+        assert_eq!(acetonitrile.bond_order(0, 2), None);
+    }
+
+    #[test]
+    fn test_expanded_valences() {
+        let nitrobenzene = kekulize("InChI=1S/C6H5NO2/c8-7(9)6-4-2-1-3-5-6/h1-5H");
+        assert_eq!(count(&nitrobenzene, BondOrder::Double), 5);
+        // Dimethyl sulfoxide: C1, C2, O3, S4.
+        let dmso = kekulize("InChI=1S/C2H6OS/c1-4(2)3/h1-2H3");
+        assert_eq!(dmso.bond_order(2, 3), Some(BondOrder::Double));
+    }
+
+    #[test]
+    fn test_charge_separation() {
+        // Methyl isocyanide: C1 methyl, C2 isocyanide carbon, N3, written
+        // C⁻≡N⁺.
+        let isocyanide = kekulize("InChI=1S/C2H3N/c1-3-2/h1H3");
+        assert_eq!(isocyanide.bond_order(1, 2), Some(BondOrder::Triple));
+        assert_eq!(isocyanide.charges(), [0, -1, 1]);
+    }
+
+    #[test]
+    fn test_mobile_hydrogens_and_charges() {
+        // Acetic acid: C1, C2, O3, O4, with the acidic hydrogen shared by
+        // both oxygens.
+        let acetic_acid = kekulize("InChI=1S/C2H4O2/c1-2(3)4/h1H3,(H,3,4)");
+        assert_eq!(count(&acetic_acid, BondOrder::Double), 1);
+        assert_eq!(acetic_acid.hydrogens().iter().map(|&h| usize::from(h)).sum::<usize>(), 4);
+
+        let cyclopentadienide = kekulize("InChI=1S/C5H5/c1-2-4-5-3-1/h1-5H/q-1");
+        assert_eq!(count(&cyclopentadienide, BondOrder::Double), 2);
+        assert_eq!(cyclopentadienide.charges().iter().sum::<i16>(), -1);
+
+        let ammonium = kekulize("InChI=1S/H3N/h1H3/p+1");
+        assert_eq!(ammonium.charges(), [0]);
+        let methylammonium = kekulize("InChI=1S/CH5N/c1-2/h2H3,1H3/q+1");
+        assert_eq!(methylammonium.charges(), [0, 1]);
+    }
+
+    #[test]
+    fn test_components_without_assignment() {
+        let inchi: InChI = "InChI=1S/C3H5.C2H6/c1-3-2;1-2/h3H,1-2H2;1-2H3".parse().unwrap();
+        let structures = inchi.kekulize();
+        assert_eq!(structures[0], Err(KekulizationError::NoValidAssignment { component: 0 }));
+        assert!(structures[1].is_ok());
+
+        let hydrogen: InChI = "InChI=1S/H2/h1H".parse().unwrap();
+        assert_eq!(
+            hydrogen.kekulize().into_iter().collect::<Result<Vec<_>, _>>().unwrap().len(),
+            1
+        );
+    }
+}
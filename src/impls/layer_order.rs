@@ -0,0 +1,146 @@
+//! State machine enforcing the order of the `/`-separated layers.
+
+use alloc::vec::Vec;
+
+use crate::errors::{Error, LayerId};
+
+/// A group of layers whose sublayers follow a fixed order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    /// The main layer with its charge, proton and stereo layers.
+    Main,
+    /// The isotope layer with its hydrogen isotope and stereo sublayers.
+    Isotope,
+    /// The fixed-H layer with its sublayers.
+    FixedHydrogen,
+    /// The isotope layer nested in the fixed-H layer.
+    FixedHydrogenIsotope,
+}
+
+impl Block {
+    /// Returns the layers of the block in the order they must appear.
+    fn order(self) -> &'static [LayerId] {
+        const STEREO: [LayerId; 4] = [
+            LayerId::DoubleBondStereo,
+            LayerId::TetrahedralStereo,
+            LayerId::StereoInversion,
+            LayerId::StereoType,
+        ];
+        const MAIN: [LayerId; 9] = [
+            LayerId::Formula,
+            LayerId::Connections,
+            LayerId::Hydrogens,
+            LayerId::Charge,
+            LayerId::Protons,
+            STEREO[0],
+            STEREO[1],
+            STEREO[2],
+            STEREO[3],
+        ];
+        const ISOTOPE: [LayerId; 6] =
+            [LayerId::Isotope, LayerId::Hydrogens, STEREO[0], STEREO[1], STEREO[2], STEREO[3]];
+        const FIXED_HYDROGEN: [LayerId; 8] = [
+            LayerId::FixedHydrogen,
+            LayerId::Hydrogens,
+            LayerId::Charge,
+            STEREO[0],
+            STEREO[1],
+            STEREO[2],
+            STEREO[3],
+            LayerId::Transposition,
+        ];
+        const FIXED_HYDROGEN_ISOTOPE: [LayerId; 7] = [
+            LayerId::Isotope,
+            LayerId::Hydrogens,
+            STEREO[0],
+            STEREO[1],
+            STEREO[2],
+            STEREO[3],
+            LayerId::Transposition,
+        ];
+        match self {
+            Block::Main => &MAIN,
+            Block::Isotope => &ISOTOPE,
+            Block::FixedHydrogen => &FIXED_HYDROGEN,
+            Block::FixedHydrogenIsotope => &FIXED_HYDROGEN_ISOTOPE,
+        }
+    }
+
+    /// Returns the block opened by `layer` when it follows this block.
+    fn open(self, layer: LayerId) -> Option<Block> {
+        match (self, layer) {
+            (Block::Main, LayerId::Isotope) => Some(Block::Isotope),
+            (Block::Main | Block::Isotope, LayerId::FixedHydrogen) => Some(Block::FixedHydrogen),
+            (Block::FixedHydrogen, LayerId::Isotope) => Some(Block::FixedHydrogenIsotope),
+            _ => None,
+        }
+    }
+}
+
+/// Tracks the position reached in the InChI layer grammar.
+///
+/// The grammar is the main layer (formula, `/c`, `/h`, `/q`, `/p`, `/b`,
+/// `/t`, `/m`, `/s`), then an optional `/i` block (`/i`, `/h`, `/b`, `/t`,
+/// `/m`, `/s`), then an optional `/f` block (`/f`, `/h`, `/q`, `/b`, `/t`,
+/// `/m`, `/s`, its own `/i` block, `/o`) and finally an optional `/r` layer,
+/// which holds a complete InChI of its own. Every layer is optional, but the
+/// ones present must follow this order and appear at most once.
+#[derive(Debug)]
+pub(super) struct LayerOrder {
+    /// The block being read.
+    block: Block,
+    /// The position in the order of the block of the last layer read.
+    position: usize,
+    /// The layers read so far, with the block they belong to.
+    seen: Vec<(Block, LayerId)>,
+    /// The last layer read.
+    previous: LayerId,
+}
+
+impl LayerOrder {
+    /// Returns the state right after the chemical formula.
+    pub(super) fn after_formula() -> Self {
+        Self {
+            block: Block::Main,
+            position: 0,
+            seen: alloc::vec![(Block::Main, LayerId::Formula)],
+            previous: LayerId::Formula,
+        }
+    }
+
+    /// Moves to `layer`, returning an error naming it and the previous layer
+    /// when the grammar does not allow it here.
+    pub(super) fn advance(&mut self, layer: LayerId) -> Result<(), Error<u16>> {
+        // The `/o` transposition closes the fixed-H layer, so no block can
+        // be opened after it.
+        let opened =
+            if self.previous == LayerId::Transposition { None } else { self.block.open(layer) };
+        let next = match opened {
+            Some(block) => Some((block, 0)),
+            None => {
+                self.block
+                    .order()
+                    .iter()
+                    .position(|&candidate| candidate == layer)
+                    .filter(|&position| position > self.position)
+                    .map(|position| (self.block, position))
+            }
+        };
+        let Some((block, position)) = next else {
+            let previous = self.previous;
+            let duplicate = self.seen.contains(&(self.block, layer))
+                || (layer == LayerId::FixedHydrogen
+                    && self.seen.iter().any(|&(_, seen)| seen == LayerId::FixedHydrogen));
+            return Err(if duplicate {
+                Error::DuplicateLayer { layer, previous }
+            } else {
+                Error::UnexpectedLayerOrder { layer, previous }
+            });
+        };
+        self.block = block;
+        self.position = position;
+        self.seen.push((block, layer));
+        self.previous = layer;
+        Ok(())
+    }
+}
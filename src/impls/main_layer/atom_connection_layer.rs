@@ -1,32 +1,32 @@
-use core::str::Chars;
+use core::fmt::Write;
 
 use crate::{
     inchi::main_layer::{AtomConnectionLayer, MolecularGraph},
     traits::{
-        parse::{FromStrWithContext, PrefixFromStrWithContext},
+        parse::{FromStrWithContext, PrefixFromStrWithContext, consumed},
         prefix::Prefix,
     },
 };
 pub mod connection_layer_token_iter;
 mod from_connection_layer_token;
-use alloc::vec::Vec;
+use alloc::{string::String, vec, vec::Vec};
 
 use from_connection_layer_token::FromConnectionLayer;
 use geometric_traits::prelude::*;
 use molecular_formulas::{BaselineDigit, InChIFormula, MolecularFormula, try_fold_number};
 
-use crate::errors::Error;
+use crate::errors::{AtomConnectionTokenError, Error, LayerError};
 
 impl FromStrWithContext for AtomConnectionLayer<u16> {
     type Context<'a> = &'a InChIFormula;
     type Input<'a> = &'a str;
     type Idx = u16;
-    fn from_str_with_context(
+    fn from_str_located(
         input: Self::Input<'_>,
         context: Self::Context<'_>,
-    ) -> Result<Self, Error<Self::Idx>> {
+    ) -> Result<Self, LayerError<Self::Idx>> {
         let Some(s) = input.strip_prefix(Self::PREFIX) else {
-            return Err(Error::MissingInchiPrefix);
+            return Err(Error::MissingInchiPrefix.into());
         };
 
         // If there are multiple molecules in the molecular formula, we need to split
@@ -36,31 +36,35 @@ impl FromStrWithContext for AtomConnectionLayer<u16> {
         for molecular_graph_input in s.split(';') {
             let mut molecular_graph_chars = molecular_graph_input.chars().peekable();
 
-            let number_of_repetitions = if let Some(Ok(repetitions)) =
+            let (number_of_repetitions, body) = if let Some(Ok(repetitions)) =
                 try_fold_number::<u32, BaselineDigit, _>(&mut molecular_graph_chars)
                 && molecular_graph_chars.next() == Some('*')
             {
-                repetitions
+                let body = &molecular_graph_input
+                    [consumed(molecular_graph_input, &molecular_graph_chars)..];
+                (repetitions, body)
             } else {
-                molecular_graph_chars = molecular_graph_input.chars().peekable();
-                1
+                (1, molecular_graph_input)
             };
 
             let Some(subformula) = subformulas.next() else {
                 return Err(Error::FormulaAndConnectionLayerMixtureMismatch(
                     context.number_of_mixtures(),
-                ));
+                )
+                .into());
             };
 
             let mg: GenericGraph<u16, SymmetricCSR2D<CSR2D<u16, u16, u16>>> =
-                MolecularGraph::from_str_with_context(molecular_graph_chars, &subformula)?;
+                MolecularGraph::from_str_located(body, &subformula)
+                    .map_err(|error| error.within(input, body))?;
             molecular_graphs.push(mg.clone());
 
             for _ in 1..number_of_repetitions {
                 let Some(_) = subformulas.next() else {
                     return Err(Error::FormulaAndConnectionLayerMixtureMismatch(
                         context.number_of_mixtures(),
-                    ));
+                    )
+                    .into());
                 };
 
                 molecular_graphs.push(mg.clone());
@@ -70,7 +74,8 @@ impl FromStrWithContext for AtomConnectionLayer<u16> {
         if subformulas.next().is_some() {
             return Err(Error::FormulaAndConnectionLayerMixtureMismatch(
                 context.number_of_mixtures(),
-            ));
+            )
+            .into());
         }
 
         Ok(molecular_graphs)
@@ -79,32 +84,204 @@ impl FromStrWithContext for AtomConnectionLayer<u16> {
 
 impl FromStrWithContext for MolecularGraph<u16> {
     type Context<'a> = &'a InChIFormula;
-    type Input<'a> = core::iter::Peekable<Chars<'a>>;
+    type Input<'a> = &'a str;
     type Idx = u16;
-    fn from_str_with_context(
+    fn from_str_located(
         input: Self::Input<'_>,
         context: Self::Context<'_>,
-    ) -> Result<Self, Error<Self::Idx>> {
-        let vocab_size = Self::Idx::try_from(context.number_of_non_hydrogens())?;
+    ) -> Result<Self, LayerError<Self::Idx>> {
+        let number_of_atoms = context.number_of_non_hydrogens();
+        let vocab_size = Self::Idx::try_from(number_of_atoms)?;
         let mut edges: Vec<(Self::Idx, Self::Idx)> = Vec::from_connection_layer_token(input)?;
         edges.sort_unstable();
 
-        let edges: SymmetricCSR2D<CSR2D<Self::Idx, Self::Idx, Self::Idx>> = MolcularGraphEdgesBuilder::default()
-            .expected_number_of_edges(Self::Idx::try_from(edges.len())?)
-            .expected_shape(vocab_size)
-            .edges(edges.clone().into_iter())
-            .build()
-            .unwrap_or_else(|_| panic!(
-                "Failed to build UndiEdgesBuilder: vocabulary size: {vocab_size}, edges: {edges:?}, number of edges: {}",
-                edges.len()
-            ));
-
-        Ok(MolecularGraph::from((vocab_size, edges)))
+        // Edges are 0-based and sorted with the smaller index first, so the
+        // second index of each edge is the one to check against the formula.
+        if let Some(&(_, index)) =
+            edges.iter().find(|&&(_, index)| usize::from(index) >= number_of_atoms)
+        {
+            return Err(AtomConnectionTokenError::AtomIndexOutOfBounds {
+                index: index + 1,
+                num_atoms: number_of_atoms,
+            }
+            .into());
+        }
+        if let Some(pair) = edges.windows(2).find(|pair| pair[0] == pair[1]) {
+            let (left, right) = pair[0];
+            return Err(AtomConnectionTokenError::DuplicateEdge(left + 1, right + 1).into());
+        }
+
+        let number_of_edges = Self::Idx::try_from(edges.len())?;
+        let edges: SymmetricCSR2D<CSR2D<Self::Idx, Self::Idx, Self::Idx>> =
+            MolcularGraphEdgesBuilder::default()
+                .expected_number_of_edges(number_of_edges)
+                .expected_shape(vocab_size)
+                .edges(edges.into_iter())
+                .build()
+                .map_err(|_| AtomConnectionTokenError::InvalidGraph)?;
+        let graph = MolecularGraph::from((vocab_size, edges));
+        check_connectivity(&graph)?;
+
+        Ok(graph)
+    }
+}
+
+/// Checks that a component graph is a single connected piece.
+///
+/// Disconnected fragments are separate components of the formula, so a
+/// multi-atom component must not contain isolated atoms or several fragments.
+fn check_connectivity(graph: &MolecularGraph<u16>) -> Result<(), AtomConnectionTokenError<u16>> {
+    let number_of_atoms = graph.number_of_nodes();
+    if number_of_atoms < 2 {
+        return Ok(());
+    }
+    if let Some(atom) = (0..number_of_atoms).find(|&atom| graph.neighbors(atom).next().is_none()) {
+        return Err(AtomConnectionTokenError::IsolatedAtom(atom + 1));
+    }
+
+    let mut visited = vec![false; usize::from(number_of_atoms)];
+    let mut stack = vec![0];
+    visited[0] = true;
+    while let Some(atom) = stack.pop() {
+        for neighbor in graph.neighbors(atom) {
+            if !visited[usize::from(neighbor)] {
+                visited[usize::from(neighbor)] = true;
+                stack.push(neighbor);
+            }
+        }
+    }
+    match (0..number_of_atoms).find(|&atom| !visited[usize::from(atom)]) {
+        Some(atom) => Err(AtomConnectionTokenError::DisconnectedGraph(atom + 1)),
+        None => Ok(()),
     }
 }
 
 impl PrefixFromStrWithContext for AtomConnectionLayer<u16> {}
 
+/// A step of the iterative connection table writer.
+enum ConnectionTableStep {
+    /// Write an atom and schedule its branches.
+    Atom(usize),
+    /// Write a ring closure to an already written atom.
+    RingClosure(usize),
+    /// Write a separator or a parenthesis.
+    Text(char),
+}
+
+/// Returns the canonical connection table of a single component, without the
+/// `c` prefix.
+///
+/// The table is the depth-first spanning tree used by InChI: it starts from
+/// the lowest-numbered terminal atom (or the lowest-numbered bonded atom when
+/// the component has no terminal atoms) and visits neighbors in ascending
+/// order. Ring closures are written at the atom that closes the ring. When an
+/// atom has several branches, they are written from the smallest subtree to
+/// the largest (counting both atoms and ring closures), ties broken by atom
+/// number, and the last one continues the chain outside the parentheses.
+pub(crate) fn connection_table(graph: &MolecularGraph<u16>) -> String {
+    let adjacency: Vec<Vec<usize>> = (0..graph.number_of_nodes())
+        .map(|atom| {
+            let mut neighbors: Vec<usize> = graph.neighbors(atom).map(usize::from).collect();
+            neighbors.sort_unstable();
+            neighbors
+        })
+        .collect();
+    let number_of_atoms = adjacency.len();
+
+    let mut table = String::new();
+    let Some(root) = (0..number_of_atoms)
+        .find(|&atom| adjacency[atom].len() == 1)
+        .or_else(|| (0..number_of_atoms).find(|&atom| !adjacency[atom].is_empty()))
+    else {
+        return table;
+    };
+
+    // Depth-first traversal building the spanning tree and the ring closures.
+    let mut visited = vec![false; number_of_atoms];
+    let mut finished = vec![false; number_of_atoms];
+    let mut parent: Vec<Option<usize>> = vec![None; number_of_atoms];
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); number_of_atoms];
+    let mut ring_closures: Vec<Vec<usize>> = vec![Vec::new(); number_of_atoms];
+    let mut preorder = Vec::with_capacity(number_of_atoms);
+    let mut stack = vec![(root, 0usize)];
+    visited[root] = true;
+    preorder.push(root);
+    while let Some((atom, next)) = stack.last_mut() {
+        let atom = *atom;
+        let Some(&neighbor) = adjacency[atom].get(*next) else {
+            finished[atom] = true;
+            stack.pop();
+            continue;
+        };
+        *next += 1;
+        if !visited[neighbor] {
+            visited[neighbor] = true;
+            parent[neighbor] = Some(atom);
+            children[atom].push(neighbor);
+            preorder.push(neighbor);
+            stack.push((neighbor, 0));
+        } else if !finished[neighbor] && parent[atom] != Some(neighbor) {
+            ring_closures[atom].push(neighbor);
+        }
+    }
+
+    // Ring closures count towards the size of the subtree that closes them.
+    let mut subtree_sizes: Vec<usize> =
+        ring_closures.iter().map(|closures| 1 + closures.len()).collect();
+    for &atom in preorder.iter().rev() {
+        if let Some(parent) = parent[atom] {
+            subtree_sizes[parent] += subtree_sizes[atom];
+        }
+    }
+
+    let mut steps = vec![ConnectionTableStep::Atom(root)];
+    while let Some(step) = steps.pop() {
+        let atom = match step {
+            ConnectionTableStep::Text(c) => {
+                table.push(c);
+                continue;
+            }
+            ConnectionTableStep::RingClosure(atom) => {
+                let _ = write!(table, "{}", atom + 1);
+                continue;
+            }
+            ConnectionTableStep::Atom(atom) => atom,
+        };
+        let _ = write!(table, "{}", atom + 1);
+
+        let mut branches: Vec<(usize, usize, ConnectionTableStep)> = ring_closures[atom]
+            .iter()
+            .map(|&closure| (0, closure, ConnectionTableStep::RingClosure(closure)))
+            .chain(
+                children[atom]
+                    .iter()
+                    .map(|&child| (subtree_sizes[child], child, ConnectionTableStep::Atom(child))),
+            )
+            .collect();
+        branches.sort_unstable_by_key(|&(size, atom, _)| (size, atom));
+
+        let Some((_, _, last)) = branches.pop() else {
+            continue;
+        };
+        steps.push(last);
+        if branches.is_empty() {
+            steps.push(ConnectionTableStep::Text('-'));
+            continue;
+        }
+        steps.push(ConnectionTableStep::Text(')'));
+        let number_of_branches = branches.len();
+        for (position, (_, _, branch)) in branches.into_iter().rev().enumerate() {
+            steps.push(branch);
+            if position + 1 < number_of_branches {
+                steps.push(ConnectionTableStep::Text(','));
+            }
+        }
+        steps.push(ConnectionTableStep::Text('('));
+    }
+
+    table
+}
+
 /// Type alias for a generic undirected edges list builder.
 pub type MolcularGraphEdgesBuilder<EdgeIterator, AtomIdx, EdgeIdx> =
     GenericUndirectedMonopartiteEdgesBuilder<
@@ -46,6 +46,13 @@ pub(super) struct ConnectionLayerTokenIter<'a, Idx> {
     tokens: ConnectionLayerSubTokenIter<'a, Idx>,
 }
 
+impl<Idx: IndexLike> ConnectionLayerTokenIter<'_, Idx> {
+    /// Returns the number of bytes left to tokenize.
+    pub(super) fn remaining(&self) -> usize {
+        self.tokens.remaining()
+    }
+}
+
 impl<Idx: IndexLike> Iterator for ConnectionLayerTokenIter<'_, Idx> {
     type Item = Result<ConnectionLayerToken<Idx>, AtomConnectionTokenError<Idx>>;
     fn next(&mut self) -> Option<Self::Item> {
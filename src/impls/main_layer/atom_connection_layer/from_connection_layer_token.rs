@@ -2,12 +2,11 @@
 //! edges.
 
 use alloc::vec::Vec;
-use core::str::Chars;
 
 use crate::{
-    errors::AtomConnectionTokenError,
+    errors::{AtomConnectionTokenError, LayerError},
     impls::main_layer::atom_connection_layer::connection_layer_token_iter::{
-        ConnectionLayerToken, ConnectionLayerTokenIter,
+        ConnectionLayerSubToken, ConnectionLayerToken, ConnectionLayerTokenIter,
     },
     traits::IndexLike,
 };
@@ -24,10 +23,9 @@ pub(super) trait FromConnectionLayer: Sized {
     ///
     /// # Errors
     ///
-    /// * Returns `AtomConnectionTokenError` if parsing fails.
-    fn from_connection_layer_token(
-        tokens: core::iter::Peekable<Chars<'_>>,
-    ) -> Result<Self, AtomConnectionTokenError<Self::AtomIndex>>;
+    /// * Returns an `AtomConnectionTokenError` if parsing fails, on the
+    ///   character or token of `tokens` that raised it.
+    fn from_connection_layer_token(tokens: &str) -> Result<Self, LayerError<Self::AtomIndex>>;
 }
 
 /// Adds an edge between two atom indices to the edges vector.
@@ -63,8 +61,12 @@ fn parse_token<Idx: IndexLike>(
             current_atom
         }
         ConnectionLayerToken::Branch(branch) => {
+            // The token iterator rejects a leading branch, but a malformed
+            // input must never panic the parser.
             let Some(last_atom) = last_atom else {
-                unreachable!("Branch cannot be the first token")
+                return Err(AtomConnectionTokenError::IllegalStartingToken(
+                    ConnectionLayerSubToken::OpenParenthesis,
+                ));
             };
             for sub_branch in branch {
                 let mut branch_last_atom = last_atom;
@@ -79,15 +81,33 @@ fn parse_token<Idx: IndexLike>(
 
 impl<Idx: IndexLike> FromConnectionLayer for Vec<(Idx, Idx)> {
     type AtomIndex = Idx;
-    fn from_connection_layer_token(
-        tokens: core::iter::Peekable<Chars<'_>>,
-    ) -> Result<Self, AtomConnectionTokenError<Self::AtomIndex>> {
+    fn from_connection_layer_token(tokens: &str) -> Result<Self, LayerError<Self::AtomIndex>> {
         let mut edges = Vec::new();
-        let iter: ConnectionLayerTokenIter<Idx> = tokens.into();
+        let mut iter: ConnectionLayerTokenIter<Idx> = tokens.chars().peekable().into();
         let mut last_atom: Option<Idx> = None;
-        for maybe_token in iter {
-            let token = maybe_token?;
-            last_atom = Some(parse_token(last_atom, token, &mut edges)?);
+        let mut end = 0;
+        while let Some(maybe_token) = iter.next() {
+            // The dash joining an atom to the previous one is not part of it.
+            let start = end + usize::from(tokens[end..].starts_with('-'));
+            end = tokens.len() - iter.remaining();
+            let token = maybe_token.map_err(|error| {
+                // Character errors are raised on the last character read,
+                // the others on the whole token.
+                let fault = match error {
+                    AtomConnectionTokenError::UnexpectedEndOfInput(_) => end..end,
+                    AtomConnectionTokenError::InvalidCharacter(_)
+                    | AtomConnectionTokenError::IllegalConsecutiveSubTokens { .. } => {
+                        let width = tokens[..end].chars().next_back().map_or(0, char::len_utf8);
+                        end - width..end
+                    }
+                    _ => start..end,
+                };
+                LayerError::at(error, fault)
+            })?;
+            last_atom = Some(
+                parse_token(last_atom, token, &mut edges)
+                    .map_err(|error| LayerError::at(error, start..end))?,
+            );
         }
 
         Ok(edges)
@@ -1,3 +1,6 @@
+use alloc::string::ToString;
+use core::fmt::{self, Display};
+
 use molecular_formulas::{InChIFormula, MolecularFormula};
 pub(crate) mod sub_tokens;
 mod token_iter;
@@ -5,23 +8,110 @@ mod token_iter;
 use token_iter::parse_component;
 
 use crate::{
-    errors::Error,
-    inchi::main_layer::HydrogensSubLayer,
+    errors::{Error, LayerError},
+    impls::display::write_components,
+    inchi::main_layer::{HydrogenComponent, HydrogensSubLayer, MobileHydrogenGroup},
     traits::{
+        IndexLike,
         parse::{FromStrWithContext, PrefixFromStrWithContext},
         prefix::Prefix,
     },
 };
 
+/// Writes sorted 0-based atom indices as comma-separated 1-based indices,
+/// compressing runs of consecutive atoms into ranges (`1-3,5`).
+fn write_atom_ranges(f: &mut fmt::Formatter<'_>, atoms: &[usize]) -> fmt::Result {
+    let mut index = 0;
+    while index < atoms.len() {
+        let start = atoms[index];
+        let mut end = start;
+        while atoms.get(index + 1) == Some(&(end + 1)) {
+            end += 1;
+            index += 1;
+        }
+        if start != atoms[0] {
+            write!(f, ",")?;
+        }
+        if start == end {
+            write!(f, "{}", start + 1)?;
+        } else {
+            write!(f, "{}-{}", start + 1, end + 1)?;
+        }
+        index += 1;
+    }
+    Ok(())
+}
+
+impl<Idx: IndexLike> Display for MobileHydrogenGroup<Idx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(H")?;
+        if self.count != 1 {
+            write!(f, "{}", self.count)?;
+        }
+        match self.negative_count {
+            0 => {}
+            1 => write!(f, "-")?,
+            n => write!(f, "-{n}")?,
+        }
+        for &atom in &self.atoms {
+            write!(f, ",{}", atom + Idx::ONE)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl<Idx: IndexLike> Display for HydrogenComponent<Idx> {
+    /// Writes the fixed hydrogens grouped by count in ascending order
+    /// (`3H,2H2,1H3`), followed by the mobile groups.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let max_count = self.fixed_h.iter().copied().max().unwrap_or(0);
+        let mut has_fixed = false;
+        for count in 1..=max_count {
+            let atoms: alloc::vec::Vec<usize> = self
+                .fixed_h
+                .iter()
+                .enumerate()
+                .filter_map(|(atom, &h)| (h == count).then_some(atom))
+                .collect();
+            if atoms.is_empty() {
+                continue;
+            }
+            if has_fixed {
+                write!(f, ",")?;
+            }
+            has_fixed = true;
+            write_atom_ranges(f, &atoms)?;
+            write!(f, "H")?;
+            if count > 1 {
+                write!(f, "{count}")?;
+            }
+        }
+        if has_fixed && !self.mobile_groups.is_empty() {
+            write!(f, ",")?;
+        }
+        for group in &self.mobile_groups {
+            write!(f, "{group}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for HydrogensSubLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::PREFIX)?;
+        write_components(f, self.components.iter().map(ToString::to_string))
+    }
+}
+
 impl FromStrWithContext for HydrogensSubLayer {
     type Context<'a> = &'a InChIFormula;
     type Input<'a> = &'a str;
     type Idx = u16;
 
-    fn from_str_with_context(
+    fn from_str_located(
         input: Self::Input<'_>,
         context: Self::Context<'_>,
-    ) -> Result<Self, Error<Self::Idx>> {
+    ) -> Result<Self, LayerError<Self::Idx>> {
         let s = input.strip_prefix(Self::PREFIX).ok_or(Error::WrongPrefix)?;
 
         let mut subformulas = context.subformulas();
@@ -49,7 +139,8 @@ impl FromStrWithContext for HydrogensSubLayer {
             let num_atoms = if non_h == 0 { sf.number_of_elements() } else { non_h };
 
             // Parse the component
-            let component = parse_component::<u16>(component_str, num_atoms)?;
+            let component = parse_component::<u16>(component_str, num_atoms)
+                .map_err(|error| error.within(input, component_str))?;
 
             // Push the first occurrence then n-1 clones for repeated fragments
             components.push(component.clone());
@@ -64,7 +155,8 @@ impl FromStrWithContext for HydrogensSubLayer {
         if subformulas.next().is_some() {
             return Err(Error::FormulaAndConnectionLayerMixtureMismatch(
                 context.number_of_mixtures(),
-            ));
+            )
+            .into());
         }
 
         Ok(HydrogensSubLayer { components })
@@ -75,6 +167,7 @@ impl PrefixFromStrWithContext for HydrogensSubLayer {}
 
 #[cfg(test)]
 mod tests {
+    use alloc::string::ToString;
     use core::str::FromStr;
 
     use molecular_formulas::InChIFormula;
@@ -238,6 +331,22 @@ mod tests {
         assert_eq!(mg.negative_count, 4);
     }
 
+    #[test]
+    fn test_display_round_trip() {
+        for (h_layer, ctx) in [
+            ("h3H,2H2,1H3", "C2H6O"),
+            ("h2,5,7-10H,1H2", "C6H8O6"),
+            ("h2H,(H,1,3)", "C3H8"),
+            ("h1-6H3,(H,7,8)(H2,9,10)", "C10H24"),
+            ("h(H-,1,2)", "C2H6"),
+            ("h(H7-10,1,2,3,4,5,6,7)", "C7H21"),
+            ("h;2*1H2", "C9H6O6.2H2O"),
+            ("h1H;", "CH4.C2H6"),
+        ] {
+            assert_eq!(parse(h_layer, ctx).unwrap().to_string(), h_layer);
+        }
+    }
+
     #[test]
     fn test_h2_formula() {
         // H2 with h1H → atom 1 (index 0) has 1 fixed H
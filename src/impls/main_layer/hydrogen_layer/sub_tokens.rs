@@ -83,6 +83,11 @@ impl<'a, Idx> From<&'a str> for HydrogenLayerSubTokenIter<'a, Idx> {
 }
 
 impl<Idx: IndexLike> HydrogenLayerSubTokenIter<'_, Idx> {
+    /// Returns the number of bytes left to tokenize.
+    pub fn remaining(&self) -> usize {
+        self.chars.clone().map(char::len_utf8).sum()
+    }
+
     /// Returns whether the next character is a digit.
     pub fn peek_is_digit(&mut self) -> Option<bool> {
         Some(self.chars.peek()?.is_ascii_digit())
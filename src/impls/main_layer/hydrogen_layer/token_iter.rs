@@ -1,10 +1,11 @@
 //! Component-level parser for the hydrogen layer.
 
 use alloc::vec::Vec;
+use core::ops::Range;
 
 use super::sub_tokens::{HydrogenLayerSubTokenIter, HydrogenLayerSubTokens};
 use crate::{
-    errors::HydrogenLayerTokenError,
+    errors::{HydrogenLayerTokenError, LayerError},
     inchi::main_layer::{HydrogenComponent, MobileHydrogenGroup},
     traits::IndexLike,
 };
@@ -30,11 +31,12 @@ fn validate_atom_index<Idx: IndexLike>(
 ///
 /// Atom indices are converted from 1-based to 0-based here.
 /// `num_atoms` comes from the subformula's non-hydrogen atom count and is used
-/// to pre-size the `fixed_h` array.
+/// to pre-size the `fixed_h` array. The fault of an error is the token of
+/// `input` that raised it.
 pub(super) fn parse_component<Idx>(
     input: &str,
     num_atoms: usize,
-) -> Result<HydrogenComponent<Idx>, HydrogenLayerTokenError<Idx>>
+) -> Result<HydrogenComponent<Idx>, LayerError<Idx>>
 where
     Idx: IndexLike,
     u8: TryFrom<Idx, Error = core::num::TryFromIntError>,
@@ -42,8 +44,9 @@ where
     let mut fixed_h: Vec<u8> = alloc::vec![0u8; num_atoms];
     let mut mobile_groups: Vec<MobileHydrogenGroup<Idx>> = Vec::new();
 
-    // Pending atom indices (1-based) accumulated before the H token
-    let mut atom_buf: Vec<Idx> = Vec::new();
+    // Pending atom indices (1-based) accumulated before the H token, with the
+    // bytes of the token they come from
+    let mut atom_buf: Vec<(Idx, Range<usize>)> = Vec::new();
 
     // Mobile-group state
     let mut in_mobile = false;
@@ -51,10 +54,14 @@ where
     let mut mobile_negative_count = 0u8;
     let mut mobile_atoms: Vec<Idx> = Vec::new();
 
-    let iter = HydrogenLayerSubTokenIter::<Idx>::from(input);
+    let mut iter = HydrogenLayerSubTokenIter::<Idx>::from(input);
 
-    for result in iter {
-        let token = result?;
+    let mut start = 0;
+    while let Some(result) = iter.next() {
+        let span = start..input.len() - iter.remaining();
+        start = span.end;
+        let locate = |error: HydrogenLayerTokenError<Idx>| LayerError::at(error, span.clone());
+        let token = result.map_err(locate)?;
         if in_mobile {
             match token {
                 HydrogenLayerSubTokens::Comma => {
@@ -62,12 +69,11 @@ where
                     // it
                 }
                 HydrogenLayerSubTokens::Index(n) => {
-                    validate_atom_index(n, num_atoms)?;
+                    validate_atom_index(n, num_atoms).map_err(locate)?;
                     mobile_atoms.push(n);
                 }
                 HydrogenLayerSubTokens::CloseParenthesis => {
-                    // Convert 1-based to 0-based and emit the group
-                    // (indices already validated above)
+                    // Emit the group with 0-based indices, validated above
                     let atoms = mobile_atoms.drain(..).map(|a| a - Idx::ONE).collect();
                     mobile_groups.push(MobileHydrogenGroup {
                         count: mobile_count,
@@ -78,16 +84,16 @@ where
                 }
                 HydrogenLayerSubTokens::Range(_) => {
                     // Ranges are not valid inside mobile groups
-                    return Err(HydrogenLayerTokenError::InvalidCharacter('-'));
+                    return Err(locate(HydrogenLayerTokenError::InvalidCharacter('-')));
                 }
                 HydrogenLayerSubTokens::H(_) => {
-                    return Err(HydrogenLayerTokenError::InvalidCharacter('H'));
+                    return Err(locate(HydrogenLayerTokenError::InvalidCharacter('H')));
                 }
                 HydrogenLayerSubTokens::SharedHydrogens { .. } => {
-                    return Err(HydrogenLayerTokenError::InvalidCharacter('('));
+                    return Err(locate(HydrogenLayerTokenError::InvalidCharacter('(')));
                 }
                 HydrogenLayerSubTokens::Asterisk(_) => {
-                    return Err(HydrogenLayerTokenError::InvalidCharacter('*'));
+                    return Err(locate(HydrogenLayerTokenError::InvalidCharacter('*')));
                 }
             }
         } else {
@@ -97,19 +103,19 @@ where
                     // blocks — skip it
                 }
                 HydrogenLayerSubTokens::Index(n) => {
-                    atom_buf.push(n);
+                    atom_buf.push((n, span));
                 }
                 HydrogenLayerSubTokens::Range((s, e)) => {
                     if s > e {
-                        return Err(HydrogenLayerTokenError::InvalidRange(s, e));
+                        return Err(locate(HydrogenLayerTokenError::InvalidRange(s, e)));
                     }
                     // Validate both endpoints
-                    validate_atom_index(s, num_atoms)?;
-                    validate_atom_index(e, num_atoms)?;
+                    validate_atom_index(s, num_atoms).map_err(locate)?;
+                    validate_atom_index(e, num_atoms).map_err(locate)?;
                     // Expand the range into atom_buf (1-based indices)
                     let mut i = s;
                     loop {
-                        atom_buf.push(i);
+                        atom_buf.push((i, span.clone()));
                         if i == e {
                             break;
                         }
@@ -118,8 +124,9 @@ where
                 }
                 HydrogenLayerSubTokens::H(count) => {
                     // Assign fixed H count to all pending atoms
-                    for idx in atom_buf.drain(..) {
-                        let zero_based = validate_atom_index(idx, num_atoms)?;
+                    for (idx, span) in atom_buf.drain(..) {
+                        let zero_based = validate_atom_index(idx, num_atoms)
+                            .map_err(|error| LayerError::at(error, span))?;
                         fixed_h[zero_based] = count;
                     }
                 }
@@ -131,12 +138,12 @@ where
                     mobile_atoms.clear();
                 }
                 HydrogenLayerSubTokens::CloseParenthesis => {
-                    return Err(HydrogenLayerTokenError::InvalidCharacter(')'));
+                    return Err(locate(HydrogenLayerTokenError::InvalidCharacter(')')));
                 }
                 HydrogenLayerSubTokens::Asterisk(_) => {
                     // Asterisk should have been consumed by the caller before invoking
                     // parse_component
-                    return Err(HydrogenLayerTokenError::InvalidCharacter('*'));
+                    return Err(locate(HydrogenLayerTokenError::InvalidCharacter('*')));
                 }
             }
         }
@@ -144,16 +151,16 @@ where
 
     // Unclosed mobile group
     if in_mobile {
-        return Err(HydrogenLayerTokenError::UnexpectedEndOfInput(
-            HydrogenLayerSubTokens::CloseParenthesis,
-        ));
+        let error =
+            HydrogenLayerTokenError::UnexpectedEndOfInput(HydrogenLayerSubTokens::CloseParenthesis);
+        return Err(LayerError::at(error, input.len()..input.len()));
     }
 
     // Pending atoms without a following H token
-    if let Some(&last) = atom_buf.last() {
-        return Err(HydrogenLayerTokenError::UnexpectedEndOfInput(HydrogenLayerSubTokens::Index(
-            last,
-        )));
+    if let Some((last, span)) = atom_buf.pop() {
+        let error =
+            HydrogenLayerTokenError::UnexpectedEndOfInput(HydrogenLayerSubTokens::Index(last));
+        return Err(LayerError::at(error, span));
     }
 
     Ok(HydrogenComponent { fixed_h, mobile_groups })
@@ -0,0 +1,261 @@
+//! Lenient parsing of InChIs with [`ParseOptions`].
+
+use alloc::{string::String, vec::Vec};
+use core::ops::Range;
+
+use crate::{
+    constants::INCHI_PREFIX,
+    errors::{LayerId, ParseError},
+    inchi::InChI,
+    parse_options::{ParseOptions, ParseWarning},
+    version::Version,
+};
+
+/// A slice of the fixed input, copied from the original one or inserted.
+pub(super) struct Piece {
+    /// The byte offset of the piece in the fixed input.
+    fixed: usize,
+    /// The byte offset of the piece in the original input.
+    original: usize,
+    /// Whether the piece was inserted rather than copied.
+    inserted: bool,
+}
+
+/// An input rewritten by the lenient fixes, which keeps track of where each
+/// of its bytes comes from in the original input.
+#[derive(Default)]
+pub(super) struct FixedInput {
+    /// The rewritten input.
+    pub(super) text: String,
+    /// The pieces making up the rewritten input, in order.
+    pieces: Vec<Piece>,
+}
+
+impl FixedInput {
+    /// Appends the `/`-separated segments of `text` for which `keep` returns
+    /// true, given the index of the segment, its text and its span in the
+    /// original input, in which `text` starts at byte `start`.
+    pub(super) fn push_segments(
+        &mut self,
+        text: &str,
+        start: usize,
+        mut keep: impl FnMut(usize, &str, Range<usize>) -> bool,
+    ) {
+        let mut offset = start;
+        for (index, segment) in text.split('/').enumerate() {
+            let span_start = offset + usize::from(index > 0);
+            let span = span_start..span_start + segment.len();
+            if keep(index, segment, span) {
+                if index > 0 {
+                    self.push_copied("/", offset);
+                }
+                self.push_copied(segment, span_start);
+            }
+            offset = span_start + segment.len();
+        }
+    }
+
+    /// Appends `text`, which starts at byte `original` of the original input.
+    fn push_copied(&mut self, text: &str, original: usize) {
+        self.pieces.push(Piece { fixed: self.text.len(), original, inserted: false });
+        self.text.push_str(text);
+    }
+
+    /// Appends `text`, which is not part of the original input and is
+    /// located at byte `original` of it.
+    fn push_inserted(&mut self, text: &str, original: usize) {
+        self.pieces.push(Piece { fixed: self.text.len(), original, inserted: true });
+        self.text.push_str(text);
+    }
+
+    /// Returns the position in the original input of byte `position` of the
+    /// rewritten input.
+    fn original_position(&self, position: usize) -> usize {
+        self.pieces.iter().rev().find(|piece| piece.fixed <= position).map_or(position, |piece| {
+            if piece.inserted { piece.original } else { piece.original + position - piece.fixed }
+        })
+    }
+
+    /// Returns the range of the original input matching `span` of the
+    /// rewritten input.
+    pub(super) fn original_span(&self, span: Range<usize>) -> Range<usize> {
+        let start = self.original_position(span.start);
+        let end =
+            if span.end > span.start { self.original_position(span.end - 1) + 1 } else { start };
+        start..end.max(start)
+    }
+}
+
+impl<V: Version> InChI<V> {
+    /// Parses an InChI, fixing the defects selected by `options` instead of
+    /// rejecting them, and returns it with a warning for every fix applied.
+    ///
+    /// With the default, strict options this behaves like
+    /// [`FromStr`](core::str::FromStr). The spans of the errors and warnings
+    /// refer to the original input.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] when the input is not a valid InChI once the
+    /// selected fixes are applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::{
+    ///     inchi::InChI,
+    ///     parse_options::{ParseOptions, ParseWarning},
+    ///     version::StandardVersion1_07_4,
+    /// };
+    ///
+    /// let (inchi, warnings): (InChI, _) =
+    ///     InChI::parse_with(" 1S/CH4/h1H4/\n", ParseOptions::lenient()).unwrap();
+    /// assert_eq!(inchi, "InChI=1S/CH4/h1H4".parse::<InChI>().unwrap());
+    /// assert_eq!(
+    ///     warnings,
+    ///     [
+    ///         ParseWarning::TrimmedWhitespace,
+    ///         ParseWarning::AddedPrefix,
+    ///         ParseWarning::RemovedTrailingSlash(12)
+    ///     ]
+    /// );
+    ///
+    /// let strict = ParseOptions::strict();
+    /// assert!(InChI::<StandardVersion1_07_4>::parse_with(" 1S/CH4/h1H4/\n", strict).is_err());
+    /// ```
+    pub fn parse_with(
+        input: &str,
+        options: ParseOptions,
+    ) -> Result<(Self, Vec<ParseWarning>), ParseError> {
+        let mut warnings = Vec::new();
+        let mut text = input;
+        let mut start = 0;
+        if options.trim_whitespace && text.trim() != text {
+            start = text.len() - text.trim_start().len();
+            text = text.trim();
+            warnings.push(ParseWarning::TrimmedWhitespace);
+        }
+
+        let mut fixed = FixedInput::default();
+        if options.allow_missing_prefix && !text.starts_with(INCHI_PREFIX) {
+            fixed.push_inserted(INCHI_PREFIX, start);
+            warnings.push(ParseWarning::AddedPrefix);
+        }
+        if options.allow_trailing_slash
+            && let Some(stripped) = text.strip_suffix('/')
+        {
+            text = stripped;
+            warnings.push(ParseWarning::RemovedTrailingSlash(start + text.len()));
+        }
+
+        // The first segment holds the version and the second the formula,
+        // so only the following ones are layers with a prefix.
+        fixed.push_segments(text, start, |index, segment, span| {
+            let unknown = index >= 2
+                && options.skip_unknown_layers
+                && LayerId::from_segment(segment) == LayerId::Unknown;
+            if unknown {
+                warnings.push(ParseWarning::SkippedUnknownLayer { segment: segment.into(), span });
+            }
+            !unknown
+        });
+
+        let inchi = fixed
+            .text
+            .parse::<Self>()
+            .map_err(|error| error.map_span(|span| fixed.original_span(span)))?;
+        Ok((inchi, warnings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        string::{String, ToString},
+        vec::Vec,
+    };
+
+    use crate::{
+        errors::{Error, LayerId},
+        inchi::InChI,
+        parse_options::{ParseOptions, ParseWarning},
+        version::StandardVersion1_07_4,
+    };
+
+    const ETHANOL: &str = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3";
+
+    fn parse(input: &str, options: ParseOptions) -> (InChI, Vec<ParseWarning>) {
+        InChI::parse_with(input, options).unwrap()
+    }
+
+    #[test]
+    fn test_strict_options_match_from_str() {
+        let (inchi, warnings) = parse(ETHANOL, ParseOptions::strict());
+        assert_eq!(inchi, ETHANOL.parse::<InChI>().unwrap());
+        assert!(warnings.is_empty());
+        for input in [" InChI=1S/CH4/h1H4", "1S/CH4/h1H4", "InChI=1S/CH4/h1H4/", "InChI=1S/CH4/x1"]
+        {
+            assert_eq!(
+                InChI::<StandardVersion1_07_4>::parse_with(input, ParseOptions::strict()),
+                input.parse::<InChI>().map(|inchi| (inchi, Vec::new())),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_lenient_options_without_defects() {
+        let (inchi, warnings) = parse(ETHANOL, ParseOptions::lenient());
+        assert_eq!(inchi, ETHANOL.parse::<InChI>().unwrap());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_each_option_fixes_only_its_defect() {
+        let options = ParseOptions::strict().with_trim_whitespace(true);
+        let (_, warnings) = parse("\t InChI=1S/CH4/h1H4\r\n", options);
+        assert_eq!(warnings, [ParseWarning::TrimmedWhitespace]);
+        assert!(InChI::<StandardVersion1_07_4>::parse_with(" 1S/CH4/h1H4", options).is_err());
+
+        let options = ParseOptions::strict().with_allow_missing_prefix(true);
+        let (_, warnings) = parse("1S/CH4/h1H4", options);
+        assert_eq!(warnings, [ParseWarning::AddedPrefix]);
+
+        let options = ParseOptions::strict().with_allow_trailing_slash(true);
+        let (_, warnings) = parse("InChI=1S/CH4/h1H4/", options);
+        assert_eq!(warnings, [ParseWarning::RemovedTrailingSlash(17)]);
+    }
+
+    #[test]
+    fn test_skip_unknown_layers() {
+        let options = ParseOptions::strict().with_skip_unknown_layers(true);
+        let input = "InChI=1S/C2H6O/c1-2-3/x12/h3H,2H2,1H3//z";
+        let (inchi, warnings) = parse(input, options);
+        assert_eq!(inchi, ETHANOL.parse::<InChI>().unwrap());
+        assert_eq!(
+            warnings,
+            [
+                ParseWarning::SkippedUnknownLayer { segment: "x12".to_string(), span: 22..25 },
+                ParseWarning::SkippedUnknownLayer { segment: String::new(), span: 38..38 },
+                ParseWarning::SkippedUnknownLayer { segment: "z".to_string(), span: 39..40 },
+            ]
+        );
+        assert_eq!(&input[22..25], "x12");
+        assert_eq!(&input[39..40], "z");
+    }
+
+    #[test]
+    fn test_error_spans_refer_to_original_input() {
+        let input = "  1S/C2H6O/c1-2-3/x/q+x ";
+        let error =
+            InChI::<StandardVersion1_07_4>::parse_with(input, ParseOptions::lenient()).unwrap_err();
+        assert_eq!(error.kind(), &Error::InvalidChargeValue('x'));
+        assert_eq!(error.layer(), LayerId::Charge);
+        assert_eq!(&input[error.span()], "x");
+
+        let error = InChI::<StandardVersion1_07_4>::parse_with("1X/CH4", ParseOptions::lenient())
+            .unwrap_err();
+        assert_eq!(error.kind(), &Error::MissingVersionPrefix);
+        assert_eq!(error.span(), 0..2);
+    }
+}
@@ -1,5 +1,7 @@
+use core::fmt::{self, Display};
+
 use crate::{
-    errors::Error,
+    errors::{Error, LayerError},
     impls::charge_layer::parse_charge,
     inchi::proton_layer::ProtonSublayer,
     traits::{
@@ -13,20 +15,32 @@ impl FromStrWithContext for ProtonSublayer {
     type Input<'a> = &'a str;
     type Idx = u16;
 
-    fn from_str_with_context(
+    fn from_str_located(
         input: Self::Input<'_>,
         _context: Self::Context<'_>,
-    ) -> Result<Self, Error<Self::Idx>> {
+    ) -> Result<Self, LayerError<Self::Idx>> {
         let s = input.strip_prefix(Self::PREFIX).ok_or(Error::WrongPrefix)?;
-        let proton_count = parse_charge(s)?;
+        let proton_count = parse_charge(s).map_err(|error| error.within(input, s))?;
         Ok(ProtonSublayer { proton_count })
     }
 }
 
 impl PrefixFromStrWithContext for ProtonSublayer {}
 
+impl Display for ProtonSublayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::PREFIX)?;
+        if self.proton_count != 0 {
+            write!(f, "{:+}", self.proton_count)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::string::ToString;
+
     use crate::{
         errors::Error, inchi::proton_layer::ProtonSublayer, traits::parse::FromStrWithContext,
     };
@@ -65,6 +79,13 @@ mod tests {
         assert_eq!(result.proton_count, 0);
     }
 
+    #[test]
+    fn test_display_round_trip() {
+        for p_layer in ["p-1", "p+5", "p"] {
+            assert_eq!(parse(p_layer).unwrap().to_string(), p_layer);
+        }
+    }
+
     #[test]
     fn test_wrong_prefix() {
         let err = parse("q-1").unwrap_err();
@@ -0,0 +1,140 @@
+use alloc::boxed::Box;
+use core::fmt::{self, Display};
+
+use crate::{
+    errors::{Error, LayerError, ParseError},
+    impls::{display::write_layers, from_str::parse_layers},
+    inchi::{InChI, ReconnectedLayer},
+    traits::{
+        parse::{FromStrWithContext, PrefixFromStrWithContext},
+        prefix::Prefix,
+    },
+    version::Version,
+};
+
+impl<V: Version> FromStrWithContext for ReconnectedLayer<V> {
+    type Context<'a> = ();
+    type Input<'a> = &'a str;
+    type Idx = u16;
+
+    /// Parses the `/r` layer together with every layer that follows it, as
+    /// they all belong to the nested InChI.
+    fn from_str_located(
+        input: Self::Input<'_>,
+        _context: Self::Context<'_>,
+    ) -> Result<Self, LayerError<Self::Idx>> {
+        Self::parse_located(input).map_err(LayerError::from)
+    }
+}
+
+impl<V: Version> ReconnectedLayer<V> {
+    /// Parses the `/r` layer and the layers that follow it, keeping the
+    /// location of errors raised in the nested layers, relative to `input`.
+    pub(crate) fn parse_located(input: &str) -> Result<Self, ParseError> {
+        let Some(s) = input.strip_prefix(Self::PREFIX) else {
+            return Err(ParseError::at_segment(Error::WrongPrefix, input, input));
+        };
+        let inchi: InChI<V> = parse_layers(s).map_err(|error| error.offset_by(1))?;
+        if inchi.reconnected.is_some() {
+            let start = input.rfind("/r").map_or(0, |position| position + 1);
+            return Err(ParseError::at_segment(
+                Error::NestedReconnectedLayer,
+                input,
+                &input[start..],
+            ));
+        }
+        Ok(ReconnectedLayer { inchi: Box::new(inchi) })
+    }
+}
+
+impl<V: Version> PrefixFromStrWithContext for ReconnectedLayer<V> {
+    fn try_build_layer(
+        input: &mut &str,
+        context: Self::Context<'_>,
+    ) -> Result<Option<Self>, LayerError<Self::Idx>> {
+        if !input.starts_with(Self::PREFIX) {
+            return Ok(None);
+        }
+
+        // The reconnected layer is the last one: it spans the rest of the input.
+        let layer = Self::from_str_located(input, context)?;
+        *input = "";
+        Ok(Some(layer))
+    }
+}
+
+impl<V: Version> Display for ReconnectedLayer<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::PREFIX)?;
+        write_layers(f, &self.inchi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::{
+        errors::{Error, LayerError},
+        inchi::ReconnectedLayer,
+        traits::parse::PrefixFromStrWithContext,
+    };
+
+    fn build(input: &str) -> (Result<Option<ReconnectedLayer>, Error<u16>>, &str) {
+        let mut input = input;
+        let result =
+            ReconnectedLayer::try_build_layer(&mut input, ()).map_err(LayerError::into_kind);
+        (result, input)
+    }
+
+    #[test]
+    fn test_nested_layers() {
+        let (result, remainder) = build("rC2H3NaO2/c1-2(4)5-3/h1H3");
+        let layer = result.unwrap().unwrap();
+        assert!(remainder.is_empty());
+        let components: alloc::vec::Vec<_> = layer.inchi().components().collect();
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].formula().to_string(), "C2H3NaO2");
+        assert!(components[0].graph().is_some());
+        assert_eq!(components[0].fixed_hydrogens(), &[3, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_nested_charge_proton_and_stereo() {
+        let (result, _) = build("rC4H8O2/c1-3(5)4(2)6/h3-6H,1-2H3/q+1/p-1/t3-,4-/m1/s1");
+        let inchi = result.unwrap().unwrap().inchi;
+        assert_eq!(inchi.charges(), Some(&[1][..]));
+        assert_eq!(inchi.proton_count(), Some(-1));
+        assert_eq!(
+            inchi.stereochemistry().unwrap().tetrahedral().unwrap().components()[0].len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_not_present() {
+        let (result, remainder) = build("i1+1");
+        assert!(result.unwrap().is_none());
+        assert_eq!(remainder, "i1+1");
+    }
+
+    #[test]
+    fn test_nested_reconnected_layer() {
+        let (result, _) = build("rC2H3NaO2/c1-2(4)5-3/h1H3/rC2H3NaO2");
+        assert!(matches!(result, Err(Error::NestedReconnectedLayer)));
+    }
+
+    #[test]
+    fn test_invalid_nested_formula() {
+        let (result, _) = build("r/c1-2");
+        assert!(matches!(result, Err(Error::MolecularFormulaParserError(_))));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for input in ["rC2H3NaO2/c1-2(4)5-3/h1H3", "rC4H8O2/c1-3(5)4(2)6/h3-6H,1-2H3/q+1/p-1"] {
+            let (result, _) = build(input);
+            assert_eq!(result.unwrap().unwrap().to_string(), input);
+        }
+    }
+}
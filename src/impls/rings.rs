@@ -0,0 +1,454 @@
+//! Ring bonds and smallest set of smallest rings of a molecular graph.
+
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+
+use geometric_traits::prelude::*;
+use molecular_formulas::MolecularFormula;
+
+use crate::inchi::{
+    Component,
+    main_layer::MolecularGraph,
+    rings::{RingSystem, RingSystemKind, Rings},
+};
+
+/// Returns the neighbours of each atom, with the index of the bond to them.
+fn adjacency(number_of_atoms: usize, bonds: &[(u16, u16)]) -> Vec<Vec<(usize, usize)>> {
+    let mut adjacency = vec![Vec::new(); number_of_atoms];
+    for (bond, &(a, b)) in bonds.iter().enumerate() {
+        adjacency[usize::from(a)].push((usize::from(b), bond));
+        adjacency[usize::from(b)].push((usize::from(a), bond));
+    }
+    adjacency
+}
+
+/// Returns, for each bond, whether it belongs to a ring, that is whether it
+/// is not a bridge of the graph.
+///
+/// Bridges are found with Tarjan's low-link search, run without recursion so
+/// that long chains do not exhaust the stack.
+pub(crate) fn ring_bonds(number_of_atoms: usize, bonds: &[(u16, u16)]) -> Vec<bool> {
+    let adjacency = adjacency(number_of_atoms, bonds);
+    let mut in_ring = vec![true; bonds.len()];
+    let mut discovery = vec![usize::MAX; number_of_atoms];
+    let mut low = vec![0; number_of_atoms];
+    let mut time = 0;
+    for root in 0..number_of_atoms {
+        if discovery[root] != usize::MAX {
+            continue;
+        }
+        discovery[root] = time;
+        low[root] = time;
+        time += 1;
+        // Each entry holds an atom, the bond it was reached by and the
+        // position of the next neighbour to visit.
+        let mut stack: Vec<(usize, Option<usize>, usize)> = vec![(root, None, 0)];
+        while let Some(&mut (atom, parent_bond, ref mut next)) = stack.last_mut() {
+            if let Some(&(neighbour, bond)) = adjacency[atom].get(*next) {
+                *next += 1;
+                if Some(bond) == parent_bond {
+                    continue;
+                }
+                if discovery[neighbour] == usize::MAX {
+                    discovery[neighbour] = time;
+                    low[neighbour] = time;
+                    time += 1;
+                    stack.push((neighbour, Some(bond), 0));
+                } else {
+                    low[atom] = low[atom].min(discovery[neighbour]);
+                }
+                continue;
+            }
+            stack.pop();
+            if let (Some(bond), Some(&(parent, _, _))) = (parent_bond, stack.last()) {
+                low[parent] = low[parent].min(low[atom]);
+                if low[atom] > discovery[parent] {
+                    in_ring[bond] = false;
+                }
+            }
+        }
+    }
+    in_ring
+}
+
+/// Returns the number of connected parts the `atoms` form.
+fn connected_parts(adjacency: &[Vec<(usize, usize)>], atoms: &[usize]) -> usize {
+    let mut parts = 0;
+    let mut seen = vec![false; adjacency.len()];
+    for &start in atoms {
+        if seen[start] {
+            continue;
+        }
+        parts += 1;
+        seen[start] = true;
+        let mut stack = vec![start];
+        while let Some(atom) = stack.pop() {
+            for &(neighbour, _) in &adjacency[atom] {
+                if !seen[neighbour] {
+                    seen[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+    }
+    parts
+}
+
+/// Returns the smallest set of smallest rings of the graph, as a minimum
+/// cycle basis, each ring listing its atoms in order around it, from its
+/// smallest atom towards the smaller of that atom's two ring neighbours.
+/// The rings are sorted by size, then by atoms.
+///
+/// The candidates are Horton's cycles: for each ring atom `v` and ring bond
+/// `x-y`, the shortest paths from `v` to `x` and to `y` closed by the bond,
+/// when they only meet at `v`. Taken from the shortest, the candidates
+/// independent over GF(2) of those already taken form the basis, which has
+/// as many rings as the bonds of the graph minus its atoms plus its
+/// connected parts.
+pub(crate) fn smallest_rings(number_of_atoms: usize, bonds: &[(u16, u16)]) -> Vec<Vec<u16>> {
+    let in_ring = ring_bonds(number_of_atoms, bonds);
+    let ring_bonds: Vec<(u16, u16)> = bonds
+        .iter()
+        .zip(&in_ring)
+        .filter_map(|(&bond, &in_ring)| in_ring.then_some(bond))
+        .collect();
+    if ring_bonds.is_empty() {
+        return Vec::new();
+    }
+    let adjacency = adjacency(number_of_atoms, &ring_bonds);
+    let ring_atoms: Vec<usize> =
+        (0..number_of_atoms).filter(|&atom| !adjacency[atom].is_empty()).collect();
+
+    // The number of independent rings is the cyclomatic number of the
+    // ring bonds.
+    let number_of_rings =
+        ring_bonds.len() + connected_parts(&adjacency, &ring_atoms) - ring_atoms.len();
+
+    let words = ring_bonds.len().div_ceil(64);
+    let mut candidates: BTreeSet<(usize, Vec<u64>)> = BTreeSet::new();
+    let mut distance = vec![usize::MAX; number_of_atoms];
+    let mut parent_bond = vec![usize::MAX; number_of_atoms];
+    let mut parent = vec![usize::MAX; number_of_atoms];
+    let mut branch = vec![usize::MAX; number_of_atoms];
+    for &root in &ring_atoms {
+        distance.fill(usize::MAX);
+        distance[root] = 0;
+        let mut queue = vec![root];
+        let mut head = 0;
+        while let Some(&atom) = queue.get(head) {
+            head += 1;
+            for &(neighbour, bond) in &adjacency[atom] {
+                if distance[neighbour] == usize::MAX {
+                    distance[neighbour] = distance[atom] + 1;
+                    parent[neighbour] = atom;
+                    parent_bond[neighbour] = bond;
+                    branch[neighbour] = if atom == root { neighbour } else { branch[atom] };
+                    queue.push(neighbour);
+                }
+            }
+        }
+        for (bond, &(x, y)) in ring_bonds.iter().enumerate() {
+            let (x, y) = (usize::from(x), usize::from(y));
+            if x == root
+                || y == root
+                || distance[x] == usize::MAX
+                || distance[x].abs_diff(distance[y]) > 1
+                || branch[x] == branch[y]
+            {
+                continue;
+            }
+            let mut cycle = vec![0u64; words];
+            cycle[bond / 64] |= 1 << (bond % 64);
+            for mut atom in [x, y] {
+                while atom != root {
+                    let bond = parent_bond[atom];
+                    cycle[bond / 64] |= 1 << (bond % 64);
+                    atom = parent[atom];
+                }
+            }
+            candidates.insert((distance[x] + distance[y] + 1, cycle));
+        }
+    }
+
+    // Gaussian elimination over GF(2), each basis row keyed by its lowest
+    // bond.
+    let mut basis: Vec<(usize, Vec<u64>)> = Vec::new();
+    let mut rings = Vec::new();
+    for (_, cycle) in candidates {
+        if rings.len() == number_of_rings {
+            break;
+        }
+        let mut reduced = cycle.clone();
+        for (pivot, row) in &basis {
+            if reduced[pivot / 64] & (1 << (pivot % 64)) != 0 {
+                for (word, row_word) in reduced.iter_mut().zip(row) {
+                    *word ^= row_word;
+                }
+            }
+        }
+        let Some(pivot) = reduced
+            .iter()
+            .enumerate()
+            .find(|&(_, &word)| word != 0)
+            .map(|(index, &word)| index * 64 + word.trailing_zeros() as usize)
+        else {
+            continue;
+        };
+        basis.push((pivot, reduced));
+        rings.push(ring_atoms_in_order(&ring_bonds, &cycle));
+    }
+    rings.sort_unstable_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+    rings
+}
+
+/// Returns the atoms of the ring made of the bonds set in `cycle`, in order
+/// around it, from its smallest atom towards the smaller of that atom's two
+/// neighbours.
+fn ring_atoms_in_order(bonds: &[(u16, u16)], cycle: &[u64]) -> Vec<u16> {
+    let members: Vec<(u16, u16)> = bonds
+        .iter()
+        .enumerate()
+        .filter(|&(bond, _)| cycle[bond / 64] & (1 << (bond % 64)) != 0)
+        .map(|(_, &bond)| bond)
+        .collect();
+    let neighbours = |atom: u16| {
+        members.iter().filter_map(move |&(a, b)| {
+            if a == atom {
+                Some(b)
+            } else if b == atom {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    };
+    let Some(start) = members.iter().flat_map(|&(a, b)| [a, b]).min() else {
+        return Vec::new();
+    };
+    let mut ring = vec![start];
+    let mut previous = start;
+    let mut current = neighbours(start).min().unwrap_or(start);
+    while current != start {
+        ring.push(current);
+        let Some(next) = neighbours(current).find(|&next| next != previous) else {
+            break;
+        };
+        previous = current;
+        current = next;
+    }
+    ring
+}
+
+/// Returns how two rings sharing atoms are joined, or `None` when they share
+/// none.
+fn junction(a: &[u16], b: &[u16]) -> Option<RingSystemKind> {
+    let shared: Vec<usize> =
+        a.iter().enumerate().filter(|(_, atom)| b.contains(atom)).map(|(i, _)| i).collect();
+    match shared.as_slice() {
+        [] => None,
+        [_] => Some(RingSystemKind::Spiro),
+        // Two atoms bonded in the ring are consecutive, or its two ends.
+        &[first, second] if second - first == 1 || (first == 0 && second == a.len() - 1) => {
+            Some(RingSystemKind::Fused)
+        }
+        _ => Some(RingSystemKind::Bridged),
+    }
+}
+
+impl Rings {
+    /// Analyses the rings of the graph with `number_of_atoms` atoms and the
+    /// given `bonds`.
+    pub(crate) fn from_bonds(number_of_atoms: usize, bonds: &[(u16, u16)]) -> Self {
+        let rings = smallest_rings(number_of_atoms, bonds);
+        let mut atom_rings = vec![Vec::new(); number_of_atoms];
+        let mut bond_rings: Vec<((u16, u16), Vec<usize>)> = Vec::new();
+        for (index, ring) in rings.iter().enumerate() {
+            for (&a, &b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                atom_rings[usize::from(a)].push(index);
+                let bond = (a.min(b), a.max(b));
+                match bond_rings.binary_search_by_key(&bond, |&(bond, _)| bond) {
+                    Ok(position) => bond_rings[position].1.push(index),
+                    Err(position) => bond_rings.insert(position, (bond, vec![index])),
+                }
+            }
+        }
+
+        // Ring systems, grown from each ring not yet part of one.
+        let mut systems = Vec::new();
+        let mut in_system = vec![false; rings.len()];
+        for start in 0..rings.len() {
+            if in_system[start] {
+                continue;
+            }
+            in_system[start] = true;
+            let mut members = vec![start];
+            let mut kind = RingSystemKind::Isolated;
+            let mut next = 0;
+            while let Some(&ring) = members.get(next) {
+                next += 1;
+                for (other, other_in_system) in in_system.iter_mut().enumerate() {
+                    if other == ring {
+                        continue;
+                    }
+                    let Some(joint) = junction(&rings[ring], &rings[other]) else {
+                        continue;
+                    };
+                    kind = match (kind, joint) {
+                        (RingSystemKind::Bridged, _) | (_, RingSystemKind::Bridged) => {
+                            RingSystemKind::Bridged
+                        }
+                        (RingSystemKind::Fused, _) | (_, RingSystemKind::Fused) => {
+                            RingSystemKind::Fused
+                        }
+                        _ => RingSystemKind::Spiro,
+                    };
+                    if !*other_in_system {
+                        *other_in_system = true;
+                        members.push(other);
+                    }
+                }
+            }
+            members.sort_unstable();
+            let atoms: BTreeSet<u16> =
+                members.iter().flat_map(|&ring| rings[ring].iter().copied()).collect();
+            systems.push(RingSystem { rings: members, atoms: atoms.into_iter().collect(), kind });
+        }
+
+        Rings { cycles: rings, atom_memberships: atom_rings, bond_memberships: bond_rings, systems }
+    }
+}
+
+impl From<&MolecularGraph<u16>> for Rings {
+    fn from(graph: &MolecularGraph<u16>) -> Self {
+        let bonds: Vec<(u16, u16)> = (0..graph.number_of_nodes())
+            .flat_map(|atom| {
+                graph
+                    .neighbors(atom)
+                    .filter(move |&other| atom < other)
+                    .map(move |other| (atom, other))
+            })
+            .collect();
+        Rings::from_bonds(usize::from(graph.number_of_nodes()), &bonds)
+    }
+}
+
+impl Component<'_> {
+    /// Returns the rings of the component, from its connection graph.
+    ///
+    /// Components without a `/c` layer have no rings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::inchi::{InChI, rings::RingSystemKind};
+    ///
+    /// // Spiro[4.5]decane: C1 is in both rings.
+    /// let inchi: InChI = "InChI=1S/C10H18/c1-2-6-10(7-3-1)8-4-5-9-10/h1-9H2".parse().unwrap();
+    /// let rings = inchi.components().next().unwrap().rings();
+    /// assert_eq!(rings.number_of_rings(), 2);
+    /// assert_eq!(rings.atom_rings(9), [0, 1]);
+    /// assert_eq!(rings.atom_ring_size(0), Some(6));
+    /// assert_eq!(rings.systems()[0].kind(), RingSystemKind::Spiro);
+    /// ```
+    #[must_use]
+    pub fn rings(&self) -> Rings {
+        self.graph().map_or_else(
+            || Rings::from_bonds(self.formula.number_of_non_hydrogens(), &[]),
+            Rings::from,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use super::{ring_bonds, smallest_rings};
+    use crate::inchi::{
+        InChI,
+        rings::{RingSystem, RingSystemKind, Rings},
+    };
+
+    fn rings(inchi: &str) -> Rings {
+        let inchi: InChI = inchi.parse().unwrap();
+        inchi.components().next().unwrap().rings()
+    }
+
+    fn kinds(rings: &Rings) -> Vec<RingSystemKind> {
+        rings.systems().iter().map(RingSystem::kind).collect()
+    }
+
+    #[test]
+    fn test_ring_bonds() {
+        // A triangle with a tail: 0-1-2-0, 2-3.
+        let bonds = [(0, 1), (0, 2), (1, 2), (2, 3)];
+        assert_eq!(ring_bonds(4, &bonds), [true, true, true, false]);
+        assert_eq!(ring_bonds(2, &[(0, 1)]), [false]);
+    }
+
+    #[test]
+    fn test_smallest_rings() {
+        // Naphthalene, as two hexagons sharing the bond 4-9.
+        let bonds = [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (4, 9),
+            (0, 9),
+            (4, 5),
+            (5, 6),
+            (6, 7),
+            (7, 8),
+            (8, 9),
+        ];
+        assert_eq!(smallest_rings(10, &bonds), [vec![0, 1, 2, 3, 4, 9], vec![4, 5, 6, 7, 8, 9]]);
+        // Cubane has five independent rings of four atoms.
+        let cube = [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (0, 3),
+            (4, 5),
+            (5, 6),
+            (6, 7),
+            (4, 7),
+            (0, 4),
+            (1, 5),
+            (2, 6),
+            (3, 7),
+        ];
+        let rings = smallest_rings(8, &cube);
+        assert_eq!(rings.len(), 5);
+        assert!(rings.iter().all(|ring| ring.len() == 4));
+        let chain: Vec<Vec<u16>> = smallest_rings(3, &[(0, 1), (1, 2)]);
+        assert!(chain.is_empty());
+    }
+
+    #[test]
+    fn test_ring_systems() {
+        let biphenyl = rings("InChI=1S/C12H10/c1-3-7-11(8-4-1)12-9-5-2-6-10-12/h1-10H");
+        assert_eq!(kinds(&biphenyl), [RingSystemKind::Isolated, RingSystemKind::Isolated]);
+        assert!(!biphenyl.is_ring_bond(10, 11));
+        assert!(biphenyl.is_ring_bond(0, 2));
+        assert_eq!(biphenyl.bond_ring_size(0, 2), Some(6));
+
+        let naphthalene = rings("InChI=1S/C10H8/c1-2-6-10-8-4-3-7-9(10)5-1/h1-8H");
+        assert_eq!(kinds(&naphthalene), [RingSystemKind::Fused]);
+        assert_eq!(naphthalene.systems()[0].atoms().len(), 10);
+        assert_eq!(naphthalene.bond_rings(8, 9), [0, 1]);
+
+        let norbornane = rings("InChI=1S/C7H12/c1-2-7-4-3-6(1)5-7/h6-7H,1-5H2");
+        assert_eq!(norbornane.number_of_rings(), 2);
+        assert_eq!(kinds(&norbornane), [RingSystemKind::Bridged]);
+
+        let adamantane = rings("InChI=1S/C10H16/c1-7-2-9-4-8(1)5-10(3-7)6-9/h7-10H,1-6H2");
+        assert_eq!(adamantane.number_of_rings(), 3);
+        assert_eq!(kinds(&adamantane), [RingSystemKind::Bridged]);
+
+        let hexane = rings("InChI=1S/C6H14/c1-3-5-6-4-2/h3-6H2,1-2H3");
+        assert_eq!(hexane.number_of_rings(), 0);
+        assert!(hexane.systems().is_empty());
+        assert!(!hexane.is_ring_atom(0));
+        assert_eq!(hexane.atom_ring_size(0), None);
+    }
+}
@@ -1,15 +1,21 @@
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::{self, Display, Write},
+    iter::Peekable,
+    str::Chars,
+};
 
 use molecular_formulas::{BaselineDigit, InChIFormula, MolecularFormula, try_fold_number};
 
 use crate::{
-    errors::Error,
+    errors::{Error, LayerError, LayerId},
+    impls::display::write_components,
     inchi::stereochemistry_layer::{
         AlleneSublayer, DoubleBondStereo, DoubleBondSublayer, StereoChemistryInformationSublayer,
-        StereoParity, TetrahedralStereo, TetrahedralSublayer,
+        StereoParity, StereochemistryLayer, TetrahedralStereo, TetrahedralSublayer,
     },
     traits::{
-        parse::{FromStrWithContext, PrefixFromStrWithContext},
+        parse::{FromStrWithContext, PrefixFromStrWithContext, consumed},
         prefix::Prefix,
     },
 };
@@ -24,68 +30,59 @@ fn parse_parity(c: char) -> Result<StereoParity, Error<u16>> {
     }
 }
 
-/// Parses a single double bond spec like `12-11+`.
-fn parse_double_bond_spec(spec: &str) -> Result<DoubleBondStereo, Error<u16>> {
-    let mut chars = spec.chars().peekable();
-
-    // Parse first atom index (1-based)
-    let atom1_1based = match try_fold_number::<u16, BaselineDigit, _>(&mut chars) {
-        Some(Ok(n)) if n > 0 => n,
+/// Parses a 1-based atom index of `spec` at the position of `chars`.
+fn parse_atom_index(spec: &str, chars: &mut Peekable<Chars<'_>>) -> Result<u16, LayerError<u16>> {
+    let start = consumed(spec, chars);
+    match try_fold_number::<u16, BaselineDigit, _>(chars) {
+        Some(Ok(n)) if n > 0 => Ok(n),
         _ => {
-            return Err(Error::InvalidStereoValue(spec.chars().next().unwrap_or('?')));
+            let c = spec[start..].chars().next().unwrap_or('?');
+            Err(LayerError::at_char(Error::InvalidStereoValue(c), spec, start))
         }
+    }
+}
+
+/// Parses the parity closing `spec` at the position of `chars`.
+fn parse_spec_parity(
+    spec: &str,
+    chars: &mut Peekable<Chars<'_>>,
+) -> Result<StereoParity, LayerError<u16>> {
+    let position = consumed(spec, chars);
+    let parity = match chars.next() {
+        Some(c) => parse_parity(c).map_err(|error| LayerError::at_char(error, spec, position))?,
+        None => return Err(LayerError::at_char(Error::InvalidStereoValue('?'), spec, position)),
     };
+    if let Some(&c) = chars.peek() {
+        return Err(LayerError::at_char(Error::InvalidStereoValue(c), spec, consumed(spec, chars)));
+    }
+    Ok(parity)
+}
+
+/// Parses a single double bond spec like `12-11+`.
+fn parse_double_bond_spec(spec: &str) -> Result<DoubleBondStereo, LayerError<u16>> {
+    let mut chars = spec.chars().peekable();
+
+    let atom1_1based = parse_atom_index(spec, &mut chars)?;
 
     // Consume '-' separator
+    let position = consumed(spec, &chars);
     match chars.next() {
         Some('-') => {}
-        Some(c) => return Err(Error::InvalidStereoValue(c)),
-        None => return Err(Error::InvalidStereoValue('-')),
+        Some(c) => return Err(LayerError::at_char(Error::InvalidStereoValue(c), spec, position)),
+        None => return Err(LayerError::at_char(Error::InvalidStereoValue('-'), spec, position)),
     }
 
-    // Parse second atom index (1-based)
-    let atom2_1based = match try_fold_number::<u16, BaselineDigit, _>(&mut chars) {
-        Some(Ok(n)) if n > 0 => n,
-        _ => {
-            return Err(Error::InvalidStereoValue(chars.next().unwrap_or('?')));
-        }
-    };
-
-    // Parse parity
-    let parity = match chars.next() {
-        Some(c) => parse_parity(c)?,
-        None => return Err(Error::InvalidStereoValue('?')),
-    };
-
-    if chars.next().is_some() {
-        return Err(Error::InvalidStereoValue('?'));
-    }
+    let atom2_1based = parse_atom_index(spec, &mut chars)?;
+    let parity = parse_spec_parity(spec, &mut chars)?;
 
     Ok(DoubleBondStereo { atom1: atom1_1based - 1, atom2: atom2_1based - 1, parity })
 }
 
 /// Parses a single tetrahedral spec like `13-`.
-fn parse_tetrahedral_spec(spec: &str) -> Result<TetrahedralStereo, Error<u16>> {
+fn parse_tetrahedral_spec(spec: &str) -> Result<TetrahedralStereo, LayerError<u16>> {
     let mut chars = spec.chars().peekable();
-
-    // Parse atom index (1-based)
-    let atom_1based = match try_fold_number::<u16, BaselineDigit, _>(&mut chars) {
-        Some(Ok(n)) if n > 0 => n,
-        _ => {
-            return Err(Error::InvalidStereoValue(spec.chars().next().unwrap_or('?')));
-        }
-    };
-
-    // Parse parity
-    let parity = match chars.next() {
-        Some(c) => parse_parity(c)?,
-        None => return Err(Error::InvalidStereoValue('?')),
-    };
-
-    if chars.next().is_some() {
-        return Err(Error::InvalidStereoValue('?'));
-    }
-
+    let atom_1based = parse_atom_index(spec, &mut chars)?;
+    let parity = parse_spec_parity(spec, &mut chars)?;
     Ok(TetrahedralStereo { atom: atom_1based - 1, parity })
 }
 
@@ -96,10 +93,10 @@ impl FromStrWithContext for DoubleBondSublayer {
     type Input<'a> = &'a str;
     type Idx = u16;
 
-    fn from_str_with_context(
+    fn from_str_located(
         input: Self::Input<'_>,
         context: Self::Context<'_>,
-    ) -> Result<Self, Error<Self::Idx>> {
+    ) -> Result<Self, LayerError<Self::Idx>> {
         let s = input.strip_prefix(Self::PREFIX).ok_or(Error::WrongPrefix)?;
 
         let mut subformulas = context.subformulas();
@@ -117,18 +114,27 @@ impl FromStrWithContext for DoubleBondSublayer {
                     (1u32, component_str)
                 };
 
-            subformulas.next().ok_or(Error::FormulaAndConnectionLayerMixtureMismatch(
-                context.number_of_mixtures(),
-            ))?;
-
-            let bonds = if component_str.is_empty() {
-                Vec::new()
-            } else {
-                component_str
-                    .split(',')
-                    .map(parse_double_bond_spec)
-                    .collect::<Result<Vec<_>, _>>()?
-            };
+            let subformula = subformulas.next().ok_or(
+                Error::FormulaAndConnectionLayerMixtureMismatch(context.number_of_mixtures()),
+            )?;
+
+            let num_atoms = subformula.number_of_non_hydrogens();
+            let mut bonds = Vec::new();
+            for spec in component_str.split(',').filter(|_| !component_str.is_empty()) {
+                let bond =
+                    parse_double_bond_spec(spec).map_err(|error| error.within(input, spec))?;
+                let index = bond.atom1.max(bond.atom2);
+                if usize::from(index) >= num_atoms {
+                    let error = Error::AtomIndexOutOfBounds {
+                        layer: LayerId::DoubleBondStereo,
+                        component: components.len(),
+                        index: index + 1,
+                        num_atoms,
+                    };
+                    return Err(LayerError::from(error).within(input, spec));
+                }
+                bonds.push(bond);
+            }
 
             components.push(bonds.clone());
             for _ in 1..reps {
@@ -142,7 +148,8 @@ impl FromStrWithContext for DoubleBondSublayer {
         if subformulas.next().is_some() {
             return Err(Error::FormulaAndConnectionLayerMixtureMismatch(
                 context.number_of_mixtures(),
-            ));
+            )
+            .into());
         }
 
         Ok(DoubleBondSublayer { components })
@@ -158,10 +165,10 @@ impl FromStrWithContext for TetrahedralSublayer {
     type Input<'a> = &'a str;
     type Idx = u16;
 
-    fn from_str_with_context(
+    fn from_str_located(
         input: Self::Input<'_>,
         context: Self::Context<'_>,
-    ) -> Result<Self, Error<Self::Idx>> {
+    ) -> Result<Self, LayerError<Self::Idx>> {
         let s = input.strip_prefix(Self::PREFIX).ok_or(Error::WrongPrefix)?;
 
         let mut subformulas = context.subformulas();
@@ -179,18 +186,26 @@ impl FromStrWithContext for TetrahedralSublayer {
                     (1u32, component_str)
                 };
 
-            subformulas.next().ok_or(Error::FormulaAndConnectionLayerMixtureMismatch(
-                context.number_of_mixtures(),
-            ))?;
-
-            let centers = if component_str.is_empty() {
-                Vec::new()
-            } else {
-                component_str
-                    .split(',')
-                    .map(parse_tetrahedral_spec)
-                    .collect::<Result<Vec<_>, _>>()?
-            };
+            let subformula = subformulas.next().ok_or(
+                Error::FormulaAndConnectionLayerMixtureMismatch(context.number_of_mixtures()),
+            )?;
+
+            let num_atoms = subformula.number_of_non_hydrogens();
+            let mut centers = Vec::new();
+            for spec in component_str.split(',').filter(|_| !component_str.is_empty()) {
+                let center =
+                    parse_tetrahedral_spec(spec).map_err(|error| error.within(input, spec))?;
+                if usize::from(center.atom) >= num_atoms {
+                    let error = Error::AtomIndexOutOfBounds {
+                        layer: LayerId::TetrahedralStereo,
+                        component: components.len(),
+                        index: center.atom + 1,
+                        num_atoms,
+                    };
+                    return Err(LayerError::from(error).within(input, spec));
+                }
+                centers.push(center);
+            }
 
             components.push(centers.clone());
             for _ in 1..reps {
@@ -204,7 +219,8 @@ impl FromStrWithContext for TetrahedralSublayer {
         if subformulas.next().is_some() {
             return Err(Error::FormulaAndConnectionLayerMixtureMismatch(
                 context.number_of_mixtures(),
-            ));
+            )
+            .into());
         }
 
         Ok(TetrahedralSublayer { components })
@@ -220,10 +236,10 @@ impl FromStrWithContext for AlleneSublayer {
     type Input<'a> = &'a str;
     type Idx = u16;
 
-    fn from_str_with_context(
+    fn from_str_located(
         input: Self::Input<'_>,
         _context: Self::Context<'_>,
-    ) -> Result<Self, Error<Self::Idx>> {
+    ) -> Result<Self, LayerError<Self::Idx>> {
         let s = input.strip_prefix(Self::PREFIX).ok_or(Error::WrongPrefix)?;
 
         let mut values = Vec::new();
@@ -231,11 +247,18 @@ impl FromStrWithContext for AlleneSublayer {
             if segment.is_empty() {
                 values.push(None);
             } else {
-                for c in segment.chars() {
+                for (position, c) in segment.char_indices() {
                     match c {
                         '0' => values.push(Some(0)),
                         '1' => values.push(Some(1)),
-                        _ => return Err(Error::InvalidStereoValue(c)),
+                        _ => {
+                            return Err(LayerError::at_char(
+                                Error::InvalidStereoValue(c),
+                                segment,
+                                position,
+                            )
+                            .within(input, segment));
+                        }
                     }
                 }
             }
@@ -254,34 +277,159 @@ impl FromStrWithContext for StereoChemistryInformationSublayer {
     type Input<'a> = &'a str;
     type Idx = u16;
 
-    fn from_str_with_context(
+    fn from_str_located(
         input: Self::Input<'_>,
         _context: Self::Context<'_>,
-    ) -> Result<Self, Error<Self::Idx>> {
+    ) -> Result<Self, LayerError<Self::Idx>> {
         let s = input.strip_prefix(Self::PREFIX).ok_or(Error::WrongPrefix)?;
 
         let mut chars = s.chars();
-        let c = chars.next().ok_or(Error::InvalidStereoValue('s'))?;
+        let c = chars
+            .next()
+            .ok_or_else(|| LayerError::at_char(Error::InvalidStereoValue('s'), input, 0))?;
         if chars.next().is_some() {
-            return Err(Error::InvalidStereoValue(c));
+            return Err(LayerError::at(Error::InvalidStereoValue(c), 1..input.len()));
         }
         match c {
             '1'..='3' => Ok(StereoChemistryInformationSublayer { value: c as u8 - b'0' }),
-            _ => Err(Error::InvalidStereoValue(c)),
+            _ => Err(LayerError::at_char(Error::InvalidStereoValue(c), input, 1)),
         }
     }
 }
 
 impl PrefixFromStrWithContext for StereoChemistryInformationSublayer {}
 
+// --- StereochemistryLayer ---
+
+impl StereochemistryLayer {
+    /// Parses the `/b`, `/t`, `/m` and `/s` sublayers at the start of `input`,
+    /// consuming them.
+    ///
+    /// Returns `None` when none of the sublayers is present.
+    pub(crate) fn try_build_layer(
+        input: &mut &str,
+        formula: &InChIFormula,
+    ) -> Result<Option<Self>, LayerError<u16>> {
+        let double_bond = DoubleBondSublayer::try_build_layer(input, formula)?;
+        let tetrahedral = TetrahedralSublayer::try_build_layer(input, formula)?;
+        let allene = AlleneSublayer::try_build_layer(input, ())?;
+        let stereo_info = StereoChemistryInformationSublayer::try_build_layer(input, ())?;
+
+        if double_bond.is_none()
+            && tetrahedral.is_none()
+            && allene.is_none()
+            && stereo_info.is_none()
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(StereochemistryLayer { double_bond, tetrahedral, allene, stereo_info }))
+    }
+}
+
+// --- Display ---
+
+/// Joins the stereo specs of one component with `,`.
+fn join_specs<T: Display>(specs: &[T]) -> String {
+    let mut joined = String::new();
+    for (position, spec) in specs.iter().enumerate() {
+        if position > 0 {
+            joined.push(',');
+        }
+        let _ = write!(joined, "{spec}");
+    }
+    joined
+}
+
+impl Display for StereoParity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char(match self {
+            StereoParity::Plus => '+',
+            StereoParity::Minus => '-',
+            StereoParity::Unknown => '?',
+        })
+    }
+}
+
+impl Display for DoubleBondStereo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}{}", self.atom1 + 1, self.atom2 + 1, self.parity)
+    }
+}
+
+impl Display for TetrahedralStereo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.atom + 1, self.parity)
+    }
+}
+
+impl Display for DoubleBondSublayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::PREFIX)?;
+        write_components(f, self.components.iter().map(|bonds| join_specs(bonds)))
+    }
+}
+
+impl Display for TetrahedralSublayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::PREFIX)?;
+        write_components(f, self.components.iter().map(|centers| join_specs(centers)))
+    }
+}
+
+impl Display for AlleneSublayer {
+    /// Writes the values back in the `.`-separated group form accepted by the
+    /// parser: consecutive values share a group and every `None` is an empty
+    /// group.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Self::PREFIX)?;
+        let mut previous: Option<Option<u8>> = None;
+        for &value in &self.values {
+            if previous.is_some() && (value.is_none() || previous == Some(None)) {
+                f.write_char('.')?;
+            }
+            if let Some(value) = value {
+                write!(f, "{value}")?;
+            }
+            previous = Some(value);
+        }
+        Ok(())
+    }
+}
+
+impl Display for StereoChemistryInformationSublayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", Self::PREFIX, self.value)
+    }
+}
+
+impl Display for StereochemistryLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sublayers: [Option<&dyn Display>; 4] = [
+            self.double_bond.as_ref().map(|sublayer| sublayer as &dyn Display),
+            self.tetrahedral.as_ref().map(|sublayer| sublayer as &dyn Display),
+            self.allene.as_ref().map(|sublayer| sublayer as &dyn Display),
+            self.stereo_info.as_ref().map(|sublayer| sublayer as &dyn Display),
+        ];
+        for (position, sublayer) in sublayers.into_iter().flatten().enumerate() {
+            if position > 0 {
+                f.write_char('/')?;
+            }
+            write!(f, "{sublayer}")?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use alloc::string::ToString;
     use core::str::FromStr;
 
     use molecular_formulas::InChIFormula;
 
     use crate::{
-        errors::Error,
+        errors::{Error, LayerId},
         inchi::stereochemistry_layer::{
             AlleneSublayer, DoubleBondSublayer, StereoChemistryInformationSublayer, StereoParity,
             TetrahedralSublayer,
@@ -365,6 +513,21 @@ mod tests {
         assert!(matches!(err, Error::WrongPrefix));
     }
 
+    #[test]
+    fn test_b_atom_index_out_of_bounds() {
+        let f = formula("C3H6.C2H4");
+        let err = DoubleBondSublayer::from_str_with_context("b3-2+;3-2-", &f).unwrap_err();
+        assert_eq!(
+            err,
+            Error::AtomIndexOutOfBounds {
+                layer: LayerId::DoubleBondStereo,
+                component: 1,
+                index: 3,
+                num_atoms: 2,
+            }
+        );
+    }
+
     // --- /t tests ---
 
     #[test]
@@ -442,6 +605,28 @@ mod tests {
         assert!(matches!(err, Error::WrongPrefix));
     }
 
+    #[test]
+    fn test_t_atom_index_out_of_bounds() {
+        let f = formula("CH4");
+        let err = TetrahedralSublayer::from_str_with_context("t9-", &f).unwrap_err();
+        assert_eq!(
+            err,
+            Error::AtomIndexOutOfBounds {
+                layer: LayerId::TetrahedralStereo,
+                component: 0,
+                index: 9,
+                num_atoms: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_t_atom_index_at_bound() {
+        let f = formula("C5H10");
+        assert!(TetrahedralSublayer::from_str_with_context("t5+", &f).is_ok());
+        assert!(TetrahedralSublayer::from_str_with_context("t6+", &f).is_err());
+    }
+
     // --- /m tests ---
 
     #[test]
@@ -673,6 +858,39 @@ mod tests {
         assert!(matches!(err, Error::InvalidStereoValue('a')));
     }
 
+    // --- Display tests ---
+
+    #[test]
+    fn test_b_display_round_trip() {
+        let f = formula("C17H30");
+        let result = DoubleBondSublayer::from_str_with_context("b12-11+,17-13?", &f).unwrap();
+        assert_eq!(result.to_string(), "b12-11+,17-13?");
+        let f = formula("C3H6.C2H4.O");
+        let result = DoubleBondSublayer::from_str_with_context("b3-2+;1-2-;", &f).unwrap();
+        assert_eq!(result.to_string(), "b3-2+;1-2-;");
+    }
+
+    #[test]
+    fn test_t_display_round_trip() {
+        let f = formula("2C20H24N2O2.H2O4S");
+        let result = TetrahedralSublayer::from_str_with_context("t2*13-,14?,19+,20-;", &f).unwrap();
+        assert_eq!(result.to_string(), "t2*13-,14?,19+,20-;");
+    }
+
+    #[test]
+    fn test_m_display_round_trip() {
+        for m_layer in ["m0", "m0.", "m.11", "m00.", "m01", "m0..1", "m..", "m"] {
+            let result = AlleneSublayer::from_str_with_context(m_layer, ()).unwrap();
+            assert_eq!(result.to_string(), m_layer);
+        }
+    }
+
+    #[test]
+    fn test_s_display_round_trip() {
+        let result = StereoChemistryInformationSublayer::from_str_with_context("s3", ()).unwrap();
+        assert_eq!(result.to_string(), "s3");
+    }
+
     // --- try_build_layer tests ---
 
     use crate::traits::parse::PrefixFromStrWithContext;
@@ -0,0 +1,67 @@
+//! Valences of neutral and charged atoms.
+
+use elements_rs::Element;
+
+/// Returns the valences InChI accepts for an atom of `element` carrying
+/// `charge`, from the lowest to the highest, or `None` for the metals and the
+/// other elements whose bonding is not constrained. The slice is never empty.
+///
+/// The valences of neutral atoms are those of [`Element::valences`]. A
+/// charged atom takes the valences of the element with as many electrons, so
+/// that N⁺ behaves like C, O⁻ like F and F⁻ like Ne, and a bare H⁺ takes no
+/// bonds. Charged atoms of the second period keep only the lowest of these
+/// valences, as they cannot expand their octet. A charged atom whose element
+/// with as many electrons has no valences, as C²⁺ like Be, is not
+/// constrained either.
+pub(crate) fn valences(element: Element, charge: i16) -> Option<&'static [u8]> {
+    let neutral = element.valences();
+    if neutral.is_empty() {
+        return None;
+    }
+    if charge == 0 {
+        return Some(neutral);
+    }
+    let electrons = i16::from(element.atomic_number()) - charge;
+    if electrons == 0 {
+        return Some(&[0]);
+    }
+    let shifted = u8::try_from(electrons)
+        .ok()
+        .and_then(|electrons| Element::try_from(electrons).ok())
+        .map(Element::valences)
+        .filter(|valences| !valences.is_empty())?;
+    Some(if element.atomic_number() <= 10 { &shifted[..shifted.len().min(1)] } else { shifted })
+}
+
+#[cfg(test)]
+mod tests {
+    use elements_rs::Element;
+
+    use super::valences;
+
+    #[test]
+    fn test_neutral_valences() {
+        assert_eq!(valences(Element::C, 0), Some(&[4][..]));
+        assert_eq!(valences(Element::N, 0), Some(&[3, 5][..]));
+        assert_eq!(valences(Element::O, 0), Some(&[2][..]));
+        assert_eq!(valences(Element::S, 0), Some(&[2, 4, 6][..]));
+        assert_eq!(valences(Element::Cl, 0), Some(&[1, 3, 5, 7][..]));
+        assert_eq!(valences(Element::H, 0), Some(&[1][..]));
+        assert_eq!(valences(Element::Fe, 0), None);
+    }
+
+    #[test]
+    fn test_charged_valences() {
+        assert_eq!(valences(Element::N, 1), Some(&[4][..]));
+        assert_eq!(valences(Element::O, 1), Some(&[3][..]));
+        assert_eq!(valences(Element::O, -1), Some(&[1][..]));
+        assert_eq!(valences(Element::C, -1), Some(&[3][..]));
+        assert_eq!(valences(Element::B, -1), Some(&[4][..]));
+        assert_eq!(valences(Element::S, 1), Some(&[3, 5][..]));
+        assert_eq!(valences(Element::F, -1), Some(&[0][..]));
+        assert_eq!(valences(Element::O, -2), Some(&[0][..]));
+        assert_eq!(valences(Element::H, 1), Some(&[0][..]));
+        assert_eq!(valences(Element::C, 2), None);
+        assert_eq!(valences(Element::Fe, 2), None);
+    }
+}
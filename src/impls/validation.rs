@@ -0,0 +1,7 @@
+//! Implementations of the optional validation passes.
+
+mod charge;
+mod conformance;
+mod hydrogens;
+mod stereo;
+mod valence;
@@ -0,0 +1,100 @@
+//! Checks the charge and proton layers against the structure.
+
+use alloc::vec::Vec;
+
+use molecular_formulas::MolecularFormula;
+
+use crate::{inchi::InChI, validation::ChargeIssue, version::Version};
+
+impl<V: Version> InChI<V> {
+    /// Checks that the `/q` and `/p` layers are consistent with the
+    /// structure, and returns the inconsistencies found.
+    ///
+    /// The mobile negative charges of a component (`(H-,…)`) are part of its
+    /// charge, so the `/q` layer must give the component at least as many
+    /// negative charges. The `/p` layer cannot remove more protons than the
+    /// formula has hydrogens. The number of `/q` entries is checked against
+    /// the components when parsing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::{inchi::InChI, validation::ChargeIssue};
+    ///
+    /// let inchi: InChI = "InChI=1S/CH4/h1H4/p-5".parse().unwrap();
+    /// assert_eq!(
+    ///     inchi.check_charges(),
+    ///     [ChargeIssue::ProtonsExceedHydrogens { protons: -5, hydrogens: 4 }]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn check_charges(&self) -> Vec<ChargeIssue> {
+        let mut issues: Vec<ChargeIssue> = self
+            .components()
+            .filter_map(|component| {
+                let negative_count: usize = component
+                    .mobile_hydrogens()
+                    .iter()
+                    .map(|group| usize::from(group.negative_count()))
+                    .sum();
+                let charge = component.charge();
+                let negative_charge = usize::from(charge.min(0).unsigned_abs());
+                (negative_count > negative_charge).then_some(
+                    ChargeIssue::MobileChargeExceedsCharge {
+                        component: component.index(),
+                        negative_count,
+                        charge,
+                    },
+                )
+            })
+            .collect();
+
+        if let Some(protons) = self.proton_count().filter(|&protons| protons < 0) {
+            let hydrogens = self.main_layer.as_ref().map_or(0, |main_layer| {
+                let formula = main_layer.chemical_formula();
+                formula.number_of_elements() - formula.number_of_non_hydrogens()
+            });
+            if usize::from(protons.unsigned_abs()) > hydrogens {
+                issues.push(ChargeIssue::ProtonsExceedHydrogens { protons, hydrogens });
+            }
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{inchi::InChI, validation::ChargeIssue};
+
+    #[test]
+    fn test_mobile_charge_in_charge_layer() {
+        let inchi: InChI = "InChI=1S/C2H6/c1-2/h1-2H2,(H-,1,2)/q-1".parse().unwrap();
+        assert!(inchi.check_charges().is_empty());
+    }
+
+    #[test]
+    fn test_mobile_charge_missing_from_charge_layer() {
+        let inchi: InChI = "InChI=1S/C2H6/c1-2/h1-2H2,(H-,1,2)".parse().unwrap();
+        assert_eq!(
+            inchi.check_charges(),
+            [ChargeIssue::MobileChargeExceedsCharge { component: 0, negative_count: 1, charge: 0 }]
+        );
+        let inchi: InChI = "InChI=1S/C2H6.Na/c1-2;/h1-2H2,(H2-2,1,2);/q-1;+1".parse().unwrap();
+        assert_eq!(
+            inchi.check_charges(),
+            [ChargeIssue::MobileChargeExceedsCharge {
+                component: 0,
+                negative_count: 2,
+                charge: -1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_deprotonation_within_hydrogens() {
+        let inchi: InChI = "InChI=1S/C2H4O2/c1-2(3)4/h1H3,(H,3,4)/p-1".parse().unwrap();
+        assert!(inchi.check_charges().is_empty());
+        let inchi: InChI = "InChI=1S/p+1".parse().unwrap();
+        assert!(inchi.check_charges().is_empty());
+    }
+}
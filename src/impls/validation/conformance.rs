@@ -0,0 +1,145 @@
+//! Checks an InChI against what standard InChI allows.
+
+use alloc::vec::Vec;
+
+use crate::{
+    inchi::{InChI, StereochemistryLayer},
+    validation::ConformanceIssue,
+    version::Version,
+};
+
+/// Returns the issues of the `/m` and `/s` flags of a stereo layer.
+fn check_stereo_flags(stereo: &StereochemistryLayer, isotopic: bool) -> Vec<ConformanceIssue> {
+    let mut issues = Vec::new();
+    if let Some(info) = stereo.stereo_info()
+        && info.value() != 1
+    {
+        let value = info.value();
+        issues.push(ConformanceIssue::NonAbsoluteStereo { isotopic, value });
+    }
+    let inversion = stereo.allene().is_some();
+    if inversion != stereo.stereo_info().is_some() {
+        issues.push(ConformanceIssue::UnpairedStereoFlag { isotopic, inversion });
+    } else if inversion && stereo.tetrahedral().is_none() {
+        issues.push(ConformanceIssue::StereoFlagsWithoutTetrahedral { isotopic });
+    }
+    issues
+}
+
+impl<V: Version> InChI<V> {
+    /// Checks the InChI against the rules of standard InChI, and returns the
+    /// features that standard InChI never produces.
+    ///
+    /// Standard InChI has no fixed hydrogen (`/f`) or reconnected (`/r`)
+    /// layer, only describes absolute stereo (`/s1`), and writes the `/m` and
+    /// `/s` flags together and only after a tetrahedral `/t` sublayer. The
+    /// isotopic stereo sublayers follow the same rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::{inchi::InChI, validation::ConformanceIssue};
+    ///
+    /// let inchi: InChI =
+    ///     "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m0/s1".parse().unwrap();
+    /// assert!(inchi.check_standard_conformance().is_empty());
+    ///
+    /// let inchi: InChI =
+    ///     "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m0/s2".parse().unwrap();
+    /// let issues = inchi.check_standard_conformance();
+    /// assert_eq!(issues, [ConformanceIssue::NonAbsoluteStereo { isotopic: false, value: 2 }]);
+    /// assert_eq!(issues[0].rule(), "absolute_stereo_only");
+    /// ```
+    #[must_use]
+    pub fn check_standard_conformance(&self) -> Vec<ConformanceIssue> {
+        let mut issues = Vec::new();
+        if self.fixed_hydrogen.is_some() {
+            issues.push(ConformanceIssue::FixedHydrogenLayer);
+        }
+        if self.reconnected.is_some() {
+            issues.push(ConformanceIssue::ReconnectedLayer);
+        }
+        if let Some(stereo) = &self.stereochemistry {
+            issues.extend(check_stereo_flags(stereo, false));
+        }
+        if let Some(stereo) = self.isotope.as_ref().and_then(|isotope| isotope.stereochemistry()) {
+            issues.extend(check_stereo_flags(stereo, true));
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{inchi::InChI, validation::ConformanceIssue, version::Version1_07_4};
+
+    #[test]
+    fn test_non_standard_layers() {
+        let inchi: InChI<Version1_07_4> =
+            "InChI=1/CH2O2/c2-1-3/h1H,(H,2,3)/f/h2H/b".parse().unwrap();
+        let issues = inchi.check_standard_conformance();
+        assert_eq!(issues, [ConformanceIssue::FixedHydrogenLayer]);
+        assert_eq!(issues[0].rule(), "no_fixed_hydrogen_layer");
+    }
+
+    #[test]
+    fn test_fixed_hydrogen_layer_in_standard_inchi() {
+        let inchi: InChI = "InChI=1S/CH2O2/c2-1-3/h1H,(H,2,3)/f/h2H/b".parse().unwrap();
+        assert_eq!(inchi.check_standard_conformance(), [ConformanceIssue::FixedHydrogenLayer]);
+    }
+
+    #[test]
+    fn test_reconnected_layer_in_standard_inchi() {
+        let inchi: InChI =
+            "InChI=1S/C2H4O2.Na/c1-2(3)4;/h1H3,(H,3,4);/q;+1/p-1/rC2H3NaO2/c1-2(4)5-3/h1H3"
+                .parse()
+                .unwrap();
+        let issues = inchi.check_standard_conformance();
+        assert_eq!(issues, [ConformanceIssue::ReconnectedLayer]);
+        assert_eq!(issues[0].rule(), "no_reconnected_layer");
+    }
+
+    #[test]
+    fn test_racemic_stereo() {
+        let inchi: InChI =
+            "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m0/s3".parse().unwrap();
+        assert_eq!(
+            inchi.check_standard_conformance(),
+            [ConformanceIssue::NonAbsoluteStereo { isotopic: false, value: 3 }]
+        );
+    }
+
+    #[test]
+    fn test_unpaired_stereo_flags() {
+        let inchi: InChI =
+            "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m0".parse().unwrap();
+        assert_eq!(
+            inchi.check_standard_conformance(),
+            [ConformanceIssue::UnpairedStereoFlag { isotopic: false, inversion: true }]
+        );
+        let inchi: InChI =
+            "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/s1".parse().unwrap();
+        assert_eq!(
+            inchi.check_standard_conformance(),
+            [ConformanceIssue::UnpairedStereoFlag { isotopic: false, inversion: false }]
+        );
+    }
+
+    #[test]
+    fn test_stereo_flags_without_tetrahedral() {
+        let inchi: InChI = "InChI=1S/C4H8/c1-3-4-2/h3-4H,1-2H3/b4-3+/m0/s1".parse().unwrap();
+        assert_eq!(
+            inchi.check_standard_conformance(),
+            [ConformanceIssue::StereoFlagsWithoutTetrahedral { isotopic: false }]
+        );
+    }
+
+    #[test]
+    fn test_isotopic_stereo_flags() {
+        let inchi: InChI = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/i2D/t2-/m1/s2".parse().unwrap();
+        assert_eq!(
+            inchi.check_standard_conformance(),
+            [ConformanceIssue::NonAbsoluteStereo { isotopic: true, value: 2 }]
+        );
+    }
+}
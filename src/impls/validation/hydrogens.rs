@@ -0,0 +1,104 @@
+//! Checks that the hydrogen layer accounts for every hydrogen of the formula.
+
+use alloc::vec::Vec;
+
+use molecular_formulas::MolecularFormula;
+
+use crate::{inchi::InChI, validation::HydrogenImbalance, version::Version};
+
+impl<V: Version> InChI<V> {
+    /// Checks that, for each component, the fixed hydrogens plus the mobile
+    /// hydrogens add up to the hydrogen count of the formula, and returns the
+    /// components where they do not.
+    ///
+    /// The formula and the `/h` layer both describe the structure before the
+    /// `/p` (de)protonation, and the mobile negative charges of a group
+    /// (`(H-,…)`) are charges rather than hydrogens, so neither changes the
+    /// balance. Components made only of hydrogen atoms, where the hydrogens
+    /// are themselves the skeleton, are not checked. The reconnected layer is
+    /// checked against its own formula.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::inchi::InChI;
+    ///
+    /// let inchi: InChI = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3".parse().unwrap();
+    /// assert!(inchi.check_hydrogen_balance().is_empty());
+    ///
+    /// let inchi: InChI = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H2".parse().unwrap();
+    /// let imbalances = inchi.check_hydrogen_balance();
+    /// assert_eq!(imbalances[0].difference(), -1);
+    /// ```
+    #[must_use]
+    pub fn check_hydrogen_balance(&self) -> Vec<HydrogenImbalance> {
+        let mut imbalances: Vec<HydrogenImbalance> = self
+            .components()
+            .filter(|component| component.formula().number_of_non_hydrogens() > 0)
+            .filter_map(|component| {
+                let formula = component.formula();
+                let expected = formula.number_of_elements() - formula.number_of_non_hydrogens();
+                let fixed: usize =
+                    component.fixed_hydrogens().iter().copied().map(usize::from).sum();
+                let mobile: usize = component
+                    .mobile_hydrogens()
+                    .iter()
+                    .map(|group| usize::from(group.count()))
+                    .sum();
+                let found = fixed + mobile;
+                (found != expected).then_some(HydrogenImbalance {
+                    component: component.index(),
+                    expected,
+                    found,
+                })
+            })
+            .collect();
+        if let Some(reconnected) = &self.reconnected {
+            imbalances.extend(reconnected.inchi.check_hydrogen_balance());
+        }
+        imbalances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::inchi::InChI;
+
+    #[test]
+    fn test_balanced_with_mobile_hydrogens() {
+        let inchi: InChI =
+            "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m0/s1".parse().unwrap();
+        assert!(inchi.check_hydrogen_balance().is_empty());
+    }
+
+    #[test]
+    fn test_balanced_with_proton_layer() {
+        let inchi: InChI = "InChI=1S/ClH.Na/h1H;/q;+1/p-1".parse().unwrap();
+        assert!(inchi.check_hydrogen_balance().is_empty());
+    }
+
+    #[test]
+    fn test_excess_hydrogens() {
+        let inchi: InChI = "InChI=1S/CH4.H2O/h1H4;1H3".parse().unwrap();
+        let imbalances = inchi.check_hydrogen_balance();
+        assert_eq!(imbalances.len(), 1);
+        assert_eq!(imbalances[0].component(), 1);
+        assert_eq!(imbalances[0].expected(), 2);
+        assert_eq!(imbalances[0].found(), 3);
+        assert_eq!(imbalances[0].difference(), 1);
+    }
+
+    #[test]
+    fn test_missing_hydrogen_layer() {
+        let inchi: InChI = "InChI=1S/C2H6O".parse().unwrap();
+        let imbalances = inchi.check_hydrogen_balance();
+        assert_eq!(imbalances.len(), 1);
+        assert_eq!(imbalances[0].difference(), -6);
+    }
+
+    #[test]
+    fn test_hydrogen_only_component_is_skipped() {
+        let inchi: InChI = "InChI=1S/H2/h1H".parse().unwrap();
+        assert!(inchi.check_hydrogen_balance().is_empty());
+    }
+}
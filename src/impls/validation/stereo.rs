@@ -0,0 +1,287 @@
+//! Cross-checks the stereo descriptors with the connection graph.
+
+use alloc::vec::Vec;
+
+use geometric_traits::prelude::*;
+
+use crate::{
+    inchi::{
+        Component, InChI,
+        stereochemistry_layer::{DoubleBondStereo, StereochemistryLayer, TetrahedralStereo},
+    },
+    validation::StereoIssue,
+    version::Version,
+};
+
+/// Returns the bonded atoms of `atom`, none when the InChI has no `/c`
+/// layer.
+fn neighbours(component: &Component<'_>, atom: u16) -> Vec<u16> {
+    component
+        .graph()
+        .filter(|graph| atom < graph.number_of_nodes())
+        .map_or_else(Vec::new, |graph| graph.neighbors(atom).collect())
+}
+
+/// Returns the number of fixed hydrogens of `atom`.
+fn fixed_hydrogens(component: &Component<'_>, atom: u16) -> u8 {
+    component.fixed_hydrogens().get(usize::from(atom)).copied().unwrap_or(0)
+}
+
+/// Returns whether `atom` may carry one of the mobile hydrogens.
+fn is_mobile(component: &Component<'_>, atom: u16) -> bool {
+    component.mobile_hydrogens().iter().any(|group| group.atoms().contains(&atom))
+}
+
+/// Returns whether `atom` can be an inner atom of a cumulene chain: two
+/// neighbours and no hydrogen, fixed or mobile.
+fn is_cumulated(component: &Component<'_>, atom: u16) -> bool {
+    neighbours(component, atom).len() == 2
+        && fixed_hydrogens(component, atom) == 0
+        && !is_mobile(component, atom)
+}
+
+/// Walks a cumulene chain entering `atom` from `previous`, and returns the
+/// number of inner atoms crossed and the atom that ends the chain.
+fn walk_cumulene(component: &Component<'_>, mut previous: u16, mut atom: u16) -> (usize, u16) {
+    let number_of_atoms = component.graph().map_or(0, |graph| usize::from(graph.number_of_nodes()));
+    let mut steps = 0;
+    // A ring made only of cumulated atoms would loop forever.
+    while steps <= number_of_atoms && is_cumulated(component, atom) {
+        let Some(next) = neighbours(component, atom).into_iter().find(|&next| next != previous)
+        else {
+            break;
+        };
+        previous = atom;
+        atom = next;
+        steps += 1;
+    }
+    (steps, atom)
+}
+
+/// Checks that the two atoms of a double bond descriptor are bonded, or are
+/// the two ends of a cumulene chain.
+fn check_double_bond(component: &Component<'_>, bond: &DoubleBondStereo) -> Option<StereoIssue> {
+    let (atom1, atom2) = (bond.atom1(), bond.atom2());
+    let start_neighbours = neighbours(component, atom1);
+    let is_plausible = start_neighbours.contains(&atom2)
+        || start_neighbours.into_iter().any(|next| {
+            let (steps, end) = walk_cumulene(component, atom1, next);
+            steps > 0 && end == atom2
+        });
+    (!is_plausible).then_some(StereoIssue::UnbondedDoubleBond {
+        component: component.index(),
+        atoms: (atom1, atom2),
+    })
+}
+
+/// Checks that a tetrahedral descriptor sits on an atom that can be a
+/// stereocentre.
+///
+/// Isotopic descriptors may sit on atoms carrying several hydrogens, as the
+/// isotopes are what tells them apart.
+fn check_tetrahedral(
+    component: &Component<'_>,
+    center: &TetrahedralStereo,
+    isotopic: bool,
+) -> Option<StereoIssue> {
+    let atom = center.atom();
+    let atom_neighbours = neighbours(component, atom);
+    let hydrogens = fixed_hydrogens(component, atom);
+    let substituents =
+        atom_neighbours.len() + usize::from(hydrogens) + usize::from(is_mobile(component, atom));
+
+    // An atom with two neighbours and no hydrogen is the centre of an allene:
+    // it must be the middle of a cumulene whose ends carry substituents.
+    if atom_neighbours.len() == 2 && substituents == 2 {
+        let (left_steps, left_end) = walk_cumulene(component, atom, atom_neighbours[0]);
+        let (right_steps, right_end) = walk_cumulene(component, atom, atom_neighbours[1]);
+        let end_substituents = |end: u16| {
+            neighbours(component, end).len().saturating_sub(1)
+                + usize::from(fixed_hydrogens(component, end))
+        };
+        let is_centred = left_steps == right_steps
+            && left_end != right_end
+            && end_substituents(left_end) >= 2
+            && end_substituents(right_end) >= 2;
+        return (!is_centred)
+            .then_some(StereoIssue::MisplacedAlleneCentre { component: component.index(), atom });
+    }
+
+    if substituents < 3 {
+        return Some(StereoIssue::TooFewSubstituents {
+            component: component.index(),
+            atom,
+            neighbours: atom_neighbours.len(),
+            hydrogens,
+        });
+    }
+    if !isotopic && hydrogens >= 2 {
+        return Some(StereoIssue::TooManyHydrogens {
+            component: component.index(),
+            atom,
+            hydrogens,
+        });
+    }
+    None
+}
+
+/// Checks the descriptors of a stereochemistry layer against `components`.
+fn check_layer(
+    components: &[Component<'_>],
+    layer: &StereochemistryLayer,
+    isotopic: bool,
+    issues: &mut Vec<StereoIssue>,
+) {
+    for component in components {
+        if let Some(double_bonds) =
+            layer.double_bond().and_then(|sublayer| sublayer.components().get(component.index()))
+        {
+            issues
+                .extend(double_bonds.iter().filter_map(|bond| check_double_bond(component, bond)));
+        }
+        if let Some(centers) =
+            layer.tetrahedral().and_then(|sublayer| sublayer.components().get(component.index()))
+        {
+            issues.extend(
+                centers.iter().filter_map(|center| check_tetrahedral(component, center, isotopic)),
+            );
+        }
+    }
+}
+
+impl<V: Version> InChI<V> {
+    /// Cross-checks the stereo descriptors with the connection graph, and
+    /// returns the descriptors that cannot describe a real stereo element.
+    ///
+    /// * Each double bond descriptor must join two bonded atoms, or the two
+    ///   ends of a cumulene chain.
+    /// * Each tetrahedral centre needs at least three neighbours and hydrogens,
+    ///   and at most one hydrogen.
+    /// * A tetrahedral descriptor on an atom with two neighbours and no
+    ///   hydrogen is an allene centre, and must sit in the middle of a cumulene
+    ///   such as `C=C=C`.
+    ///
+    /// The main and isotopic stereochemistry are checked against the main
+    /// layer, and the reconnected layer against its own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::{inchi::InChI, validation::StereoIssue};
+    ///
+    /// // The CH2 of ethanol cannot be a stereocentre.
+    /// let inchi: InChI = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/t2-".parse().unwrap();
+    /// assert_eq!(
+    ///     inchi.check_stereochemistry(),
+    ///     [StereoIssue::TooManyHydrogens { component: 0, atom: 1, hydrogens: 2 }]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn check_stereochemistry(&self) -> Vec<StereoIssue> {
+        let mut issues = Vec::new();
+        let components: Vec<Component<'_>> = self.components().collect();
+        if let Some(stereochemistry) = &self.stereochemistry {
+            check_layer(&components, stereochemistry, false, &mut issues);
+        }
+        if let Some(stereochemistry) =
+            self.isotope.as_ref().and_then(|isotope| isotope.stereochemistry.as_ref())
+        {
+            check_layer(&components, stereochemistry, true, &mut issues);
+        }
+        if let Some(reconnected) = &self.reconnected {
+            issues.extend(reconnected.inchi.check_stereochemistry());
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use crate::{inchi::InChI, validation::StereoIssue};
+
+    fn check(inchi: &str) -> Vec<StereoIssue> {
+        inchi.parse::<InChI>().unwrap().check_stereochemistry()
+    }
+
+    #[test]
+    fn test_valid_stereocentres() {
+        // Ascorbic acid, alanine and glucose.
+        assert!(
+            check("InChI=1S/C6H8O6/c7-1-2(8)5-3(9)4(10)6(11)12-5/h2,5,7-10H,1H2/t2-,5+/m0/s1")
+                .is_empty()
+        );
+        assert!(check("InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m0/s1").is_empty());
+        assert!(
+            check(
+                "InChI=1S/C6H12O6/c7-1-2-3(8)4(9)5(10)6(11)12-2/h2-11H,1H2/t2-,3-,4+,5-,6?/m1/s1"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_valid_double_bond() {
+        assert!(check("InChI=1S/C4H8/c1-3-4-2/h3-4H,1-2H3/b4-3+").is_empty());
+    }
+
+    #[test]
+    fn test_unbonded_double_bond() {
+        assert_eq!(
+            check("InChI=1S/C4H8/c1-3-4-2/h3-4H,1-2H3/b4-1+"),
+            vec![StereoIssue::UnbondedDoubleBond { component: 0, atoms: (3, 0) }]
+        );
+    }
+
+    #[test]
+    fn test_cumulene_double_bond() {
+        // Hexa-2,3,4-triene: the stereo descriptor joins the ends 5-6 of the
+        // cumulene C5=C3=C4=C6.
+        assert!(check("InChI=1S/C6H8/c1-3-5-6-4-2/h3-4H,1-2H3/b4-3+").is_empty());
+    }
+
+    #[test]
+    fn test_allene_centre() {
+        // Penta-2,3-diene: atom 5 is the middle of C3=C5=C4.
+        assert!(check("InChI=1S/C5H8/c1-3-5-4-2/h3-4H,1-2H3/t5-").is_empty());
+    }
+
+    #[test]
+    fn test_misplaced_allene_centre() {
+        // In but-2-yne C1-C3-C4-C2, atom 3 is an end of the chain, not its
+        // middle.
+        assert_eq!(
+            check("InChI=1S/C4H6/c1-3-4-2/h1-2H3/t3-"),
+            vec![StereoIssue::MisplacedAlleneCentre { component: 0, atom: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_too_few_substituents() {
+        // Methanol oxygen: one neighbour and one hydrogen.
+        assert_eq!(
+            check("InChI=1S/CH4O/c1-2/h2H,1H3/t2-"),
+            vec![StereoIssue::TooFewSubstituents {
+                component: 0,
+                atom: 1,
+                neighbours: 1,
+                hydrogens: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn test_too_many_hydrogens() {
+        assert_eq!(
+            check("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/t2-"),
+            vec![StereoIssue::TooManyHydrogens { component: 0, atom: 1, hydrogens: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_isotopic_centre_with_two_hydrogens() {
+        // Ethanol-1-d: atom 2 carries H and D.
+        assert!(check("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/i2D/t2-/m1/s1").is_empty());
+    }
+}
@@ -0,0 +1,170 @@
+//! Checks the valence of every atom against its element.
+
+use alloc::vec::Vec;
+
+use elements_rs::Element;
+use geometric_traits::prelude::*;
+
+use crate::{
+    impls::valences::valences,
+    inchi::{Component, InChI},
+    validation::ValenceIssue,
+    version::Version,
+};
+
+/// Returns the largest valence InChI accepts for an atom of `element`
+/// carrying `charge`, or `None` for the metals and the other elements whose
+/// bonding is not constrained.
+fn largest_valence(element: Element, charge: i16) -> Option<usize> {
+    valences(element, charge).map(|valences| valences.last().copied().map_or(0, usize::from))
+}
+
+/// Returns the atoms of `component` exceeding the valence of their element.
+fn check_component(component: &Component<'_>) -> Vec<ValenceIssue> {
+    // Components too large to index have no connection layer to check.
+    let (Some(graph), Ok(elements)) = (component.graph(), component.elements()) else {
+        return Vec::new();
+    };
+    let mut remaining_charge = component.charge();
+    elements
+        .into_iter()
+        .zip(0..graph.number_of_nodes())
+        .filter_map(|(element, atom)| {
+            let max_valence = largest_valence(element, 0)?;
+            let connections = graph.neighbors(atom).count();
+            let hydrogens =
+                component.fixed_hydrogens().get(usize::from(atom)).copied().unwrap_or(0);
+            let used = connections + usize::from(hydrogens);
+            if used <= max_valence {
+                return None;
+            }
+            // The atom takes the fewest units of the remaining charge that
+            // give it a valence large enough.
+            let sign = remaining_charge.signum();
+            let charge = (1..=remaining_charge.abs())
+                .map(|units| units * sign)
+                .find(|&charge| largest_valence(element, charge).is_some_and(|max| used <= max));
+            if let Some(charge) = charge {
+                remaining_charge -= charge;
+                return None;
+            }
+            Some(ValenceIssue {
+                component: component.index(),
+                atom,
+                element,
+                connections,
+                hydrogens,
+                max_valence,
+            })
+        })
+        .collect()
+}
+
+impl<V: Version> InChI<V> {
+    /// Checks that no atom has more bonded atoms and fixed hydrogens than the
+    /// largest valence of its element, and returns the atoms that do.
+    ///
+    /// The elements are read from the Hill-ordered formula. Bond orders are
+    /// not part of the connection layer, so only excess valence can be
+    /// detected. The charge of a component goes, in atom order, to the atoms
+    /// that need it: an atom above the valences of its neutral element takes
+    /// the fewest units of charge that give it a valence it fits, as the
+    /// oxygen of trimethyloxonium. The atoms of a mobile group are assumed
+    /// to carry none of its hydrogens. Elements without a fixed
+    /// valence, such as the metals, are not checked. The reconnected layer is
+    /// checked as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::inchi::InChI;
+    ///
+    /// let inchi: InChI = "InChI=1S/C5H12/c1-5(2,3)4/h1-4H3".parse().unwrap();
+    /// assert!(inchi.check_valences().is_empty());
+    ///
+    /// let inchi: InChI = "InChI=1S/C5H12/c1-5(2,3)4/h1-4H3,5H".parse().unwrap();
+    /// let issues = inchi.check_valences();
+    /// assert_eq!(
+    ///     issues[0].to_string(),
+    ///     "C atom 5 in component 0 has 4 connections and 1 hydrogens, more than its maximum valence of 4"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn check_valences(&self) -> Vec<ValenceIssue> {
+        let mut issues: Vec<ValenceIssue> =
+            self.components().flat_map(|component| check_component(&component)).collect();
+        if let Some(reconnected) = &self.reconnected {
+            issues.extend(reconnected.inchi.check_valences());
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use elements_rs::Element;
+
+    use crate::inchi::InChI;
+
+    #[test]
+    fn test_pentavalent_carbon() {
+        let inchi: InChI = "InChI=1S/C6H14/c1-6(2,3,4)5/h1-5H3".parse().unwrap();
+        let issues = inchi.check_valences();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].component(), 0);
+        assert_eq!(issues[0].atom(), 5);
+        assert_eq!(issues[0].element(), Element::C);
+        assert_eq!(issues[0].connections(), 5);
+        assert_eq!(issues[0].hydrogens(), 0);
+        assert_eq!(issues[0].max_valence(), 4);
+    }
+
+    #[test]
+    fn test_elements_follow_hill_order() {
+        // Atom 3 is the oxygen: two bonds and one hydrogen exceed its valence.
+        let inchi: InChI = "InChI=1S/C2H6O/c1-2-3/h2H2,1H3,3H2".parse().unwrap();
+        let issues = inchi.check_valences();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].element(), Element::O);
+        assert_eq!(issues[0].atom(), 2);
+    }
+
+    #[test]
+    fn test_charge_allows_extra_bond() {
+        let inchi: InChI = "InChI=1S/C4H12N/c1-5(2,3)4/h1-4H3/q+1".parse().unwrap();
+        assert!(inchi.check_valences().is_empty());
+        let inchi: InChI = "InChI=1S/C4H12O/c1-5(2,3)4/h1-4H3".parse().unwrap();
+        assert_eq!(inchi.check_valences().len(), 1);
+        let inchi: InChI = "InChI=1S/C3H9O/c1-4(2)3/h1-3H3/q+1".parse().unwrap();
+        assert!(inchi.check_valences().is_empty());
+    }
+
+    #[test]
+    fn test_charge_goes_to_one_atom() {
+        // A negative charge gives oxygen fewer bonds, not more.
+        let inchi: InChI = "InChI=1S/C3H9O/c1-4(2)3/h1-3H3/q-1".parse().unwrap();
+        let issues = inchi.check_valences();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].max_valence(), 2);
+        // A single positive charge fits only one of two trivalent oxygens.
+        let inchi: InChI = "InChI=1S/C4H12O2/c1-5(2)6(3)4/h1-4H3/q+1".parse().unwrap();
+        let issues = inchi.check_valences();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].atom(), 5);
+    }
+
+    #[test]
+    fn test_hypervalent_atoms() {
+        let inchi: InChI = "InChI=1S/C3H9NO/c1-4(2,3)5/h1-3H3".parse().unwrap();
+        assert!(inchi.check_valences().is_empty());
+        let inchi: InChI = "InChI=1S/C2H6O4S/c1-5-7(3,4)6-2/h1-2H3".parse().unwrap();
+        assert!(inchi.check_valences().is_empty());
+    }
+
+    #[test]
+    fn test_metals_are_not_checked() {
+        assert_eq!(super::largest_valence(Element::Fe, 0), None);
+        let inchi: InChI = "InChI=1S/C2H6Hg/c1-3-2/h1-2H3".parse().unwrap();
+        assert!(inchi.check_valences().is_empty());
+    }
+}
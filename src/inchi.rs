@@ -1,16 +1,31 @@
 //! Module for the InChI structure and its layers.
 
+pub mod any_inchi;
+pub mod aromaticity;
+pub mod atom;
 pub mod charge_layer;
+pub mod component;
 pub mod fixed_hydrogen;
 pub mod isotope_layer;
+pub mod kekule;
 pub mod main_layer;
 pub mod proton_layer;
 pub mod reconnected_layer;
+pub mod rings;
 pub mod stereochemistry_layer;
+use alloc::vec::Vec;
+
+pub use any_inchi::AnyInChI;
+pub use aromaticity::Aromaticity;
+pub use atom::Atom;
+pub use component::Component;
 pub use fixed_hydrogen::FixedHydrogenLayer;
 pub use isotope_layer::IsotopeLayer;
+pub use kekule::{BondOrder, KekuleStructure};
 pub use main_layer::MainLayer;
+use molecular_formulas::MolecularFormula;
 pub use reconnected_layer::ReconnectedLayer;
+pub use rings::Rings;
 pub use stereochemistry_layer::StereochemistryLayer;
 
 use crate::{
@@ -19,6 +34,12 @@ use crate::{
 };
 
 impl<V: Version> InChI<V> {
+    /// Returns whether the InChI is a standard one (`InChI=1S/`).
+    #[must_use]
+    pub fn is_standard(&self) -> bool {
+        V::IS_STANDARD
+    }
+
     /// Returns the main layer (formula, connections, hydrogens), if present.
     ///
     /// Proton-only InChIs (e.g. `InChI=1S/p+1`) have no main layer.
@@ -39,6 +60,27 @@ impl<V: Version> InChI<V> {
         self.proton.as_ref().map(|p| p.proton_count)
     }
 
+    /// Returns the net ionic charge of the InChI: the sum of the component
+    /// charges of the `/q` layer plus the protons added or removed by the
+    /// `/p` layer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::inchi::InChI;
+    ///
+    /// let acetate: InChI = "InChI=1S/C2H4O2/c1-2(3)4/h1H3,(H,3,4)/p-1".parse().unwrap();
+    /// assert_eq!(acetate.net_charge(), -1);
+    ///
+    /// let salt: InChI = "InChI=1S/C2H4O2.Na/c1-2(3)4;/h1H3,(H,3,4);/q;+1/p-1".parse().unwrap();
+    /// assert_eq!(salt.net_charge(), 0);
+    /// ```
+    #[must_use]
+    pub fn net_charge(&self) -> i32 {
+        let charge: i32 = self.charges().unwrap_or_default().iter().copied().map(i32::from).sum();
+        charge + self.proton_count().map_or(0, i32::from)
+    }
+
     /// Returns the stereochemistry layer, if present.
     #[must_use]
     pub fn stereochemistry(&self) -> Option<&StereochemistryLayer> {
@@ -50,6 +92,68 @@ impl<V: Version> InChI<V> {
     pub fn isotope(&self) -> Option<&IsotopeLayer> {
         self.isotope.as_ref()
     }
+
+    /// Returns the fixed hydrogen layer, if present.
+    #[must_use]
+    pub fn fixed_hydrogen(&self) -> Option<&FixedHydrogenLayer> {
+        self.fixed_hydrogen.as_ref()
+    }
+
+    /// Returns the reconnected layer, if present.
+    #[must_use]
+    pub fn reconnected(&self) -> Option<&ReconnectedLayer<V>> {
+        self.reconnected.as_ref()
+    }
+
+    /// Returns an iterator over the components of the InChI.
+    ///
+    /// Each [`Component`] joins the entries of every per-component layer for
+    /// one `.`-separated fragment of the formula, with the `n*` repetition
+    /// prefixes already expanded. Proton-only InChIs have no components.
+    pub fn components(&self) -> impl Iterator<Item = Component<'_>> + '_ {
+        let main_layer = self.main_layer.as_ref();
+        let graphs = main_layer.and_then(MainLayer::atom_connections);
+        let hydrogens = main_layer.and_then(MainLayer::hydrogens);
+        let double_bonds =
+            self.stereochemistry.as_ref().and_then(StereochemistryLayer::double_bond);
+        let tetrahedral = self.stereochemistry.as_ref().and_then(StereochemistryLayer::tetrahedral);
+
+        main_layer
+            .into_iter()
+            .flat_map(|main_layer| main_layer.chemical_formula().subformulas())
+            .enumerate()
+            .map(move |(index, formula)| {
+                Component {
+                    index,
+                    formula,
+                    graph: graphs.and_then(|graphs| graphs.get(index)),
+                    hydrogens: hydrogens.and_then(|hydrogens| hydrogens.components.get(index)),
+                    charge: self
+                        .charge
+                        .as_ref()
+                        .and_then(|charge| charge.charges.get(index).copied())
+                        .unwrap_or(0),
+                    double_bonds: double_bonds
+                        .and_then(|double_bonds| double_bonds.components.get(index))
+                        .map_or(&[], Vec::as_slice),
+                    tetrahedral: tetrahedral
+                        .and_then(|tetrahedral| tetrahedral.components.get(index))
+                        .map_or(&[], Vec::as_slice),
+                    isotope: self
+                        .isotope
+                        .as_ref()
+                        .and_then(|isotope| isotope.components.get(index)),
+                }
+            })
+    }
+
+    /// Returns an iterator over the components of the reconnected `/r`
+    /// layer, in which the bonds to metal atoms are restored.
+    ///
+    /// The iterator is empty when the InChI has no reconnected layer.
+    pub fn reconnected_components(&self) -> impl Iterator<Item = Component<'_>> + '_ {
+        self.reconnected.iter().flat_map(|reconnected| reconnected.inchi.components())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -61,6 +165,6 @@ pub struct InChI<V: Version = crate::version::StandardVersion1_07_4> {
     pub(crate) stereochemistry: Option<StereochemistryLayer>,
     pub(crate) isotope: Option<IsotopeLayer>,
     pub(crate) fixed_hydrogen: Option<FixedHydrogenLayer>,
-    pub(crate) reconnected: Option<ReconnectedLayer>,
+    pub(crate) reconnected: Option<ReconnectedLayer<V>>,
     pub(crate) _version: core::marker::PhantomData<V>,
 }
@@ -0,0 +1,56 @@
+//! Module for InChIs whose version is only known at runtime.
+
+use crate::{
+    inchi::InChI,
+    version::{StandardVersion1_07_4, Version1_07_4},
+};
+
+#[derive(Debug, PartialEq, Eq)]
+/// An InChI of any supported version, detected from its prefix when parsing.
+///
+/// Use this type when the input mixes standard (`InChI=1S/`) and non-standard
+/// (`InChI=1/`) identifiers.
+pub enum AnyInChI {
+    /// A standard InChI (`InChI=1S/`).
+    Standard(InChI<StandardVersion1_07_4>),
+    /// A non-standard InChI (`InChI=1/`).
+    NonStandard(InChI<Version1_07_4>),
+}
+
+impl AnyInChI {
+    /// Returns whether the InChI is a standard one.
+    #[must_use]
+    pub fn is_standard(&self) -> bool {
+        matches!(self, AnyInChI::Standard(_))
+    }
+
+    /// Returns the InChI if it is a standard one.
+    #[must_use]
+    pub fn as_standard(&self) -> Option<&InChI<StandardVersion1_07_4>> {
+        match self {
+            AnyInChI::Standard(inchi) => Some(inchi),
+            AnyInChI::NonStandard(_) => None,
+        }
+    }
+
+    /// Returns the InChI if it is a non-standard one.
+    #[must_use]
+    pub fn as_non_standard(&self) -> Option<&InChI<Version1_07_4>> {
+        match self {
+            AnyInChI::Standard(_) => None,
+            AnyInChI::NonStandard(inchi) => Some(inchi),
+        }
+    }
+}
+
+impl From<InChI<StandardVersion1_07_4>> for AnyInChI {
+    fn from(inchi: InChI<StandardVersion1_07_4>) -> Self {
+        AnyInChI::Standard(inchi)
+    }
+}
+
+impl From<InChI<Version1_07_4>> for AnyInChI {
+    fn from(inchi: InChI<Version1_07_4>) -> Self {
+        AnyInChI::NonStandard(inchi)
+    }
+}
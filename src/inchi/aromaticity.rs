@@ -0,0 +1,69 @@
+//! Module for the aromatic atoms and bonds perceived in an InChI component.
+
+use alloc::vec::Vec;
+
+use crate::inchi::KekuleStructure;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The aromatic atoms, bonds and rings of a component, perceived on one of
+/// its Kekulé structures.
+///
+/// A ring atom contributes one π electron when it has a double bond within
+/// a ring, none when it has a double bond out of the rings to an atom other
+/// than carbon, as the carbonyl carbon of a pyridone, or an empty p orbital,
+/// as B or C⁺, and two when it has a lone pair with only single bonds, as
+/// the nitrogen of pyrrole, the oxygen of furan or C⁻. Atoms with a triple
+/// bond, two double bonds, a double bond out of the rings to a carbon, or
+/// no p orbital to share are not aromatic.
+///
+/// Each ring of the smallest set of smallest rings whose atoms all
+/// contribute is aromatic when it holds 4n + 2 π electrons, following
+/// Hückel's rule. So are two contributing rings sharing a bond, as the five
+/// and seven atom rings of azulene, and every fused system of contributing
+/// rings as a whole, when the atoms they cover hold 4n + 2 π electrons.
+pub struct Aromaticity {
+    pub(crate) atoms: Vec<bool>,
+    pub(crate) bonds: Vec<(u16, u16)>,
+    pub(crate) rings: Vec<Vec<u16>>,
+    pub(crate) kekule_structure: KekuleStructure,
+}
+
+impl Aromaticity {
+    /// Returns whether atom `atom`, a 0-based index, is aromatic.
+    #[must_use]
+    pub fn is_aromatic_atom(&self, atom: u16) -> bool {
+        self.atoms.get(usize::from(atom)).copied().unwrap_or(false)
+    }
+
+    /// Returns the 0-based indices of the aromatic atoms, in increasing
+    /// order.
+    pub fn aromatic_atoms(&self) -> impl Iterator<Item = u16> + '_ {
+        self.atoms.iter().zip(0..).filter_map(|(&aromatic, atom)| aromatic.then_some(atom))
+    }
+
+    /// Returns whether the bond between atoms `a` and `b` is aromatic.
+    #[must_use]
+    pub fn is_aromatic_bond(&self, a: u16, b: u16) -> bool {
+        self.bonds.binary_search(&(a.min(b), a.max(b))).is_ok()
+    }
+
+    /// Returns the aromatic bonds, as pairs of 0-based atom indices with the
+    /// smaller index first, sorted.
+    #[must_use]
+    pub fn aromatic_bonds(&self) -> &[(u16, u16)] {
+        &self.bonds
+    }
+
+    /// Returns the aromatic rings of the smallest set of smallest rings,
+    /// each listing its atoms in order around it.
+    #[must_use]
+    pub fn aromatic_rings(&self) -> &[Vec<u16>] {
+        &self.rings
+    }
+
+    /// Returns the Kekulé structure the aromaticity was perceived on.
+    #[must_use]
+    pub fn kekule_structure(&self) -> &KekuleStructure {
+        &self.kekule_structure
+    }
+}
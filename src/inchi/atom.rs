@@ -0,0 +1,112 @@
+//! Module for the per-atom view of an InChI component.
+
+use alloc::vec::Vec;
+
+use elements_rs::Element;
+
+use crate::inchi::{isotope_layer::IsotopeAtom, main_layer::MobileHydrogenGroup};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A view over a single atom of a [`Component`](crate::inchi::Component).
+///
+/// InChI numbers the non-hydrogen atoms of a component in the Hill order of
+/// its formula: the carbons first, then the other elements alphabetically.
+/// The view joins the element of the atom with its entries in the `/c`, `/h`
+/// and `/i` layers, including the mobile hydrogen group it belongs to.
+pub struct Atom<'a> {
+    pub(crate) index: u16,
+    pub(crate) element: Element,
+    pub(crate) hydrogens: u8,
+    pub(crate) min_hydrogens: u8,
+    pub(crate) max_hydrogens: u8,
+    pub(crate) isotope: Option<&'a IsotopeAtom>,
+    pub(crate) neighbours: Vec<u16>,
+    pub(crate) mobile_group: Option<(usize, &'a MobileHydrogenGroup)>,
+}
+
+impl<'a> Atom<'a> {
+    /// Returns the 0-based index of the atom in its component.
+    #[must_use]
+    pub fn index(&self) -> u16 {
+        self.index
+    }
+
+    /// Returns the element of the atom.
+    #[must_use]
+    pub fn element(&self) -> Element {
+        self.element
+    }
+
+    /// Returns the number of fixed hydrogens attached to the atom.
+    #[must_use]
+    pub fn hydrogens(&self) -> u8 {
+        self.hydrogens
+    }
+
+    /// Returns the isotope specification of the atom, if the `/i` layer has
+    /// one.
+    #[must_use]
+    pub fn isotope(&self) -> Option<&'a IsotopeAtom> {
+        self.isotope
+    }
+
+    /// Returns the mass shift of the atom relative to the rounded average
+    /// atomic mass of its element, if the `/i` layer gives one.
+    #[must_use]
+    pub fn mass_shift(&self) -> Option<i16> {
+        self.isotope.and_then(IsotopeAtom::mass_shift)
+    }
+
+    /// Returns the 0-based indices of the bonded atoms, in ascending order.
+    /// Empty when the InChI has no `/c` layer.
+    #[must_use]
+    pub fn neighbours(&self) -> &[u16] {
+        &self.neighbours
+    }
+
+    /// Returns the number of bonded atoms.
+    #[must_use]
+    pub fn degree(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    /// Returns the mobile hydrogen group the atom belongs to, if any.
+    #[must_use]
+    pub fn mobile_group(&self) -> Option<&'a MobileHydrogenGroup> {
+        self.mobile_group.map(|(_, group)| group)
+    }
+
+    /// Returns the 0-based index of the mobile hydrogen group the atom
+    /// belongs to among the groups of its component, if any.
+    #[must_use]
+    pub fn mobile_group_index(&self) -> Option<usize> {
+        self.mobile_group.map(|(index, _)| index)
+    }
+
+    /// Returns the fewest hydrogens the atom can carry: its fixed hydrogens,
+    /// plus the mobile hydrogens of its group that the other atoms of the
+    /// group have no free valence for.
+    #[must_use]
+    pub fn min_hydrogens(&self) -> u8 {
+        self.min_hydrogens
+    }
+
+    /// Returns the most hydrogens the atom can carry: its fixed hydrogens
+    /// plus the mobile hydrogens of its group, up to its free valence.
+    ///
+    /// The free valence is what the lowest fitting valence of the neutral
+    /// element leaves once the bonds and the fixed hydrogens are counted.
+    /// Bond orders are not known, so every bond counts as single, and atoms
+    /// of elements without valences, such as the metals, are not limited.
+    #[must_use]
+    pub fn max_hydrogens(&self) -> u8 {
+        self.max_hydrogens
+    }
+
+    /// Returns whether the atom can carry one of the mobile negative charges
+    /// of its group.
+    #[must_use]
+    pub fn can_carry_negative_charge(&self) -> bool {
+        self.mobile_group().is_some_and(|group| group.negative_count() > 0)
+    }
+}
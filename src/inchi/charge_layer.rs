@@ -5,10 +5,15 @@ use alloc::vec::Vec;
 use crate::traits::prefix::Prefix;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct ChargeSubLayer {
-    pub(crate) charges: Vec<i16>,
+/// The `/q` sublayer, with one charge per component.
+///
+/// In the fixed-H layer, a component may be written `m` when its charge is
+/// the same as in the main layer: its charges are then `Option<i16>`, `None`
+/// for such a component.
+pub(crate) struct ChargeSubLayer<C = i16> {
+    pub(crate) charges: Vec<C>,
 }
 
-impl Prefix for ChargeSubLayer {
+impl<C> Prefix for ChargeSubLayer<C> {
     const PREFIX: char = 'q';
 }
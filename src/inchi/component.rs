@@ -0,0 +1,236 @@
+//! Module for the per-component view of an InChI.
+
+use alloc::vec::Vec;
+
+use elements_rs::Element;
+use geometric_traits::prelude::*;
+use molecular_formulas::{InChIFormula, MolecularFormula};
+
+use crate::{
+    errors::ComponentError,
+    impls::valences::valences,
+    inchi::{
+        Atom,
+        isotope_layer::IsotopeComponent,
+        main_layer::{HydrogenComponent, MobileHydrogenGroup, MolecularGraph},
+        stereochemistry_layer::{DoubleBondStereo, TetrahedralStereo},
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A view over a single component of an InChI.
+///
+/// A component is one `.`-separated fragment of the chemical formula, with
+/// the `n*` repetition prefixes expanded. The view lines up the entries of
+/// every per-component layer (`/c`, `/h`, `/q`, `/b`, `/t`, `/i`) for that
+/// fragment.
+pub struct Component<'a> {
+    pub(crate) index: usize,
+    pub(crate) formula: InChIFormula,
+    pub(crate) graph: Option<&'a MolecularGraph<u16>>,
+    pub(crate) hydrogens: Option<&'a HydrogenComponent>,
+    pub(crate) charge: i16,
+    pub(crate) double_bonds: &'a [DoubleBondStereo],
+    pub(crate) tetrahedral: &'a [TetrahedralStereo],
+    pub(crate) isotope: Option<&'a IsotopeComponent>,
+}
+
+impl<'a> Component<'a> {
+    /// Returns the 0-based position of this component in the InChI.
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the subformula of this component.
+    #[must_use]
+    pub fn formula(&self) -> &InChIFormula {
+        &self.formula
+    }
+
+    /// Returns the element of each atom, indexed by 0-based atom index.
+    ///
+    /// InChI numbers the non-hydrogen atoms in the Hill order of the formula,
+    /// so carbons come first and the other elements follow alphabetically.
+    /// Components made only of hydrogen, such as `H2`, number the hydrogen
+    /// atoms themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ComponentError::TooManyAtoms`] when the formula has more
+    /// atoms than a `u16` atom index can address.
+    pub fn elements(&self) -> Result<Vec<Element>, ComponentError> {
+        let mut elements = Vec::new();
+        let mut hydrogens: usize = 0;
+        let extend = |elements: &mut Vec<Element>, element, count| {
+            let atoms = elements.len().saturating_add(count);
+            if atoms > usize::from(u16::MAX) + 1 {
+                return Err(ComponentError::TooManyAtoms { component: self.index, atoms });
+            }
+            elements.extend(core::iter::repeat_n(element, count));
+            Ok(())
+        };
+        for (element, count) in self.formula.iter_counted_elements() {
+            let count = usize::try_from(count).unwrap_or(usize::MAX);
+            if element == Element::H {
+                hydrogens = hydrogens.saturating_add(count);
+            } else {
+                extend(&mut elements, element, count)?;
+            }
+        }
+        if elements.is_empty() {
+            extend(&mut elements, Element::H, hydrogens)?;
+        }
+        Ok(elements)
+    }
+
+    /// Returns the atom with 0-based index `index`, or `None` when the
+    /// component has no such atom.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use elements_rs::Element;
+    /// use inchi_parser::inchi::InChI;
+    ///
+    /// let inchi: InChI = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/i3+2".parse().unwrap();
+    /// let component = inchi.components().next().unwrap();
+    /// let oxygen = component.atom(2).unwrap();
+    /// assert_eq!(oxygen.element(), Element::O);
+    /// assert_eq!(oxygen.hydrogens(), 1);
+    /// assert_eq!(oxygen.mass_shift(), Some(2));
+    /// assert_eq!(oxygen.neighbours(), [1]);
+    /// assert_eq!(oxygen.degree(), 1);
+    /// assert!(component.atom(3).is_none());
+    /// ```
+    #[must_use]
+    pub fn atom(&self, index: u16) -> Option<Atom<'a>> {
+        let elements = self.elements().ok()?;
+        (usize::from(index) < elements.len()).then(|| self.build_atom(index, &elements))
+    }
+
+    /// Returns an iterator over the atoms of this component, in InChI order.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ComponentError`] when the elements of the component
+    /// cannot be read, see [`Component::elements`].
+    pub fn atoms(&self) -> Result<impl Iterator<Item = Atom<'a>> + '_, ComponentError> {
+        let elements = self.elements()?;
+        Ok((0..=u16::MAX).take(elements.len()).map(move |index| self.build_atom(index, &elements)))
+    }
+
+    /// Returns the 0-based indices of the atoms bonded to atom `index`.
+    fn neighbours(&self, index: u16) -> Vec<u16> {
+        self.graph
+            .filter(|graph| index < graph.number_of_nodes())
+            .map_or_else(Vec::new, |graph| graph.neighbors(index).collect())
+    }
+
+    /// Returns how many more hydrogens atom `index`, of `element`, can carry:
+    /// the lowest valence of its neutral element that fits its bonds and
+    /// fixed hydrogens, minus these. `None` when the element has no valences
+    /// to limit it, as for the metals.
+    fn free_valence(&self, index: u16, element: Element) -> Option<usize> {
+        let fixed = self.fixed_hydrogens().get(usize::from(index)).copied().unwrap_or(0);
+        let used = self.neighbours(index).len() + usize::from(fixed);
+        let valences = valences(element, 0)?;
+        Some(
+            valences
+                .iter()
+                .map(|&valence| usize::from(valence))
+                .find(|&valence| valence >= used)
+                .map_or(0, |valence| valence - used),
+        )
+    }
+
+    /// Returns the view over atom `index` of the component, whose atoms are
+    /// of `elements`.
+    fn build_atom(&self, index: u16, elements: &[Element]) -> Atom<'a> {
+        let mut neighbours = self.neighbours(index);
+        neighbours.sort_unstable();
+        let hydrogens = self.fixed_hydrogens().get(usize::from(index)).copied().unwrap_or(0);
+        let mobile_group = self
+            .mobile_hydrogens()
+            .iter()
+            .enumerate()
+            .find(|(_, group)| group.atoms.contains(&index));
+
+        // The mobile hydrogens an atom takes are bounded by its free valence,
+        // and it must take those the other atoms of its group cannot.
+        let (min_mobile, max_mobile) = mobile_group.map_or((0, 0), |(_, group)| {
+            let count = usize::from(group.count());
+            let free = |atom: u16| {
+                elements
+                    .get(usize::from(atom))
+                    .and_then(|&element| self.free_valence(atom, element))
+                    .map_or(count, |free| free.min(count))
+            };
+            let others: usize =
+                group.atoms.iter().filter(|&&atom| atom != index).map(|&atom| free(atom)).sum();
+            let max = free(index);
+            (count.saturating_sub(others).min(max), max)
+        });
+        let with_fixed =
+            |mobile: usize| u8::try_from(usize::from(hydrogens) + mobile).unwrap_or(u8::MAX);
+
+        Atom {
+            index,
+            element: elements[usize::from(index)],
+            hydrogens,
+            min_hydrogens: with_fixed(min_mobile),
+            max_hydrogens: with_fixed(max_mobile),
+            isotope: self
+                .isotope
+                .and_then(|isotope| isotope.atoms.iter().find(|atom| atom.atom_index == index)),
+            neighbours,
+            mobile_group,
+        }
+    }
+
+    /// Returns the molecular graph of this component, if the InChI has a
+    /// `/c` layer.
+    #[must_use]
+    pub fn graph(&self) -> Option<&'a MolecularGraph<u16>> {
+        self.graph
+    }
+
+    /// Returns the per-atom fixed hydrogen counts, indexed by 0-based atom
+    /// index. Empty when the InChI has no `/h` layer.
+    #[must_use]
+    pub fn fixed_hydrogens(&self) -> &'a [u8] {
+        self.hydrogens.map_or(&[], |hydrogens| hydrogens.fixed_h.as_slice())
+    }
+
+    /// Returns the mobile hydrogen groups of this component.
+    #[must_use]
+    pub fn mobile_hydrogens(&self) -> &'a [MobileHydrogenGroup] {
+        self.hydrogens.map_or(&[], |hydrogens| hydrogens.mobile_groups.as_slice())
+    }
+
+    /// Returns the charge of this component, `0` when the InChI has no `/q`
+    /// layer.
+    #[must_use]
+    pub fn charge(&self) -> i16 {
+        self.charge
+    }
+
+    /// Returns the double bond stereo specifications of this component.
+    #[must_use]
+    pub fn double_bond_stereo(&self) -> &'a [DoubleBondStereo] {
+        self.double_bonds
+    }
+
+    /// Returns the tetrahedral stereo specifications of this component.
+    #[must_use]
+    pub fn tetrahedral_stereo(&self) -> &'a [TetrahedralStereo] {
+        self.tetrahedral
+    }
+
+    /// Returns the isotope specifications of this component, if the InChI
+    /// has an `/i` layer.
+    #[must_use]
+    pub fn isotope(&self) -> Option<&'a IsotopeComponent> {
+        self.isotope
+    }
+}
@@ -1,11 +1,97 @@
 //! Module for handling the fixed hydrogen layer.
 
-use crate::traits::prefix::Prefix;
+use alloc::vec::Vec;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+use molecular_formulas::InChIFormula;
+
+use crate::{
+    inchi::{
+        IsotopeLayer, StereochemistryLayer,
+        charge_layer::ChargeSubLayer,
+        main_layer::{HydrogenComponent, HydrogensSubLayer},
+    },
+    traits::prefix::Prefix,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// Represents the fixed hydrogen layer of an InChI.
-pub struct FixedHydrogenLayer;
+///
+/// The layer starts with `/f` and describes the structure with every mobile
+/// hydrogen assigned to a specific atom. It only appears in non-standard
+/// InChIs.
+pub struct FixedHydrogenLayer {
+    /// Formula of the fixed-H structure. `None` when it is the same as the
+    /// main layer formula (an empty `/f`).
+    pub(crate) chemical_formula: Option<InChIFormula>,
+    pub(crate) hydrogens: Option<HydrogensSubLayer>,
+    pub(crate) charge: Option<ChargeSubLayer<Option<i16>>>,
+    pub(crate) stereochemistry: Option<StereochemistryLayer>,
+    pub(crate) isotope: Option<IsotopeLayer>,
+    pub(crate) transposition: Option<TranspositionSubLayer>,
+}
+
+impl FixedHydrogenLayer {
+    /// Returns the formula of the fixed-H structure, if it differs from the
+    /// main layer formula.
+    #[must_use]
+    pub fn chemical_formula(&self) -> Option<&InChIFormula> {
+        self.chemical_formula.as_ref()
+    }
+
+    /// Returns the per-component fixed hydrogens from the `/h` sublayer, if
+    /// present.
+    #[must_use]
+    pub fn hydrogens(&self) -> Option<&[HydrogenComponent]> {
+        self.hydrogens.as_ref().map(|h| h.components.as_slice())
+    }
+
+    /// Returns the per-component charges from the `/q` sublayer, if present.
+    /// A component written `m`, whose charge is the same as in the main
+    /// layer, has no charge of its own.
+    #[must_use]
+    pub fn charges(&self) -> Option<&[Option<i16>]> {
+        self.charge.as_ref().map(|c| c.charges.as_slice())
+    }
+
+    /// Returns the fixed-H stereochemistry sublayers, if present.
+    #[must_use]
+    pub fn stereochemistry(&self) -> Option<&StereochemistryLayer> {
+        self.stereochemistry.as_ref()
+    }
+
+    /// Returns the fixed-H isotope sublayer, if present.
+    #[must_use]
+    pub fn isotope(&self) -> Option<&IsotopeLayer> {
+        self.isotope.as_ref()
+    }
+
+    /// Returns the `/o` transposition, if present.
+    #[must_use]
+    pub fn transposition(&self) -> Option<&TranspositionSubLayer> {
+        self.transposition.as_ref()
+    }
+}
 
 impl Prefix for FixedHydrogenLayer {
     const PREFIX: char = 'f';
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The `/o` transposition sublayer: how the components of the fixed-H layer
+/// are reordered with respect to the main layer.
+pub struct TranspositionSubLayer {
+    /// Permutation cycles of 0-based component indices.
+    pub(crate) cycles: Vec<Vec<u16>>,
+}
+
+impl TranspositionSubLayer {
+    /// Returns the permutation cycles, as 0-based component indices.
+    #[must_use]
+    pub fn cycles(&self) -> &[Vec<u16>] {
+        &self.cycles
+    }
+}
+
+impl Prefix for TranspositionSubLayer {
+    const PREFIX: char = 'o';
+}
@@ -4,7 +4,7 @@ use alloc::vec::Vec;
 
 use elements_rs::isotopes::HydrogenIsotope;
 
-use crate::traits::prefix::Prefix;
+use crate::{inchi::StereochemistryLayer, traits::prefix::Prefix};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// An atom isotope specification.
@@ -40,6 +40,9 @@ pub struct IsotopeComponent {
 /// The isotope layer of an InChI.
 pub struct IsotopeLayer {
     pub(crate) components: Vec<IsotopeComponent>,
+    /// The isotopic `/b`, `/t`, `/m` and `/s` sublayers, describing
+    /// stereochemistry that only exists because of the isotopes.
+    pub(crate) stereochemistry: Option<StereochemistryLayer>,
 }
 
 impl IsotopeAtom {
@@ -97,6 +100,13 @@ impl IsotopeLayer {
     pub fn components(&self) -> &[IsotopeComponent] {
         &self.components
     }
+
+    /// Returns the isotopic stereochemistry, if the layer is followed by
+    /// isotope-dependent `/b`, `/t`, `/m` or `/s` sublayers.
+    #[must_use]
+    pub fn stereochemistry(&self) -> Option<&StereochemistryLayer> {
+        self.stereochemistry.as_ref()
+    }
 }
 
 impl Prefix for IsotopeLayer {
@@ -0,0 +1,73 @@
+//! Module for the bond orders assigned to an InChI component.
+
+use alloc::vec::Vec;
+
+/// The order of a bond in a Kekulé structure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BondOrder {
+    /// A single bond.
+    Single,
+    /// A double bond.
+    Double,
+    /// A triple bond.
+    Triple,
+}
+
+impl BondOrder {
+    /// Returns the number of electron pairs shared by the bond.
+    #[must_use]
+    pub fn value(self) -> u8 {
+        match self {
+            BondOrder::Single => 1,
+            BondOrder::Double => 2,
+            BondOrder::Triple => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A Kekulé structure of a component: an order for every bond of its
+/// connection graph, with the hydrogens and charges of each atom that make
+/// the valences add up.
+///
+/// The mobile hydrogens and the mobile negative charges of the `/h` layer
+/// are placed on one atom each, and the charge of the `/q` layer on the
+/// atoms that can carry it.
+pub struct KekuleStructure {
+    pub(crate) bonds: Vec<((u16, u16), BondOrder)>,
+    pub(crate) hydrogens: Vec<u8>,
+    pub(crate) charges: Vec<i16>,
+}
+
+impl KekuleStructure {
+    /// Returns the bonds, as pairs of 0-based atom indices with the smaller
+    /// index first, sorted, with their orders.
+    #[must_use]
+    pub fn bonds(&self) -> &[((u16, u16), BondOrder)] {
+        &self.bonds
+    }
+
+    /// Returns the order of the bond between atoms `a` and `b`, or `None`
+    /// when they are not bonded.
+    #[must_use]
+    pub fn bond_order(&self, a: u16, b: u16) -> Option<BondOrder> {
+        let atoms = (a.min(b), a.max(b));
+        self.bonds
+            .binary_search_by_key(&atoms, |&(atoms, _)| atoms)
+            .ok()
+            .map(|position| self.bonds[position].1)
+    }
+
+    /// Returns the number of hydrogens of each atom, mobile ones included,
+    /// indexed by 0-based atom index.
+    #[must_use]
+    pub fn hydrogens(&self) -> &[u8] {
+        &self.hydrogens
+    }
+
+    /// Returns the formal charge of each atom, indexed by 0-based atom index.
+    #[must_use]
+    pub fn charges(&self) -> &[i16] {
+        &self.charges
+    }
+}
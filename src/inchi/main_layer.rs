@@ -1,14 +1,26 @@
 //! Module for the main layer of an InChI.
 
 mod atom_connection_layer;
-pub(crate) use atom_connection_layer::{AtomConnectionLayer, MolecularGraph};
+pub(crate) use atom_connection_layer::AtomConnectionLayer;
+pub use atom_connection_layer::MolecularGraph;
 mod hydrogen_layer;
-use core::str::FromStr;
+use core::{
+    fmt::{self, Display},
+    str::FromStr,
+};
 
-pub(crate) use hydrogen_layer::{HydrogenComponent, HydrogensSubLayer, MobileHydrogenGroup};
+pub(crate) use hydrogen_layer::HydrogensSubLayer;
+pub use hydrogen_layer::{HydrogenComponent, MobileHydrogenGroup};
 use molecular_formulas::InChIFormula;
 
-use crate::traits::parse::{ConsumeStr, PrefixFromStrWithContext};
+use crate::{
+    errors::LayerError,
+    impls::{display::write_components, main_layer::atom_connection_layer::connection_table},
+    traits::{
+        parse::{ConsumeStr, PrefixFromStrWithContext},
+        prefix::Prefix,
+    },
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// The main layer of an InChI.
@@ -22,32 +34,50 @@ impl MainLayer {
     pub(crate) fn chemical_formula(&self) -> &InChIFormula {
         &self.chemical_formula
     }
+
+    pub(crate) fn atom_connections(&self) -> Option<&AtomConnectionLayer<u16>> {
+        self.atom_connections.as_ref()
+    }
+
+    pub(crate) fn hydrogens(&self) -> Option<&HydrogensSubLayer> {
+        self.hydrogens.as_ref()
+    }
 }
 
 impl ConsumeStr for MainLayer {
     type Idx = u16;
-    fn consume_str(input: &str) -> Result<(Self, &str), crate::errors::Error<Self::Idx>> {
+    fn consume_str(input: &mut &str) -> Result<Self, LayerError<Self::Idx>> {
         // Then we parse the molecular formula layer
         // we strip everything until the next '/'
-        let (chemical_formula_layer, mut layer_remainder) =
+        let (chemical_formula_layer, layer_remainder) =
             input.split_once('/').unwrap_or((input, ""));
 
         // Then we parse the molecular formula layer
         let chemical_formula = InChIFormula::from_str(chemical_formula_layer)?;
+        *input = layer_remainder;
 
-        let atom_connection_layer =
-            AtomConnectionLayer::try_build_layer(&mut layer_remainder, &chemical_formula)?;
+        let atom_connection_layer = AtomConnectionLayer::try_build_layer(input, &chemical_formula)?;
 
-        let hydrogen_layer =
-            HydrogensSubLayer::try_build_layer(&mut layer_remainder, &chemical_formula)?;
+        let hydrogen_layer = HydrogensSubLayer::try_build_layer(input, &chemical_formula)?;
+
+        Ok(MainLayer {
+            chemical_formula,
+            atom_connections: atom_connection_layer,
+            hydrogens: hydrogen_layer,
+        })
+    }
+}
 
-        Ok((
-            MainLayer {
-                chemical_formula,
-                atom_connections: atom_connection_layer,
-                hydrogens: hydrogen_layer,
-            },
-            layer_remainder,
-        ))
+impl Display for MainLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.chemical_formula)?;
+        if let Some(atom_connections) = &self.atom_connections {
+            write!(f, "/{}", AtomConnectionLayer::<u16>::PREFIX)?;
+            write_components(f, atom_connections.iter().map(connection_table))?;
+        }
+        if let Some(hydrogens) = &self.hydrogens {
+            write!(f, "/{hydrogens}")?;
+        }
+        Ok(())
     }
 }
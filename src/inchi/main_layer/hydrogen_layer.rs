@@ -6,7 +6,7 @@ use crate::traits::prefix::Prefix;
 
 /// A group of mobile (tautomeric) hydrogens delocalized over `atoms`.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct MobileHydrogenGroup<Idx = u16> {
+pub struct MobileHydrogenGroup<Idx = u16> {
     /// Number of mobile hydrogens in this group.
     pub(crate) count: u8,
     /// Number of mobile negative charges (from `(HN-M,...)`). 0 means neutral.
@@ -15,6 +15,26 @@ pub(crate) struct MobileHydrogenGroup<Idx = u16> {
     pub(crate) atoms: Vec<Idx>,
 }
 
+impl<Idx> MobileHydrogenGroup<Idx> {
+    /// Returns the number of mobile hydrogens in this group.
+    #[must_use]
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+
+    /// Returns the number of mobile negative charges in this group.
+    #[must_use]
+    pub fn negative_count(&self) -> u8 {
+        self.negative_count
+    }
+
+    /// Returns the 0-based indices of the atoms sharing the mobile hydrogens.
+    #[must_use]
+    pub fn atoms(&self) -> &[Idx] {
+        &self.atoms
+    }
+}
+
 /// Hydrogen data for one molecular component.
 ///
 /// `fixed_h[i]` is indexed by **0-based atom index** within this component.
@@ -25,7 +45,7 @@ pub(crate) struct MobileHydrogenGroup<Idx = u16> {
 /// The value type is `u8` because it is a **hydrogen count** (always 0–8),
 /// not an atom index.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct HydrogenComponent<Idx = u16> {
+pub struct HydrogenComponent<Idx = u16> {
     /// Per-atom fixed hydrogen counts, indexed by 0-based atom index.
     /// `0` means no fixed hydrogens.
     pub(crate) fixed_h: Vec<u8>,
@@ -33,6 +53,21 @@ pub(crate) struct HydrogenComponent<Idx = u16> {
     pub(crate) mobile_groups: Vec<MobileHydrogenGroup<Idx>>,
 }
 
+impl<Idx> HydrogenComponent<Idx> {
+    /// Returns the per-atom fixed hydrogen counts, indexed by 0-based atom
+    /// index.
+    #[must_use]
+    pub fn fixed_hydrogens(&self) -> &[u8] {
+        &self.fixed_h
+    }
+
+    /// Returns the mobile hydrogen groups of this component.
+    #[must_use]
+    pub fn mobile_groups(&self) -> &[MobileHydrogenGroup<Idx>] {
+        &self.mobile_groups
+    }
+}
+
 /// The parsed hydrogen sublayer: one `HydrogenComponent` per `;`-delimited
 /// fragment.
 #[derive(Debug, Clone, PartialEq, Eq)]
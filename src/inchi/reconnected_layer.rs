@@ -1,11 +1,31 @@
 //! Module for the reconnected layer of an InChI.
 
-use crate::traits::prefix::Prefix;
+use alloc::boxed::Box;
+
+use crate::{
+    inchi::InChI,
+    traits::prefix::Prefix,
+    version::{StandardVersion1_07_4, Version},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 /// The reconnected layer of an InChI.
-pub struct ReconnectedLayer;
+///
+/// The layer starts with `/r` and describes the structure with the bonds to
+/// metal atoms restored. It is a complete nested InChI, with its own formula,
+/// connections, hydrogens, charges, stereochemistry and isotopes.
+pub struct ReconnectedLayer<V: Version = StandardVersion1_07_4> {
+    pub(crate) inchi: Box<InChI<V>>,
+}
+
+impl<V: Version> ReconnectedLayer<V> {
+    /// Returns the reconnected structure as a nested InChI.
+    #[must_use]
+    pub fn inchi(&self) -> &InChI<V> {
+        &self.inchi
+    }
+}
 
-impl Prefix for ReconnectedLayer {
+impl<V: Version> Prefix for ReconnectedLayer<V> {
     const PREFIX: char = 'r';
 }
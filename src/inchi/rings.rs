@@ -0,0 +1,129 @@
+//! Module for the rings and ring systems of a molecular graph.
+
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// How the rings of a ring system are joined.
+///
+/// The rings of a system are compared two by two: rings sharing a single
+/// atom are spiro-joined, rings sharing two bonded atoms are fused, and
+/// rings sharing more atoms are bridged. A system takes the strongest kind
+/// found among its pairs, bridged before fused before spiro, so that a
+/// naphthalene spiro-joined to a cyclohexane is fused.
+pub enum RingSystemKind {
+    /// A single ring.
+    Isolated,
+    /// Rings sharing bonds, as in naphthalene.
+    Fused,
+    /// Rings sharing single atoms, as in spiro\[4.5\]decane.
+    Spiro,
+    /// Rings sharing more than a bond, as in norbornane or adamantane.
+    Bridged,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A ring system: the rings connected through shared atoms.
+pub struct RingSystem {
+    pub(crate) rings: Vec<usize>,
+    pub(crate) atoms: Vec<u16>,
+    pub(crate) kind: RingSystemKind,
+}
+
+impl RingSystem {
+    /// Returns the indices of the rings of the system in [`Rings::rings`],
+    /// in increasing order.
+    #[must_use]
+    pub fn rings(&self) -> &[usize] {
+        &self.rings
+    }
+
+    /// Returns the 0-based indices of the atoms of the system, in increasing
+    /// order.
+    #[must_use]
+    pub fn atoms(&self) -> &[u16] {
+        &self.atoms
+    }
+
+    /// Returns how the rings of the system are joined.
+    #[must_use]
+    pub fn kind(&self) -> RingSystemKind {
+        self.kind
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The rings of a molecular graph: its smallest set of smallest rings, the
+/// rings each atom and bond belongs to, and the ring systems they form.
+///
+/// The smallest set of smallest rings is a minimum cycle basis: as many
+/// rings as the bonds of the graph minus its atoms plus its connected
+/// parts, as small as possible. It is not unique for some cages, such as
+/// cubane, which has six faces but five rings in the set.
+pub struct Rings {
+    pub(crate) cycles: Vec<Vec<u16>>,
+    pub(crate) atom_memberships: Vec<Vec<usize>>,
+    pub(crate) bond_memberships: Vec<((u16, u16), Vec<usize>)>,
+    pub(crate) systems: Vec<RingSystem>,
+}
+
+impl Rings {
+    /// Returns the smallest set of smallest rings, each listing its atoms in
+    /// order around it from its smallest atom, sorted by size then atoms.
+    #[must_use]
+    pub fn rings(&self) -> &[Vec<u16>] {
+        &self.cycles
+    }
+
+    /// Returns the number of rings in the smallest set of smallest rings.
+    #[must_use]
+    pub fn number_of_rings(&self) -> usize {
+        self.cycles.len()
+    }
+
+    /// Returns the indices in [`Rings::rings`] of the rings atom `atom`, a
+    /// 0-based index, belongs to, in increasing order.
+    #[must_use]
+    pub fn atom_rings(&self, atom: u16) -> &[usize] {
+        self.atom_memberships.get(usize::from(atom)).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns whether atom `atom` belongs to a ring.
+    #[must_use]
+    pub fn is_ring_atom(&self, atom: u16) -> bool {
+        !self.atom_rings(atom).is_empty()
+    }
+
+    /// Returns the size of the smallest ring atom `atom` belongs to.
+    #[must_use]
+    pub fn atom_ring_size(&self, atom: u16) -> Option<usize> {
+        self.atom_rings(atom).first().map(|&ring| self.cycles[ring].len())
+    }
+
+    /// Returns the indices in [`Rings::rings`] of the rings the bond between
+    /// atoms `a` and `b` belongs to, in increasing order.
+    #[must_use]
+    pub fn bond_rings(&self, a: u16, b: u16) -> &[usize] {
+        self.bond_memberships
+            .binary_search_by_key(&(a.min(b), a.max(b)), |&(bond, _)| bond)
+            .map_or(&[], |position| self.bond_memberships[position].1.as_slice())
+    }
+
+    /// Returns whether the bond between atoms `a` and `b` belongs to a ring.
+    #[must_use]
+    pub fn is_ring_bond(&self, a: u16, b: u16) -> bool {
+        !self.bond_rings(a, b).is_empty()
+    }
+
+    /// Returns the size of the smallest ring the bond between atoms `a` and
+    /// `b` belongs to.
+    #[must_use]
+    pub fn bond_ring_size(&self, a: u16, b: u16) -> Option<usize> {
+        self.bond_rings(a, b).first().map(|&ring| self.cycles[ring].len())
+    }
+
+    /// Returns the ring systems, ordered by their smallest ring.
+    #[must_use]
+    pub fn systems(&self) -> &[RingSystem] {
+        &self.systems
+    }
+}
@@ -0,0 +1,82 @@
+//! Module for the InChIKey, the hashed fixed-length form of an InChI.
+
+use alloc::string::String;
+
+use crate::{inchi::InChI, version::Version};
+
+/// Length of an InChIKey, hyphens included.
+pub(crate) const INCHI_KEY_LENGTH: usize = 27;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+/// A validated InChIKey, such as `LFQSCWFLJHTTHZ-UHFFFAOYSA-N`.
+///
+/// The key is laid out as `XXXXXXXXXXXXXX-YYYYYYYYFV-P`: a 14-letter block
+/// hashing the skeleton, an 8-letter block hashing the stereochemistry and
+/// isotopes, the standard flag `F`, the version `V` and the protonation flag
+/// `P`.
+pub struct InChIKey {
+    pub(crate) key: String,
+}
+
+impl InChIKey {
+    /// Returns the 14-letter block hashing the skeleton (main layer and
+    /// charge).
+    #[must_use]
+    pub fn skeleton(&self) -> &str {
+        &self.key[..14]
+    }
+
+    /// Returns the 8-letter block hashing the remaining layers
+    /// (stereochemistry, isotopes, fixed hydrogens and reconnected metals).
+    #[must_use]
+    pub fn stereo_isotope(&self) -> &str {
+        &self.key[15..23]
+    }
+
+    /// Returns whether the key was generated from a standard InChI (flag
+    /// `S`, as opposed to `N`).
+    #[must_use]
+    pub fn is_standard(&self) -> bool {
+        self.key.as_bytes()[23] == b'S'
+    }
+
+    /// Returns the InChI version of the key (`A` is version 1).
+    #[must_use]
+    pub fn version(&self) -> u8 {
+        self.key.as_bytes()[24] - b'A' + 1
+    }
+
+    /// Returns the number of protons added (positive) or removed (negative)
+    /// by the `/p` layer.
+    ///
+    /// Returns `None` when the key flags more than 12 protons (`A`).
+    #[must_use]
+    pub fn protonation(&self) -> Option<i8> {
+        match self.key.as_bytes()[26] {
+            b'A' => None,
+            flag => i8::try_from(i16::from(flag) - i16::from(b'N')).ok(),
+        }
+    }
+
+    /// Returns the key as a string slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns whether the key is the one generated from `inchi`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use inchi_parser::{inchi::InChI, inchi_key::InChIKey};
+    ///
+    /// let inchi: InChI = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3".parse().unwrap();
+    /// let key: InChIKey = "LFQSCWFLJHTTHZ-UHFFFAOYSA-N".parse().unwrap();
+    /// assert!(key.matches(&inchi));
+    /// ```
+    #[must_use]
+    pub fn matches<V: Version>(&self, inchi: &InChI<V>) -> bool {
+        inchi.inchi_key() == *self
+    }
+}
@@ -2,8 +2,12 @@
 #![no_std]
 extern crate alloc;
 mod constants;
+pub mod diagnostics;
 pub mod errors;
 mod impls;
 pub mod inchi;
+pub mod inchi_key;
+pub mod parse_options;
 mod traits;
-mod version;
+pub mod validation;
+pub mod version;
@@ -0,0 +1,95 @@
+//! Options for lenient parsing, and the warnings describing the fixes it
+//! applies.
+//!
+//! The strict [`FromStr`](core::str::FromStr) implementation of
+//! [`InChI`](crate::inchi::InChI) stays the default. `InChI::parse_with`
+//! accepts the small defects of text copied from spreadsheets and papers,
+//! and reports every fix it made.
+
+use alloc::string::String;
+use core::ops::Range;
+
+/// Options of `InChI::parse_with`, selecting which defects of the input are
+/// fixed instead of rejected.
+///
+/// The default options are strict, so that parsing with them behaves like
+/// [`FromStr`](core::str::FromStr).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    pub(crate) trim_whitespace: bool,
+    pub(crate) allow_missing_prefix: bool,
+    pub(crate) allow_trailing_slash: bool,
+    pub(crate) skip_unknown_layers: bool,
+}
+
+impl ParseOptions {
+    /// Returns the strict options, which fix nothing.
+    #[must_use]
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    /// Returns the options fixing every supported defect.
+    #[must_use]
+    pub fn lenient() -> Self {
+        Self {
+            trim_whitespace: true,
+            allow_missing_prefix: true,
+            allow_trailing_slash: true,
+            skip_unknown_layers: true,
+        }
+    }
+
+    /// Sets whether surrounding whitespace and newlines are removed.
+    #[must_use]
+    pub fn with_trim_whitespace(mut self, trim_whitespace: bool) -> Self {
+        self.trim_whitespace = trim_whitespace;
+        self
+    }
+
+    /// Sets whether an input without the `InChI=` prefix is accepted.
+    #[must_use]
+    pub fn with_allow_missing_prefix(mut self, allow_missing_prefix: bool) -> Self {
+        self.allow_missing_prefix = allow_missing_prefix;
+        self
+    }
+
+    /// Sets whether a trailing `/` is removed.
+    #[must_use]
+    pub fn with_allow_trailing_slash(mut self, allow_trailing_slash: bool) -> Self {
+        self.allow_trailing_slash = allow_trailing_slash;
+        self
+    }
+
+    /// Sets whether layers without a known prefix are skipped instead of
+    /// rejected.
+    #[must_use]
+    pub fn with_skip_unknown_layers(mut self, skip_unknown_layers: bool) -> Self {
+        self.skip_unknown_layers = skip_unknown_layers;
+        self
+    }
+}
+
+/// A fix applied to the input by lenient parsing.
+///
+/// Spans are byte ranges of the original input.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseWarning {
+    /// Whitespace or newlines surrounding the InChI were removed.
+    #[error("Removed the whitespace surrounding the InChI")]
+    TrimmedWhitespace,
+    /// The missing `InChI=` prefix was added.
+    #[error("Added the missing 'InChI=' prefix")]
+    AddedPrefix,
+    /// A trailing `/` was removed.
+    #[error("Removed the trailing '/' at byte {0}")]
+    RemovedTrailingSlash(usize),
+    /// A layer without a known prefix was skipped.
+    #[error("Skipped the unknown layer '{segment}' at bytes {}..{}", span.start, span.end)]
+    SkippedUnknownLayer {
+        /// The skipped segment, without its `/` separator
+        segment: String,
+        /// The byte range of the segment in the input
+        span: Range<usize>,
+    },
+}
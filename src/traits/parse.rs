@@ -1,6 +1,9 @@
-use core::str::Chars;
+use core::{iter::Peekable, str::Chars};
 
-use crate::traits::{IndexLike, prefix::Prefix};
+use crate::{
+    errors::LayerError,
+    traits::{IndexLike, prefix::Prefix},
+};
 
 /// Trait for parsing InChI layers.
 pub trait FromStrWithContext: Sized {
@@ -8,31 +11,54 @@ pub trait FromStrWithContext: Sized {
     type Context<'a>;
     type Input<'a>: FromInChIStr<'a>;
     /// Given a string that matches a specific layer, the function tries to
-    /// parse that layer and returns the struct for that layer or an error.
+    /// parse that layer and returns the struct for that layer or an error,
+    /// with the bytes of the input at fault when the layer can point at them.
+    fn from_str_located(
+        input: Self::Input<'_>,
+        context: Self::Context<'_>,
+    ) -> Result<Self, LayerError<Self::Idx>>;
+
+    /// Parses the layer like [`FromStrWithContext::from_str_located`],
+    /// without locating errors, for the unit tests of the layers.
+    #[cfg(test)]
     fn from_str_with_context(
         input: Self::Input<'_>,
         context: Self::Context<'_>,
-    ) -> Result<Self, crate::errors::Error<Self::Idx>>;
+    ) -> Result<Self, crate::errors::Error<Self::Idx>> {
+        Self::from_str_located(input, context).map_err(LayerError::into_kind)
+    }
+}
+
+/// Returns how many bytes of `input` the iterator `chars` over it has
+/// consumed.
+pub(crate) fn consumed(input: &str, chars: &Peekable<Chars<'_>>) -> usize {
+    input.len() - chars.clone().map(char::len_utf8).sum::<usize>()
 }
 
 pub trait ConsumeStr: Sized {
     type Idx: IndexLike;
     /// Takes a string as input and checks if it matches the layer.
     /// If it does, then the begining of the string that matches a layer is
-    /// consumed. If it does then the string return itself.
-    fn consume_str(input: &str) -> Result<(Self, &str), crate::errors::Error<Self::Idx>>;
+    /// consumed. On error, the input is left at the segment that failed,
+    /// and the fault of the error is relative to it.
+    fn consume_str(input: &mut &str) -> Result<Self, LayerError<Self::Idx>>;
 }
 
 pub trait PrefixFromStrWithContext: Prefix + FromStrWithContext {
+    /// Parses the layer at the start of the input if it starts with the
+    /// layer prefix, consuming it. On error, the input is left at the
+    /// segment that failed, and the fault of the error is relative to it.
     fn try_build_layer(
         input: &mut &str,
         context: Self::Context<'_>,
-    ) -> Result<Option<Self>, crate::errors::Error<Self::Idx>> {
+    ) -> Result<Option<Self>, LayerError<Self::Idx>> {
         let layer = if input.starts_with(Self::PREFIX) {
             let (layer_string, layer_remainder_string) =
                 input.split_once('/').unwrap_or((input, ""));
+            let layer =
+                Self::from_str_located(FromInChIStr::from_inchi_str(layer_string), context)?;
             *input = layer_remainder_string;
-            Some(Self::from_str_with_context(FromInChIStr::from_inchi_str(layer_string), context)?)
+            Some(layer)
         } else {
             None
         };
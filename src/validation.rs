@@ -0,0 +1,22 @@
+//! Optional validation passes over a parsed InChI.
+//!
+//! Parsing only checks that an InChI is well formed. The checks in this
+//! module cross-reference the layers with one another to catch InChIs that
+//! are syntactically valid but chemically implausible. Each check is run on
+//! demand through a method of [`InChI`](crate::inchi::InChI) and returns the
+//! issues it found.
+//!
+//! Atom indices stored in the issues are 0-based, like in the rest of the
+//! API, while their messages use the 1-based numbering of the InChI string.
+
+pub mod charge;
+pub mod conformance;
+pub mod hydrogens;
+pub mod stereo;
+pub mod valence;
+
+pub use charge::ChargeIssue;
+pub use conformance::ConformanceIssue;
+pub use hydrogens::HydrogenImbalance;
+pub use stereo::StereoIssue;
+pub use valence::ValenceIssue;
@@ -0,0 +1,28 @@
+//! Issues found by checking the charge and proton layers against the
+//! structure.
+
+/// A charge or proton layer inconsistent with the rest of the InChI.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChargeIssue {
+    /// A component has more mobile negative charges than the charge layer
+    /// gives it.
+    #[error(
+        "Component {component} has {negative_count} mobile negative charges but a charge of {charge}"
+    )]
+    MobileChargeExceedsCharge {
+        /// The 0-based index of the component
+        component: usize,
+        /// The number of negative charges of its mobile hydrogen groups
+        negative_count: usize,
+        /// The charge of the component in the `/q` layer
+        charge: i16,
+    },
+    /// The proton layer removes more protons than the InChI has hydrogens.
+    #[error("The proton layer removes {} protons, but there are only {hydrogens} hydrogens", protons.unsigned_abs())]
+    ProtonsExceedHydrogens {
+        /// The proton balance of the `/p` layer
+        protons: i16,
+        /// The number of hydrogens in the formula
+        hydrogens: usize,
+    },
+}
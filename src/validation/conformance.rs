@@ -0,0 +1,58 @@
+//! Issues found by checking an InChI against the rules of standard InChI.
+
+/// A feature of an InChI that standard InChI never produces.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConformanceIssue {
+    /// The InChI has a fixed hydrogen `/f` layer.
+    #[error("Standard InChI has no fixed hydrogen layer (/f)")]
+    FixedHydrogenLayer,
+    /// The InChI has a reconnected `/r` layer.
+    #[error("Standard InChI has no reconnected layer (/r)")]
+    ReconnectedLayer,
+    /// The stereo type is relative (`/s2`) or racemic (`/s3`).
+    #[error(
+        "Standard InChI only describes absolute stereo (/s1), found /s{value} in the {} stereo layer",
+        if *isotopic { "isotopic" } else { "main" }
+    )]
+    NonAbsoluteStereo {
+        /// Whether the flag belongs to the isotopic stereo sublayers
+        isotopic: bool,
+        /// The stereo type value
+        value: u8,
+    },
+    /// An `/m` or `/s` flag appears without the other.
+    #[error(
+        "Standard InChI writes /m and /s together, found only /{} in the {} stereo layer",
+        if *inversion { 'm' } else { 's' },
+        if *isotopic { "isotopic" } else { "main" }
+    )]
+    UnpairedStereoFlag {
+        /// Whether the flag belongs to the isotopic stereo sublayers
+        isotopic: bool,
+        /// Whether the flag present is `/m`, otherwise it is `/s`
+        inversion: bool,
+    },
+    /// The `/m` and `/s` flags appear without a tetrahedral `/t` sublayer.
+    #[error(
+        "Standard InChI only writes /m and /s after a /t sublayer, which the {} stereo layer lacks",
+        if *isotopic { "isotopic" } else { "main" }
+    )]
+    StereoFlagsWithoutTetrahedral {
+        /// Whether the flags belong to the isotopic stereo sublayers
+        isotopic: bool,
+    },
+}
+
+impl ConformanceIssue {
+    /// Returns a stable identifier of the rule the issue breaks.
+    #[must_use]
+    pub fn rule(&self) -> &'static str {
+        match self {
+            Self::FixedHydrogenLayer => "no_fixed_hydrogen_layer",
+            Self::ReconnectedLayer => "no_reconnected_layer",
+            Self::NonAbsoluteStereo { .. } => "absolute_stereo_only",
+            Self::UnpairedStereoFlag { .. } => "paired_stereo_flags",
+            Self::StereoFlagsWithoutTetrahedral { .. } => "stereo_flags_need_tetrahedral",
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Issues found by checking the hydrogen layer against the formula.
+
+/// A component whose fixed and mobile hydrogens do not add up to the
+/// hydrogen count of its formula.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error(
+    "Component {component} has {found} hydrogens in the hydrogen layer but {expected} in its formula"
+)]
+pub struct HydrogenImbalance {
+    pub(crate) component: usize,
+    pub(crate) expected: usize,
+    pub(crate) found: usize,
+}
+
+impl HydrogenImbalance {
+    /// Returns the 0-based index of the component.
+    #[must_use]
+    pub fn component(&self) -> usize {
+        self.component
+    }
+
+    /// Returns the number of hydrogens in the formula of the component.
+    #[must_use]
+    pub fn expected(&self) -> usize {
+        self.expected
+    }
+
+    /// Returns the number of fixed and mobile hydrogens of the component.
+    #[must_use]
+    pub fn found(&self) -> usize {
+        self.found
+    }
+
+    /// Returns how many hydrogens the hydrogen layer has in excess of the
+    /// formula, negative when it lacks some.
+    #[must_use]
+    pub fn difference(&self) -> isize {
+        self.found.cast_signed() - self.expected.cast_signed()
+    }
+}
@@ -0,0 +1,56 @@
+//! Issues found by cross-checking stereo descriptors with the connection
+//! graph.
+
+/// A stereo descriptor that cannot describe a real stereo element of the
+/// connection graph.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StereoIssue {
+    /// A double bond descriptor joins two atoms that are neither bonded nor
+    /// the two ends of a cumulene chain.
+    #[error(
+        "Double bond stereo {}-{} in component {component} joins atoms that are neither bonded nor the ends of a cumulene",
+        atoms.0 + 1,
+        atoms.1 + 1
+    )]
+    UnbondedDoubleBond {
+        /// The 0-based index of the component
+        component: usize,
+        /// The 0-based atom indices of the descriptor
+        atoms: (u16, u16),
+    },
+    /// A tetrahedral centre has fewer than three neighbours and hydrogens.
+    #[error(
+        "Tetrahedral centre {} in component {component} has only {neighbours} neighbours and {hydrogens} hydrogens",
+        atom + 1
+    )]
+    TooFewSubstituents {
+        /// The 0-based index of the component
+        component: usize,
+        /// The 0-based index of the atom
+        atom: u16,
+        /// The number of bonded atoms
+        neighbours: usize,
+        /// The number of fixed hydrogens
+        hydrogens: u8,
+    },
+    /// A tetrahedral centre carries two or more identical hydrogens.
+    #[error("Tetrahedral centre {} in component {component} carries {hydrogens} hydrogens", atom + 1)]
+    TooManyHydrogens {
+        /// The 0-based index of the component
+        component: usize,
+        /// The 0-based index of the atom
+        atom: u16,
+        /// The number of fixed hydrogens
+        hydrogens: u8,
+    },
+    /// An allene centre, i.e. a tetrahedral descriptor on an atom with two
+    /// neighbours and no hydrogen, is not the middle of a cumulene chain
+    /// such as `C=C=C`.
+    #[error("Allene centre {} in component {component} is not the middle of a cumulene", atom + 1)]
+    MisplacedAlleneCentre {
+        /// The 0-based index of the component
+        component: usize,
+        /// The 0-based index of the atom
+        atom: u16,
+    },
+}
@@ -0,0 +1,57 @@
+//! Issues found by checking the atom valences against their elements.
+
+use elements_rs::Element;
+
+/// An atom with more bonded atoms and hydrogens than its element allows.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error(
+    "{element} atom {} in component {component} has {connections} connections and {hydrogens} hydrogens, more than its maximum valence of {max_valence}",
+    atom + 1
+)]
+pub struct ValenceIssue {
+    pub(crate) component: usize,
+    pub(crate) atom: u16,
+    pub(crate) element: Element,
+    pub(crate) connections: usize,
+    pub(crate) hydrogens: u8,
+    pub(crate) max_valence: usize,
+}
+
+impl ValenceIssue {
+    /// Returns the 0-based index of the component.
+    #[must_use]
+    pub fn component(&self) -> usize {
+        self.component
+    }
+
+    /// Returns the 0-based index of the atom.
+    #[must_use]
+    pub fn atom(&self) -> u16 {
+        self.atom
+    }
+
+    /// Returns the element of the atom.
+    #[must_use]
+    pub fn element(&self) -> Element {
+        self.element
+    }
+
+    /// Returns the number of bonded atoms.
+    #[must_use]
+    pub fn connections(&self) -> usize {
+        self.connections
+    }
+
+    /// Returns the number of fixed hydrogens.
+    #[must_use]
+    pub fn hydrogens(&self) -> u8 {
+        self.hydrogens
+    }
+
+    /// Returns the largest valence allowed for the atom, including the
+    /// allowance for the component charge.
+    #[must_use]
+    pub fn max_valence(&self) -> usize {
+        self.max_valence
+    }
+}
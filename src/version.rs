@@ -1,25 +1,36 @@
+//! Module defining the supported InChI versions.
+
 use core::fmt::Debug;
 
 /// Trait for the different InChI versions.
 pub trait Version: Default + Debug + PartialEq + Eq {
+    /// The InChI software version.
     const VERSION: &'static str;
+    /// The prefix following `InChI=`, such as `1S` for standard InChIs.
     const VERSION_PREFIX: &'static str;
+    /// Whether the version describes standard InChIs.
+    const IS_STANDARD: bool;
 }
 
-/// InChI version 1.07.4
+/// Standard InChI version 1.07.4 (`InChI=1S/`).
 #[derive(Default, Debug, PartialEq, Eq)]
 pub struct StandardVersion1_07_4;
 
-#[allow(dead_code)]
+/// Non-standard InChI version 1.07.4 (`InChI=1/`).
+///
+/// Non-standard InChIs may carry layers that standard ones never have, such
+/// as the fixed hydrogen `/f` and reconnected `/r` layers.
 #[derive(Default, Debug, PartialEq, Eq)]
 pub struct Version1_07_4;
 
 impl Version for StandardVersion1_07_4 {
     const VERSION: &'static str = "1.07.4";
     const VERSION_PREFIX: &'static str = "1S";
+    const IS_STANDARD: bool = true;
 }
 
 impl Version for Version1_07_4 {
     const VERSION: &'static str = "1.07.4";
     const VERSION_PREFIX: &'static str = "1";
+    const IS_STANDARD: bool = false;
 }
@@ -1,6 +1,11 @@
 //! InChI parsing integration tests.
 
-use inchi_parser::inchi::{InChI, stereochemistry_layer::StereoParity};
+use inchi_parser::{
+    errors::{ComponentError, KekulizationError},
+    inchi::{AnyInChI, InChI, stereochemistry_layer::StereoParity},
+    inchi_key::InChIKey,
+    version::Version1_07_4,
+};
 
 const INCHI_TEST: &[&str] = &[
     "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3",
@@ -126,6 +131,7 @@ const INCHI_TEST: &[&str] = &[
     "InChI=1S/p+5",
     "InChI=1S/p+2",
     "InChI=1S/C2N2/c3-1-2-4",
+    "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/i2D/t2-/m1/s1",
 ];
 
 #[test]
@@ -137,6 +143,14 @@ fn test_inchi_parsing() {
     }
 }
 
+#[test]
+fn test_inchi_display_round_trip() {
+    for (i, &inchi_str) in INCHI_TEST.iter().enumerate() {
+        let inchi: InChI = inchi_str.parse().unwrap();
+        assert_eq!(inchi.to_string(), inchi_str, "{i}) Round trip changed the InChI");
+    }
+}
+
 #[test]
 fn test_charge_layer_oxide_anion() {
     // InChI=1S/O/q-2
@@ -258,6 +272,18 @@ fn test_isotope_atom_level_deuterium() {
     assert_eq!(atom.hydrogen_isotopes()[0].isotope(), elements_rs::isotopes::HydrogenIsotope::D);
 }
 
+#[test]
+fn test_isotope_stereo_sublayers() {
+    // (S)-ethanol-1-d: atom 2 is only a stereocentre because of deuterium.
+    let inchi: InChI = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/i2D/t2-/m1/s1".parse().unwrap();
+    assert!(inchi.stereochemistry().is_none());
+    let stereo = inchi.isotope().unwrap().stereochemistry().expect("isotopic stereo");
+    let center = &stereo.tetrahedral().unwrap().components()[0][0];
+    assert_eq!(center.atom(), 1);
+    assert_eq!(center.parity(), StereoParity::Minus);
+    assert_eq!(stereo.stereo_info().unwrap().value(), 1);
+}
+
 // --- Stereochemistry layer integration tests ---
 
 #[test]
@@ -632,3 +658,309 @@ fn test_normal_inchi_has_main_layer() {
     let inchi: InChI = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3".parse().unwrap();
     assert!(inchi.main_layer().is_some());
 }
+
+// --- Per-component view ---
+
+#[test]
+fn test_components_expand_repetition_prefix() {
+    let inchi: InChI = "InChI=1S/C15H32N2.2C4H6O6/c1-16(12-6-7-13-16)10-4-3-5-11-17(2)14-8-9-15-17;2*5-1(3(7)8)2(6)4(9)10/h3-15H2,1-2H3;2*1-2,5-6H,(H,7,8)(H,9,10)/q+2;;/p-2/t;2*1-,2-/m.11/s1".parse().unwrap();
+    let components: Vec<_> = inchi.components().collect();
+    assert_eq!(components.len(), 3);
+
+    assert_eq!(components[0].formula().to_string(), "C15H32N2");
+    assert_eq!(components[0].charge(), 2);
+    assert!(components[0].tetrahedral_stereo().is_empty());
+    assert!(components[0].mobile_hydrogens().is_empty());
+
+    for component in &components[1..] {
+        assert_eq!(component.formula().to_string(), "C4H6O6");
+        assert_eq!(component.charge(), 0);
+        assert_eq!(component.tetrahedral_stereo().len(), 2);
+        assert_eq!(component.fixed_hydrogens(), &[1, 1, 0, 0, 1, 1, 0, 0, 0, 0]);
+        assert_eq!(component.mobile_hydrogens().len(), 2);
+        assert_eq!(component.mobile_hydrogens()[0].count(), 1);
+        assert_eq!(component.mobile_hydrogens()[0].atoms(), &[6, 7]);
+    }
+    assert_eq!(components[2].index(), 2);
+}
+
+#[test]
+fn test_components_without_optional_layers() {
+    let inchi: InChI = "InChI=1S/ClH.Na/h1H;/q;+1/p-1".parse().unwrap();
+    let components: Vec<_> = inchi.components().collect();
+    assert_eq!(components.len(), 2);
+    assert!(components[0].graph().is_none());
+    assert_eq!(components[0].fixed_hydrogens(), &[1]);
+    assert_eq!(components[0].charge(), 0);
+    assert_eq!(components[1].fixed_hydrogens(), &[0]);
+    assert_eq!(components[1].charge(), 1);
+    assert!(components[1].isotope().is_none());
+}
+
+#[test]
+fn test_components_of_proton_only_inchi() {
+    let inchi: InChI = "InChI=1S/p+1".parse().unwrap();
+    assert_eq!(inchi.components().count(), 0);
+}
+
+// --- Non-standard InChIs ---
+
+const NON_STANDARD_INCHI_TEST: &[&str] = &[
+    "InChI=1/C2H6O/c1-2-3/h3H,2H2,1H3",
+    "InChI=1/C2H4O2/c1-2(3)4/h1H3,(H,3,4)/f/h3H",
+    "InChI=1/C2H4O2.Na/c1-2(3)4;/h1H3,(H,3,4);/q;+1/p-1/fC2H3O2.Na/q-1;m/rC2H3NaO2/c1-2(4)5-3/h1H3",
+    "InChI=1/C2H4O2.Na/c1-2(3)4;/h1H3,(H,3,4);/q;+1/p-1/fC2H3O2.Na/q-1;m",
+    "InChI=1/2C2H4O2.Ca/c2*1-2(3)4;/h2*1H3,(H,3,4);/q;;+2/p-2/f2C2H3O2.Ca/q2*-1;m",
+];
+
+#[test]
+fn test_non_standard_inchi_round_trip() {
+    for &inchi_str in NON_STANDARD_INCHI_TEST {
+        let inchi: InChI<Version1_07_4> = inchi_str.parse().unwrap();
+        assert!(!inchi.is_standard());
+        assert_eq!(inchi.to_string(), inchi_str);
+    }
+}
+
+#[test]
+fn test_non_standard_inchi_extra_layers() {
+    let inchi: InChI<Version1_07_4> = NON_STANDARD_INCHI_TEST[2].parse().unwrap();
+    let fixed_hydrogen = inchi.fixed_hydrogen().expect("fixed-H layer should be present");
+    assert_eq!(fixed_hydrogen.chemical_formula().unwrap().to_string(), "C2H3O2.Na");
+    // The sodium keeps the charge of the main layer, written `m`.
+    assert_eq!(fixed_hydrogen.charges(), Some(&[Some(-1), None][..]));
+
+    assert_eq!(inchi.components().count(), 2);
+    let reconnected: Vec<_> = inchi.reconnected_components().collect();
+    assert_eq!(reconnected.len(), 1);
+    assert_eq!(reconnected[0].formula().to_string(), "C2H3NaO2");
+
+    let calcium_acetate: InChI<Version1_07_4> = NON_STANDARD_INCHI_TEST[4].parse().unwrap();
+    let fixed_hydrogen = calcium_acetate.fixed_hydrogen().unwrap();
+    assert_eq!(fixed_hydrogen.charges(), Some(&[Some(-1), Some(-1), None][..]));
+}
+
+#[test]
+fn test_any_inchi_detects_version() {
+    let standard: AnyInChI = INCHI_TEST[0].parse().unwrap();
+    assert!(standard.is_standard());
+    assert!(standard.as_standard().is_some());
+    assert_eq!(standard.to_string(), INCHI_TEST[0]);
+
+    for &inchi_str in NON_STANDARD_INCHI_TEST {
+        let non_standard: AnyInChI = inchi_str.parse().unwrap();
+        assert!(!non_standard.is_standard());
+        assert!(non_standard.as_non_standard().is_some());
+        assert_eq!(non_standard.to_string(), inchi_str);
+    }
+}
+
+// --- InChIKey ---
+
+const INCHI_KEY_TEST: &[(&str, &str)] = &[
+    ("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3", "LFQSCWFLJHTTHZ-UHFFFAOYSA-N"),
+    ("InChI=1S/H2O/h1H2", "XLYOFNOQVPJJNP-UHFFFAOYSA-N"),
+    ("InChI=1S/CH4/h1H4", "VNWKTOKETHGBQD-UHFFFAOYSA-N"),
+    ("InChI=1S/C6H6/c1-2-4-6-5-3-1/h1-6H", "UHOVQNZJYSORNB-UHFFFAOYSA-N"),
+    (
+        "InChI=1S/C8H10N4O2/c1-10-4-9-6-5(10)7(13)12(3)8(14)11(6)2/h4H,1-3H3",
+        "RYYVLZVUVIJVGH-UHFFFAOYSA-N",
+    ),
+    ("InChI=1S/C2H4O2/c1-2(3)4/h1H3,(H,3,4)", "QTBSBXVTEAMEQO-UHFFFAOYSA-N"),
+    ("InChI=1S/ClH.Na/h1H;/q;+1/p-1", "FAPWRFPIFSIZLT-UHFFFAOYSA-M"),
+    ("InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m0/s1", "QNAYBMKLOCPYGJ-REOHCLBHSA-N"),
+    (
+        "InChI=1S/C6H12O6/c7-1-2-3(8)4(9)5(10)6(11)12-2/h2-11H,1H2/t2-,3-,4+,5-,6?/m1/s1",
+        "WQZGKKKJIJFFOK-GASJEMHNSA-N",
+    ),
+    ("InChI=1S/H2O/h1H2/i/hD2", "XLYOFNOQVPJJNP-ZSJDYOACSA-N"),
+    ("InChI=1S/p+1", "GPRLSGONYQIRFK-UHFFFAOYSA-N"),
+];
+
+#[test]
+fn test_inchi_key() {
+    for &(inchi_str, inchi_key) in INCHI_KEY_TEST {
+        let inchi: InChI = inchi_str.parse().unwrap();
+        assert_eq!(inchi.inchi_key().as_str(), inchi_key, "{inchi_str}");
+        let key: InChIKey = inchi_key.parse().unwrap();
+        assert!(key.matches(&inchi), "{inchi_str}");
+    }
+}
+
+#[test]
+fn test_inchi_key_mismatch() {
+    // Pair every InChI with the key of the next one: none of them must match.
+    for (pair, next) in INCHI_KEY_TEST.iter().zip(INCHI_KEY_TEST.iter().cycle().skip(1)) {
+        let inchi: InChI = pair.0.parse().unwrap();
+        let key: InChIKey = next.1.parse().unwrap();
+        assert!(!key.matches(&inchi), "{} / {}", pair.0, next.1);
+    }
+}
+
+#[test]
+fn test_inchi_key_protonation() {
+    let inchi: InChI = "InChI=1S/ClH.Na/h1H;/q;+1/p-1".parse().unwrap();
+    let key = inchi.inchi_key();
+    assert_eq!(key.protonation(), Some(-1));
+    assert!(key.is_standard());
+    assert_eq!(key.version(), 1);
+
+    // The flag runs from `B` at −12 to `Z` at +12, and is `A` beyond.
+    for (proton, flag) in [("/p+12", 'Z'), ("/p-12", 'B'), ("/p+13", 'A'), ("/p-13", 'A')] {
+        let inchi: InChI = format!("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3{proton}").parse().unwrap();
+        assert!(inchi.inchi_key().as_str().ends_with(flag), "{proton}");
+    }
+}
+
+#[test]
+fn test_inchi_key_non_standard_flag() {
+    let inchi: InChI<Version1_07_4> = "InChI=1/C2H6O/c1-2-3/h3H,2H2,1H3".parse().unwrap();
+    let key = inchi.inchi_key();
+    assert_eq!(key.as_str(), "LFQSCWFLJHTTHZ-UHFFFAOYNA-N");
+    assert!(!key.is_standard());
+}
+
+#[test]
+fn test_real_inchis_pass_checks() {
+    for inchi_str in INCHI_TEST {
+        let inchi: InChI = inchi_str.parse().unwrap();
+        assert_eq!(inchi.check_stereochemistry(), [], "{inchi_str}");
+        assert_eq!(inchi.check_hydrogen_balance(), [], "{inchi_str}");
+        assert_eq!(inchi.check_valences(), [], "{inchi_str}");
+        assert_eq!(inchi.check_standard_conformance(), [], "{inchi_str}");
+        assert_eq!(<InChI>::validate(inchi_str), [], "{inchi_str}");
+
+        // Atomic oxygen is a radical, with no closed-shell structure.
+        if *inchi_str != "InChI=1S/O" {
+            for structure in inchi.kekulize() {
+                assert!(structure.is_ok(), "{inchi_str}: {structure:?}");
+            }
+        }
+
+        // Every component is connected, so its smallest set of smallest rings
+        // has one ring per bond beyond a spanning tree.
+        for component in inchi.components().filter(|component| component.graph().is_some()) {
+            let atoms = component.atoms().unwrap().count();
+            let bonds = component.atoms().unwrap().map(|atom| atom.degree()).sum::<usize>() / 2;
+            let rings = component.rings();
+            assert_eq!(rings.number_of_rings(), bonds + 1 - atoms, "{inchi_str}");
+            for ring in rings.rings() {
+                assert!(ring.iter().all(|&atom| rings.is_ring_atom(atom)), "{inchi_str}");
+            }
+        }
+    }
+    let oxygen: InChI = "InChI=1S/O".parse().unwrap();
+    assert_eq!(oxygen.kekulize(), [Err(KekulizationError::NoValidAssignment { component: 0 })]);
+}
+
+#[test]
+fn test_aromaticity() {
+    // Caffeine: the methyl carbons C1 to C3 and the oxygens stay out of both
+    // rings, whose nine atoms all share 6 π electrons each.
+    let caffeine: InChI =
+        "InChI=1S/C8H10N4O2/c1-10-4-9-6-5(10)7(13)12(3)8(14)11(6)2/h4H,1-3H3".parse().unwrap();
+    let aromaticity = caffeine.components().next().unwrap().aromaticity().unwrap();
+    assert_eq!(aromaticity.aromatic_atoms().collect::<Vec<_>>(), [3, 4, 5, 6, 7, 8, 9, 10, 11]);
+    assert_eq!(aromaticity.aromatic_rings().len(), 2);
+    assert!(aromaticity.is_aromatic_bond(4, 5));
+    assert!(!aromaticity.is_aromatic_bond(6, 12));
+}
+
+#[test]
+fn test_net_charge() {
+    let cases = [
+        ("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3", 0),
+        ("InChI=1S/C4H12N/c1-5(2,3)4/h1-4H3/q+1", 1),
+        ("InChI=1S/C2H4O2/c1-2(3)4/h1H3,(H,3,4)/p-1", -1),
+        ("InChI=1S/2ClH.Ca/h2*1H;/q;;+2/p-2", 0),
+        ("InChI=1S/p+1", 1),
+    ];
+    for (inchi_str, net_charge) in cases {
+        let inchi: InChI = inchi_str.parse().unwrap();
+        assert_eq!(inchi.net_charge(), net_charge, "{inchi_str}");
+        assert_eq!(inchi.check_charges(), [], "{inchi_str}");
+    }
+}
+
+#[test]
+fn test_atom_model() {
+    use elements_rs::Element;
+
+    // L-alanine: atoms 1-3 are carbons, 4 the nitrogen and 5-6 the oxygens.
+    let inchi: InChI =
+        "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m0/s1".parse().unwrap();
+    let component = inchi.components().next().unwrap();
+    let elements: Vec<Element> = component.atoms().unwrap().map(|atom| atom.element()).collect();
+    assert_eq!(elements, [Element::C, Element::C, Element::C, Element::N, Element::O, Element::O]);
+
+    let alpha_carbon = component.atom(1).unwrap();
+    assert_eq!(alpha_carbon.neighbours(), [0, 2, 3]);
+    assert_eq!(alpha_carbon.degree(), 3);
+    assert_eq!(alpha_carbon.hydrogens(), 1);
+    assert_eq!(alpha_carbon.mass_shift(), None);
+
+    let nitrogen = component.atom(3).unwrap();
+    assert_eq!(nitrogen.element(), Element::N);
+    assert_eq!(nitrogen.hydrogens(), 2);
+    assert!(component.atom(6).is_none());
+
+    // The metal of a salt is a component of its own, without bonds.
+    let inchi: InChI = "InChI=1S/ClH.Na/h1H;/q;+1/p-1".parse().unwrap();
+    let sodium = inchi.components().nth(1).unwrap().atom(0).unwrap();
+    assert_eq!(sodium.element(), Element::Na);
+    assert_eq!(sodium.degree(), 0);
+
+    // Atoms are indexed by u16, which a formula can outgrow.
+    let inchi: InChI = "InChI=1S/C70000".parse().unwrap();
+    let component = inchi.components().next().unwrap();
+    assert_eq!(
+        component.elements(),
+        Err(ComponentError::TooManyAtoms { component: 0, atoms: 70000 })
+    );
+    assert!(component.atom(0).is_none());
+}
+
+#[test]
+fn test_atom_hydrogen_ranges() {
+    // Acetic acid: the acidic hydrogen moves between the two oxygens.
+    let inchi: InChI = "InChI=1S/C2H4O2/c1-2(3)4/h1H3,(H,3,4)".parse().unwrap();
+    let component = inchi.components().next().unwrap();
+    let methyl = component.atom(0).unwrap();
+    assert_eq!((methyl.min_hydrogens(), methyl.max_hydrogens()), (3, 3));
+    assert_eq!(methyl.mobile_group_index(), None);
+    for index in [2, 3] {
+        let oxygen = component.atom(index).unwrap();
+        assert_eq!((oxygen.min_hydrogens(), oxygen.max_hydrogens()), (0, 1));
+        assert_eq!(oxygen.mobile_group_index(), Some(0));
+        assert_eq!(oxygen.mobile_group().unwrap().atoms(), [2, 3]);
+        assert!(!oxygen.can_carry_negative_charge());
+    }
+
+    // A charged mobile group, next to a second, neutral one.
+    let inchi: InChI = "InChI=1S/C4H10/c1-3-4-2/h1-2H2,(H-,1,3)(H2,2,4)/q-1".parse().unwrap();
+    let component = inchi.components().next().unwrap();
+    let first = component.atom(0).unwrap();
+    assert_eq!((first.min_hydrogens(), first.max_hydrogens()), (2, 3));
+    assert!(first.can_carry_negative_charge());
+    // The terminal CH2 has room for one more hydrogen, the other atom of
+    // its group for two.
+    let second = component.atom(1).unwrap();
+    assert_eq!(second.mobile_group_index(), Some(1));
+    assert_eq!((second.min_hydrogens(), second.max_hydrogens()), (2, 3));
+    assert!(!second.can_carry_negative_charge());
+
+    // Acetamide: the oxygen has room for one of the two mobile hydrogens, so
+    // the nitrogen takes at least the other one.
+    let inchi: InChI = "InChI=1S/C2H5NO/c1-2(3)4/h1H3,(H2,3,4)".parse().unwrap();
+    let component = inchi.components().next().unwrap();
+    let nitrogen = component.atom(2).unwrap();
+    assert_eq!((nitrogen.min_hydrogens(), nitrogen.max_hydrogens()), (1, 2));
+    let oxygen = component.atom(3).unwrap();
+    assert_eq!((oxygen.min_hydrogens(), oxygen.max_hydrogens()), (0, 1));
+
+    // Without valences to limit it, a metal may take the whole group.
+    let inchi: InChI = "InChI=1S/CH2NNa/c2-1-3/h(H2,2,3)".parse().unwrap();
+    let component = inchi.components().next().unwrap();
+    let sodium = component.atom(2).unwrap();
+    assert_eq!((sodium.min_hydrogens(), sodium.max_hydrogens()), (0, 2));
+}
@@ -1,29 +1,30 @@
 //! Tests for invalid InChI strings
 
 use inchi_parser::{
-    errors::{AtomConnectionTokenError, Error, HydrogenLayerTokenError},
-    inchi::InChI,
+    errors::{AtomConnectionTokenError, Error, HydrogenLayerTokenError, LayerId, ParseError},
+    inchi::{AnyInChI, InChI},
+    version::Version1_07_4,
 };
 use molecular_formulas::errors::ParserError;
 
 #[test]
 fn test_missing_inchi_prefix() {
     let inchi_str = "1S/C2H6O/c1-2-3/h3H,2H2,1H3"; // Missing "InChI=" prefix
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert!(matches!(result, Err(Error::MissingInchiPrefix)));
 }
 
 #[test]
 fn test_missing_version_prefix() {
     let inchi_str = "InChI=/C2H6O/c1-2-3/h3H,2H2,1H3"; // Missing version prefix
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert!(matches!(result, Err(Error::MissingVersionPrefix)));
 }
 
 #[test]
 fn test_missing_forward_slash() {
     let inchi_str = "InChI=1SC2H6O/c1-2-3/h3H,2H2,1H3"; // Missing '/' after version
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert!(matches!(result, Err(Error::MissingForwardSlash(_))));
 }
 
@@ -31,21 +32,21 @@ fn test_missing_forward_slash() {
 fn test_formula_only_inchi() {
     // A formula-only InChI (no /c or /h layers) is valid.
     let inchi_str = "InChI=1S/C2H6O";
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert!(result.is_ok(), "Formula-only InChI should parse successfully: {result:?}");
 }
 
 #[test]
 fn test_not_hill_sorted() {
     let inchi_str = "InChI=1S/C2OH6/";
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert!(matches!(result, Err(Error::MolecularFormulaParserError(ParserError::NotHillOrdered))));
 }
 
 #[test]
 fn test_unbalanced_parenthesis() {
     let inchi_str = "InChI=1S/C2H6O/c1)/";
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert_eq!(
         result,
         Err(Error::AtomConnectionTokenError(
@@ -57,7 +58,7 @@ fn test_unbalanced_parenthesis() {
 #[test]
 fn test_self_loop_comma() {
     let inchi_str = "InChI=1S/C16H25NS/c1-12(2)13-7-5-8-15(3)9-6-10-16(4,16(13)15)17-11-18/h13-14H,1,5-10H2,2-4H3/t13-,14-,15+,16-/m1/s1";
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert_eq!(
         result,
         Err(Error::AtomConnectionTokenError(AtomConnectionTokenError::SelfLoopDetected(16)))
@@ -67,7 +68,7 @@ fn test_self_loop_comma() {
 #[test]
 fn test_self_loop_dash() {
     let inchi_str = "InChI=1S/C16H25NS/c1-12(2)13-7-5-5-15(3)9-6-10-16(4,16(13)15)17-11-18/h13-14H,1,5-10H2,2-4H3/t13-,14-,15+,16-/m1/s1";
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert_eq!(
         result,
         Err(Error::AtomConnectionTokenError(AtomConnectionTokenError::SelfLoopDetected(5)))
@@ -79,18 +80,104 @@ fn test_self_loop_parenthesis() {
     // The string has two self-loops: `12(12)` and `5-5`.
     // The parser now encounters `12(12)` first (parenthesis-based self-loop).
     let inchi_str = "InChI=1S/C16H25NS/c1-12(12)13-7-5-5-15(3)9-6-10-16(4,16(13)15)17-11-18/h13-14H,1,5-10H2,2-4H3/t13-,14-,15+,16-/m1/s1";
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert_eq!(
         result,
         Err(Error::AtomConnectionTokenError(AtomConnectionTokenError::SelfLoopDetected(12)))
     );
 }
 
+#[test]
+fn test_connection_out_of_bounds_atom_index() {
+    // C2H6O has 3 non-hydrogen atoms, so atom index 4 is out of bounds.
+    let inchi_str = "InChI=1S/C2H6O/c1-2-4";
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
+    assert_eq!(
+        result,
+        Err(Error::AtomConnectionTokenError(AtomConnectionTokenError::AtomIndexOutOfBounds {
+            index: 4,
+            num_atoms: 3,
+        }))
+    );
+}
+
+#[test]
+fn test_connection_out_of_bounds_in_second_component() {
+    let inchi_str = "InChI=1S/C2H6.CH4/c1-2;2-1";
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
+    assert_eq!(
+        result,
+        Err(Error::AtomConnectionTokenError(AtomConnectionTokenError::AtomIndexOutOfBounds {
+            index: 2,
+            num_atoms: 1,
+        }))
+    );
+}
+
+#[test]
+fn test_connection_duplicate_ring_closure() {
+    // The ring closure 2-1 repeats the bond 1-2.
+    let inchi_str = "InChI=1S/C2H6/c1-2-1";
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
+    assert_eq!(
+        result,
+        Err(Error::AtomConnectionTokenError(AtomConnectionTokenError::DuplicateEdge(1, 2)))
+    );
+}
+
+#[test]
+fn test_connection_duplicate_branch() {
+    let inchi_str = "InChI=1S/C3H8/c1-2(3)3";
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
+    assert_eq!(
+        result,
+        Err(Error::AtomConnectionTokenError(AtomConnectionTokenError::DuplicateEdge(2, 3)))
+    );
+}
+
+#[test]
+fn test_connection_isolated_atom() {
+    let inchi_str = "InChI=1S/C3H8O/c1-2-3";
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
+    assert_eq!(
+        result,
+        Err(Error::AtomConnectionTokenError(AtomConnectionTokenError::IsolatedAtom(4)))
+    );
+}
+
+#[test]
+fn test_connection_empty_multi_atom_component() {
+    let inchi_str = "InChI=1S/C2H6.CH4/c1-2;/h1-2H3;1H4";
+    assert!(inchi_str.parse::<InChI>().is_ok());
+    let inchi_str = "InChI=1S/C2H6.CH4/c;/h1-2H3;1H4";
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
+    assert_eq!(
+        result,
+        Err(Error::AtomConnectionTokenError(AtomConnectionTokenError::IsolatedAtom(1)))
+    );
+}
+
+#[test]
+fn test_connection_layer_without_heavy_atoms() {
+    let inchi_str = "InChI=1S/H2/c1-2";
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
+    assert!(
+        matches!(
+            result,
+            Err(Error::AtomConnectionTokenError(AtomConnectionTokenError::AtomIndexOutOfBounds {
+                num_atoms: 0,
+                ..
+            }))
+        ),
+        "{result:?}"
+    );
+}
+
 #[test]
 fn test_h_layer_out_of_bounds_atom_index() {
     // C2H6 has 2 non-hydrogen atoms, so atom index 4 is out of bounds.
     let inchi_str = "InChI=1S/C2H6/c1-2/h4H";
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert_eq!(
         result,
         Err(Error::HydrogenLayerTokenError(HydrogenLayerTokenError::AtomIndexOutOfBounds {
@@ -100,11 +187,28 @@ fn test_h_layer_out_of_bounds_atom_index() {
     );
 }
 
+#[test]
+fn test_stereo_and_isotope_out_of_bounds_atom_index() {
+    for (inchi_str, layer, component, index, num_atoms) in [
+        ("InChI=1S/CH4/h1H4/t9-", LayerId::TetrahedralStereo, 0, 9, 1),
+        ("InChI=1S/C2H4.CH4/c1-2;/h1-2H2;1H4/b1-3+;", LayerId::DoubleBondStereo, 0, 3, 2),
+        ("InChI=1S/CH4.C2H6/c;1-2/h1H4;1-2H3/i;3+1", LayerId::Isotope, 1, 3, 2),
+        ("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/i2D/t4-/m1/s1", LayerId::TetrahedralStereo, 0, 4, 3),
+    ] {
+        let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
+        assert_eq!(
+            result,
+            Err(Error::AtomIndexOutOfBounds { layer, component, index, num_atoms }),
+            "{inchi_str}"
+        );
+    }
+}
+
 #[test]
 fn test_h_layer_reversed_range() {
     // Range 5-3 is invalid (start > end).
     let inchi_str = "InChI=1S/C6H12O6/c7-1-2(8)5-3(9)4(10)6(11)12-5/h5-3H";
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert_eq!(
         result,
         Err(Error::HydrogenLayerTokenError(HydrogenLayerTokenError::InvalidRange(5, 3)))
@@ -115,7 +219,7 @@ fn test_h_layer_reversed_range() {
 fn test_unrecognized_layer_prefix() {
     // "xGARBAGE" is not a known layer prefix.
     let inchi_str = "InChI=1S/CH4/xGARBAGE";
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert_eq!(result, Err(Error::UnrecognizedLayerPrefix('x')));
 }
 
@@ -123,7 +227,7 @@ fn test_unrecognized_layer_prefix() {
 fn test_valid_unimplemented_layers_still_parse() {
     // InChI with charge and stereo layers should parse without error.
     let inchi_str = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/q+1/b2-3+";
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert!(result.is_ok(), "Known layers after main layer should be accepted: {result:?}");
 }
 
@@ -131,9 +235,218 @@ fn test_valid_unimplemented_layers_still_parse() {
 fn test_formula_more_components_than_h_layer() {
     // Formula has 2 components (CH4.C2H6) but /h provides only 1.
     let inchi_str = "InChI=1S/CH4.C2H6/h1H";
-    let result = inchi_str.parse::<InChI>();
+    let result = inchi_str.parse::<InChI>().map_err(ParseError::into_kind);
     assert!(
         matches!(result, Err(Error::FormulaAndConnectionLayerMixtureMismatch(_))),
         "Should error when formula has more components than /h layer: {result:?}"
     );
 }
+
+#[test]
+fn test_version_mismatch() {
+    // A non-standard InChI is not a standard one, and vice versa.
+    let result = "InChI=1/C2H6O/c1-2-3/h3H,2H2,1H3".parse::<InChI>().map_err(ParseError::into_kind);
+    assert!(matches!(result, Err(Error::MissingVersionPrefix)));
+    let result = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3"
+        .parse::<InChI<Version1_07_4>>()
+        .map_err(ParseError::into_kind);
+    assert!(matches!(result, Err(Error::MissingForwardSlash(_))));
+}
+
+#[test]
+fn test_any_inchi_unknown_version() {
+    for inchi_str in ["InChI=2S/C2H6O", "InChI=1SC2H6O", "InChI=/C2H6O", "C2H6O"] {
+        let result = inchi_str.parse::<AnyInChI>().map_err(ParseError::into_kind);
+        assert!(
+            matches!(result, Err(Error::MissingVersionPrefix | Error::MissingInchiPrefix)),
+            "{inchi_str}: {result:?}"
+        );
+    }
+}
+
+#[test]
+fn test_error_location_in_main_layer() {
+    let inchi_str = "InChI=1S/C2H6O/c1)/h3H,2H2,1H3";
+    let error = inchi_str.parse::<InChI>().unwrap_err();
+    assert_eq!(error.layer(), LayerId::Connections);
+    assert_eq!(&inchi_str[error.span()], ")");
+    assert_eq!(error.span(), 17..18);
+    assert_eq!(error.code(), "connection_unbalanced_bracket");
+}
+
+#[test]
+fn test_error_location_on_faulty_bytes() {
+    // Each error is located on the bytes at fault, not on its whole layer.
+    for (inchi_str, layer, faulty) in [
+        ("InChI=1S/C2H3O2.Na/c1-2(3)4;/h1H3,(H,3,4);/q-1;m", LayerId::Charge, "m"),
+        ("InChI=1S/C2H6O/c1-2-2/h3H,2H2,1H3", LayerId::Connections, "2"),
+        ("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1/q+1", LayerId::Hydrogens, "1"),
+        ("InChI=1S/C2H6O/c1-2-3/h3H,4H2,1H3", LayerId::Hydrogens, "4"),
+        ("InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/i1+1,2+x", LayerId::Isotope, "x"),
+        (
+            "InChI=1S/C4H10O/c1-3-4(2)5/h4-5H,3H2,1-2H3/t4-,9+/m0/s1",
+            LayerId::TetrahedralStereo,
+            "9+",
+        ),
+    ] {
+        let error = inchi_str.parse::<InChI>().unwrap_err();
+        assert_eq!(error.layer(), layer, "{inchi_str}");
+        assert_eq!(&inchi_str[error.span()], faulty, "{inchi_str}");
+    }
+}
+
+#[test]
+fn test_error_location_in_stereo_sublayer() {
+    let inchi_str = "InChI=1S/C6H8O6/c7-1-2(8)5-3(9)4(10)6(11)12-5/h2,5,7-10H,1H2/t2-,5+/m0/s9";
+    let error = inchi_str.parse::<InChI>().unwrap_err();
+    assert_eq!(error.layer(), LayerId::StereoType);
+    assert_eq!(&inchi_str[error.span()], "9");
+    assert_eq!(error.code(), "invalid_stereo");
+}
+
+#[test]
+fn test_error_location_in_formula() {
+    let inchi_str = "InChI=1S/C2OH6/c1-2-3";
+    let error = inchi_str.parse::<InChI>().unwrap_err();
+    assert_eq!(error.layer(), LayerId::Formula);
+    assert_eq!(&inchi_str[error.span()], "C2OH6");
+}
+
+#[test]
+fn test_error_location_in_isotopic_hydrogens() {
+    let inchi_str = "InChI=1S/H2O/h1H2/i/hDx";
+    let error = inchi_str.parse::<InChI>().unwrap_err();
+    assert_eq!(error.layer(), LayerId::Hydrogens);
+    assert_eq!(&inchi_str[error.span()], "x");
+    assert_eq!(error.code(), "invalid_isotope");
+}
+
+#[test]
+fn test_error_location_in_fixed_hydrogen_layer() {
+    let inchi_str = "InChI=1/C2H6O/c1-2-3/h3H,2H2,1H3/f/h3H/qx";
+    let error = inchi_str.parse::<InChI<Version1_07_4>>().unwrap_err();
+    assert_eq!(error.layer(), LayerId::Charge);
+    assert_eq!(&inchi_str[error.span()], "x");
+}
+
+#[test]
+fn test_error_location_in_reconnected_layer() {
+    let inchi_str = "InChI=1/C2H6O/c1-2-3/h3H,2H2,1H3/rC2H6O/c1-2-3/h3x";
+    let error = inchi_str.parse::<InChI<Version1_07_4>>().unwrap_err();
+    assert_eq!(error.layer(), LayerId::Hydrogens);
+    assert_eq!(&inchi_str[error.span()], "x");
+}
+
+#[test]
+fn test_error_location_of_unrecognized_layer() {
+    let inchi_str = "InChI=1S/CH4/h1H4/xGARBAGE";
+    let error = inchi_str.parse::<InChI>().unwrap_err();
+    assert_eq!(error.layer(), LayerId::Unknown);
+    assert_eq!(&inchi_str[error.span()], "xGARBAGE");
+    assert_eq!(error.code(), "unrecognized_layer_prefix");
+}
+
+#[test]
+fn test_error_location_of_version() {
+    let error = "InChI=2S/CH4".parse::<InChI>().unwrap_err();
+    assert_eq!(error.layer(), LayerId::Version);
+    assert_eq!(error.span(), 6..8);
+    let error = "CH4".parse::<AnyInChI>().unwrap_err();
+    assert_eq!(error.layer(), LayerId::Version);
+    assert_eq!(error.span(), 0..0);
+}
+
+#[test]
+fn test_error_render() {
+    let inchi_str = "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/t2?/s9";
+    let error = inchi_str.parse::<InChI>().unwrap_err();
+    assert_eq!(
+        error.render(inchi_str),
+        concat!(
+            "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/t2?/s9\n",
+            "                                       ^ invalid_stereo: Invalid stereo value: '9'"
+        )
+    );
+}
+
+#[test]
+fn test_layer_order_errors() {
+    let cases = [
+        (
+            "InChI=1S/C4H8/c1-3-4-2/h3-4H,1-2H3/t3-/b4-3+",
+            Error::UnexpectedLayerOrder {
+                layer: LayerId::DoubleBondStereo,
+                previous: LayerId::TetrahedralStereo,
+            },
+            "b4-3+",
+        ),
+        (
+            "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/i2D/q+1",
+            Error::UnexpectedLayerOrder { layer: LayerId::Charge, previous: LayerId::Isotope },
+            "q+1",
+        ),
+        (
+            "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/q-1/q+1",
+            Error::DuplicateLayer { layer: LayerId::Charge, previous: LayerId::Charge },
+            "q+1",
+        ),
+        (
+            "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/q-1/p+1/q+1",
+            Error::DuplicateLayer { layer: LayerId::Charge, previous: LayerId::Protons },
+            "q+1",
+        ),
+        (
+            "InChI=1S/C2H6O/h3H,2H2,1H3/c1-2-3",
+            Error::UnexpectedLayerOrder {
+                layer: LayerId::Connections,
+                previous: LayerId::Hydrogens,
+            },
+            "c1-2-3",
+        ),
+        (
+            "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3/i2D/i3D",
+            Error::DuplicateLayer { layer: LayerId::Isotope, previous: LayerId::Isotope },
+            "i3D",
+        ),
+    ];
+    for (inchi_str, expected, segment) in cases {
+        let error = inchi_str.parse::<InChI>().unwrap_err();
+        assert_eq!(error.kind(), &expected, "{inchi_str}");
+        assert_eq!(&inchi_str[error.span()], segment, "{inchi_str}");
+    }
+}
+
+#[test]
+fn test_fixed_hydrogen_layer_order_errors() {
+    let inchi_str = "InChI=1/CH2O2/c2-1-3/h1H,(H,2,3)/f/h2H/f/h3H";
+    let error = inchi_str.parse::<InChI<Version1_07_4>>().unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &Error::DuplicateLayer { layer: LayerId::FixedHydrogen, previous: LayerId::Hydrogens }
+    );
+
+    let inchi_str = "InChI=1/CH2O2/c2-1-3/h1H,(H,2,3)/f/h2H/o(1,2)/i1+1";
+    let error = inchi_str.parse::<InChI<Version1_07_4>>().unwrap_err();
+    assert_eq!(
+        error.kind(),
+        &Error::UnexpectedLayerOrder { layer: LayerId::Isotope, previous: LayerId::Transposition }
+    );
+}
+
+#[test]
+fn test_charge_entries_must_match_components() {
+    for inchi_str in [
+        "InChI=1S/2ClH.Ca/h2*1H;/q;+2",
+        "InChI=1S/2ClH.Ca/h2*1H;/q;;;+2",
+        "InChI=1S/2ClH.Ca/h2*1H;/q3*+1;+2",
+    ] {
+        let error = inchi_str.parse::<InChI>().unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &Error::FormulaAndConnectionLayerMixtureMismatch(3),
+            "{inchi_str}"
+        );
+        assert_eq!(error.layer(), LayerId::Charge, "{inchi_str}");
+    }
+    assert!("InChI=1S/2ClH.Ca/h2*1H;/q2*-1;+2".parse::<InChI>().is_ok());
+}
@@ -18,7 +18,7 @@ use std::{
 
 use csv::ReaderBuilder;
 use flate2::read::GzDecoder;
-use inchi_parser::inchi::InChI;
+use inchi_parser::{inchi::InChI, inchi_key::InChIKey};
 use indicatif::{ProgressBar, ProgressStyle};
 use serde::Deserialize;
 
@@ -80,7 +80,8 @@ async fn ensure_pubchem_file(file_path: &Path) -> Result<(), Box<dyn std::error:
     Ok(())
 }
 
-/// Read and validate PubChem data from the CID-InChI-Key file.
+/// Read and validate PubChem data from the CID-InChI-Key file, checking both
+/// the parsing of each InChI and the InChIKey generated from it.
 fn validate_pubchem_inchi(file_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
     let file = File::open(file_path)?;
     let decoder = GzDecoder::new(file);
@@ -110,9 +111,21 @@ fn validate_pubchem_inchi(file_path: &Path) -> Result<(), Box<dyn std::error::Er
         pb.inc(1);
 
         match compound.inchi.parse::<InChI>() {
-            Ok(_) => {}
+            Ok(inchi) => {
+                let matches = compound
+                    .inchi_key
+                    .parse::<InChIKey>()
+                    .is_ok_and(|inchi_key| inchi_key.matches(&inchi));
+                if !matches {
+                    let entry = error_examples.entry("InChIKey mismatch".to_string()).or_default();
+                    if entry.len() < 2 {
+                        entry.push(compound.inchi);
+                    }
+                }
+            }
             Err(e) => {
-                let error_key = e.to_string();
+                // Group by kind and layer: the full message includes the span.
+                let error_key = format!("{} in the {}", e.kind(), e.layer());
                 let entry = error_examples.entry(error_key).or_default();
                 if entry.len() < 2 {
                     entry.push(compound.inchi);